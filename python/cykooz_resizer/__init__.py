@@ -3,5 +3,6 @@
 :Date: 20.03.2021
 """
 from .alpha import AlphaMulDiv
-from .resize import Resizer
+from .color import ColorMapper
+from .resize import ResizePlan, Resizer, StreamingResizer
 from .structs import *
@@ -2,7 +2,7 @@
 :Authors: cykooz
 :Date: 02.08.2021
 """
-from typing import Optional
+from typing import Optional, Tuple
 
 
 try:
@@ -32,21 +32,35 @@ class AlphaMulDiv:
             src_image: ImageData,
             dst_image: ImageData,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
-        self._rust_alpha_mul_div.multiply_alpha(
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
+        """Multiplies RGB-channels of the source image by alpha-channel
+        and stores the result into the destination image.
+
+        :return: `True` if the source image is opaque, so its pixels
+                 are copied without multiplication.
+        """
+        return self._rust_alpha_mul_div.multiply_alpha(
             src_image.rust_image,
             dst_image.rust_image,
             thread_pool,
+            threading_threshold,
         )
 
     def multiply_alpha_inplace(
             self,
             image: ImageData,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
-        self._rust_alpha_mul_div.multiply_alpha_inplace(
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
+        """Multiplies RGB-channels of the image by alpha-channel inplace.
+
+        :return: `True` if the image is opaque, so it is not changed.
+        """
+        return self._rust_alpha_mul_div.multiply_alpha_inplace(
             image.rust_image,
             thread_pool,
+            threading_threshold,
         )
 
     def divide_alpha(
@@ -54,27 +68,42 @@ class AlphaMulDiv:
             src_image: ImageData,
             dst_image: ImageData,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
-        self._rust_alpha_mul_div.divide_alpha(
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
+        """Divides RGB-channels of the source image by alpha-channel
+        and stores the result into the destination image.
+
+        :return: `True` if the source image is opaque, so its pixels
+                 are copied without division.
+        """
+        return self._rust_alpha_mul_div.divide_alpha(
             src_image.rust_image,
             dst_image.rust_image,
             thread_pool,
+            threading_threshold,
         )
 
     def divide_alpha_inplace(
             self,
             image: ImageData,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
-        self._rust_alpha_mul_div.divide_alpha_inplace(
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
+        """Divides RGB-channels of the image by alpha-channel inplace.
+
+        :return: `True` if the image is opaque, so it is not changed.
+        """
+        return self._rust_alpha_mul_div.divide_alpha_inplace(
             image.rust_image,
             thread_pool,
+            threading_threshold,
         )
 
     def multiply_alpha_pil(
             self,
             image: 'PilImage.Image',
             thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
     ) -> 'PilImage.Image':
         if image.mode == 'RGBa':
             return image.copy()
@@ -88,6 +117,7 @@ class AlphaMulDiv:
             src_view,
             dst_view,
             thread_pool,
+            threading_threshold,
         )
         return dst_img
 
@@ -95,7 +125,14 @@ class AlphaMulDiv:
             self,
             image: 'PilImage.Image',
             thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+            region: Optional[Tuple[int, int, int, int]] = None,
     ):
+        """Multiplies RGB-channels of the PIL image by alpha-channel inplace.
+
+        Only pixels of the given region `(left, top, width, height)`
+        are changed if it is set, but the mode of the whole image is changed.
+        """
         if image.mode == 'RGBa':
             return
         elif image.mode != 'RGBA':
@@ -106,6 +143,8 @@ class AlphaMulDiv:
         self._rust_alpha_mul_div.multiply_alpha_pil_inplace(
             image_view,
             thread_pool,
+            threading_threshold,
+            region,
         )
         set_image_mode(image, 'RGBa')
 
@@ -113,6 +152,7 @@ class AlphaMulDiv:
             self,
             image: 'PilImage.Image',
             thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
     ) -> 'PilImage.Image':
         if image.mode == 'RGBA':
             return image.copy()
@@ -125,6 +165,7 @@ class AlphaMulDiv:
             src_view,
             dst_view,
             thread_pool,
+            threading_threshold,
         )
         return dst_img
 
@@ -132,7 +173,14 @@ class AlphaMulDiv:
             self,
             image: 'PilImage.Image',
             thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+            region: Optional[Tuple[int, int, int, int]] = None,
     ):
+        """Divides RGB-channels of the PIL image by alpha-channel inplace.
+
+        Only pixels of the given region `(left, top, width, height)`
+        are changed if it is set, but the mode of the whole image is changed.
+        """
         if image.mode == 'RGBA':
             return
         elif image.mode != 'RGBa':
@@ -143,9 +191,48 @@ class AlphaMulDiv:
         self._rust_alpha_mul_div.divide_alpha_pil_inplace(
             image_view,
             thread_pool,
+            threading_threshold,
+            region,
         )
         set_image_mode(image, 'RGBA')
 
+    def bleed_alpha_pil(
+            self,
+            image: 'PilImage.Image',
+            iterations: Optional[int] = None,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'PilImage.Image':
+        """Returns a copy of RGBA image with RGB-channels of fully
+        transparent pixels filled with colors of the nearest visible
+        pixels (see `ImageData.bleed_alpha()`)."""
+        image = image.copy()
+        self.bleed_alpha_pil_inplace(image, iterations, thread_pool, threading_threshold)
+        return image
+
+    def bleed_alpha_pil_inplace(
+            self,
+            image: 'PilImage.Image',
+            iterations: Optional[int] = None,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Fills RGB-channels of fully transparent pixels of RGBA image
+        inplace with colors of the nearest visible pixels."""
+        if image.mode != 'RGBA':
+            raise ValueError('Unsupported mode of source image.')
+        if iterations is not None and iterations < 1:
+            raise ValueError('Count of iterations must be greater than zero')
+        if image.readonly:
+            image._copy()
+        image_view = PilImageWrapper(image)
+        self._rust_alpha_mul_div.bleed_alpha_pil_inplace(
+            image_view,
+            iterations,
+            thread_pool,
+            threading_threshold,
+        )
+
 
 def set_image_mode(image: 'PilImage.Image', mode: str):
     if hasattr(image, '_mode'):
@@ -0,0 +1,165 @@
+"""
+:Authors: cykooz
+:Date: 18.10.2026
+"""
+from typing import Optional
+
+
+try:
+    from PIL import Image as PilImage
+except ImportError:
+    PilImage = None
+
+from .rust_lib import PilImageWrapper, ResizerThreadPool, RustColorMapper
+from .structs import ImageData, TransferFunction
+
+
+SUPPORTED_PIL_MODES = ('RGB', 'RGBA', 'L', 'F')
+
+
+def _check_pil_mode(image: 'PilImage.Image'):
+    if image.mode not in SUPPORTED_PIL_MODES:
+        raise ValueError(f'"{image.mode}" is unsupported mode of PIL image')
+
+
+class ColorMapper:
+    """Maps values of pixel components between a colorspace with
+    the given transfer function and linear colorspace.
+
+    Source and destination images may have different types of pixel
+    components (U8, U16 or F32), but count of components must be equal.
+    Components of F32 images are expected in the range [0.0, 1.0].
+    Alpha channel is not mapped, it is only converted into the type of
+    components of the destination image.
+    """
+
+    def __init__(self, transfer_function: TransferFunction = TransferFunction.srgb):
+        self._rust_color_mapper = RustColorMapper(transfer_function.value)
+
+    @property
+    def transfer_function(self) -> TransferFunction:
+        return TransferFunction(self._rust_color_mapper.get_transfer_function())
+
+    def forward_map(
+            self,
+            src_image: ImageData,
+            dst_image: ImageData,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps the source image into linear colorspace and stores
+        the result into the destination image."""
+        self._rust_color_mapper.forward_map(
+            src_image.rust_image,
+            dst_image.rust_image,
+            thread_pool,
+            threading_threshold,
+        )
+
+    def forward_map_inplace(
+            self,
+            image: ImageData,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps the image into linear colorspace inplace."""
+        self._rust_color_mapper.forward_map_inplace(
+            image.rust_image,
+            thread_pool,
+            threading_threshold,
+        )
+
+    def backward_map(
+            self,
+            src_image: ImageData,
+            dst_image: ImageData,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps the source image from linear colorspace and stores
+        the result into the destination image."""
+        self._rust_color_mapper.backward_map(
+            src_image.rust_image,
+            dst_image.rust_image,
+            thread_pool,
+            threading_threshold,
+        )
+
+    def backward_map_inplace(
+            self,
+            image: ImageData,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps the image from linear colorspace inplace."""
+        self._rust_color_mapper.backward_map_inplace(
+            image.rust_image,
+            thread_pool,
+            threading_threshold,
+        )
+
+    def forward_map_pil(
+            self,
+            src_image: 'PilImage.Image',
+            dst_image: 'PilImage.Image',
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        _check_pil_mode(src_image)
+        _check_pil_mode(dst_image)
+        src_image.load()
+        self._rust_color_mapper.forward_map_pil(
+            PilImageWrapper(src_image),
+            PilImageWrapper(dst_image),
+            thread_pool,
+            threading_threshold,
+        )
+
+    def forward_map_pil_inplace(
+            self,
+            image: 'PilImage.Image',
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        _check_pil_mode(image)
+        image.load()
+        if image.readonly:
+            image._copy()
+        self._rust_color_mapper.forward_map_pil_inplace(
+            PilImageWrapper(image),
+            thread_pool,
+            threading_threshold,
+        )
+
+    def backward_map_pil(
+            self,
+            src_image: 'PilImage.Image',
+            dst_image: 'PilImage.Image',
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        _check_pil_mode(src_image)
+        _check_pil_mode(dst_image)
+        src_image.load()
+        self._rust_color_mapper.backward_map_pil(
+            PilImageWrapper(src_image),
+            PilImageWrapper(dst_image),
+            thread_pool,
+            threading_threshold,
+        )
+
+    def backward_map_pil_inplace(
+            self,
+            image: 'PilImage.Image',
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        _check_pil_mode(image)
+        image.load()
+        if image.readonly:
+            image._copy()
+        self._rust_color_mapper.backward_map_pil_inplace(
+            PilImageWrapper(image),
+            thread_pool,
+            threading_threshold,
+        )
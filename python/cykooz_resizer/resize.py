@@ -2,7 +2,7 @@
 :Authors: cykooz
 :Date: 02.08.2021
 """
-from typing import Optional
+from typing import Callable, Iterable, Iterator, List, Optional, Sequence, Tuple, Union
 
 
 try:
@@ -11,8 +11,28 @@ except ImportError:
     PilImage = None
 
 from .alpha import AlphaMulDiv, set_image_mode
-from .rust_lib import PilImageWrapper, RustResizer
-from .structs import Algorithm, CpuExtensions, ImageData, ResizeOptions
+from .rust_lib import PilImageWrapper, RustResizePlan, RustResizer, RustStreamingResizer
+from .structs import (
+    Algorithm,
+    CpuExtensions,
+    FilterType,
+    ImageData,
+    PixelType,
+    ResizeOptions,
+    ResizeReport,
+    TileLayout,
+)
+
+
+# Types of pixels of images that are used to cut tiles
+# from PIL images with corresponding modes.
+_PIL_TILE_PIXEL_TYPES = {
+    'L': PixelType.U8,
+    'RGB': PixelType.U8x3,
+    'RGBA': PixelType.U8x4,
+    'I': PixelType.I32,
+    'F': PixelType.F32,
+}
 
 
 class Resizer:
@@ -35,24 +55,164 @@ class Resizer:
             src_image: ImageData,
             dst_image: ImageData,
             options: Optional[ResizeOptions] = None
-    ):
+    ) -> ResizeReport:
         """Resize source image into size of destination image and store result
         into buffer of destination image.
+
+        Images may have different types of pixels with the same count
+        of components (e.g. U16x4 and U8x4).
+
+        :return: A report about the performed resizing.
         """
-        self._rust_resizer.resize(
+        return self._rust_resizer.resize(
             src_image.rust_image,
             dst_image.rust_image,
             options.rust_options if options else None,
         )
 
+    def resize_multi(
+            self,
+            src_image: ImageData,
+            dst_images: Sequence[ImageData],
+            options: Optional[ResizeOptions] = None
+    ) -> List[ResizeReport]:
+        """Resize source image into sizes of several destination images
+        and store results into buffers of destination images.
+
+        Destination images are resized from the largest to the smallest one.
+        A destination image is resized from an already resized image instead
+        of the source image if the resized image is at least two times larger,
+        has the same type of pixels as the source image, and options allow it
+        (only the convolution algorithm without cropping, padding, a region
+        of the destination image, border modes, linear colorspace, dithering,
+        channel operations and a background).
+
+        Destination images resized from the source image with the same
+        height (for images with U8 components) or the same width (for other
+        images) share the first pass of the convolution, if options allow
+        cascading and pixels are resized without processing of alpha channel
+        (images without alpha channel or with `use_alpha=False`) and
+        detection of grayscale images. Results are the same as resizing
+        of each image separately.
+
+        Destination images which don't depend on each other are resized
+        in parallel if `options` have a thread-pool.
+
+        :return: Reports about the performed resizing of destination images.
+        """
+        return self._rust_resizer.resize_multi(
+            src_image.rust_image,
+            [image.rust_image for image in dst_images],
+            options.rust_options if options else None,
+        )
+
+    def build_pyramid(
+            self,
+            src_image: ImageData,
+            levels: Optional[int] = None,
+            filter_type: FilterType = FilterType.box,
+            min_size: int = 1,
+            options: Optional[ResizeOptions] = None
+    ) -> List[ImageData]:
+        """Build levels of an image pyramid (mipmaps) from the source image.
+
+        Each level is a half of the previous one (rounded down, but not less
+        than one pixel) and is resized from it by the convolution with
+        the given filter. Levels are built until the count of levels
+        is reached or the larger dimension of the last level is not greater
+        than `min_size`.
+
+        Resize algorithm, cropping, padding and the region of the destination
+        image from options are not used. Use `ResizeOptions.linear_light`
+        to resize levels in linear colorspace. Reordering and selecting
+        of channels and the background from options are applied once
+        to every level, levels are resized from untransformed ones.
+
+        :return: Levels of the pyramid without the source image.
+        """
+        rust_images = self._rust_resizer.build_pyramid(
+            src_image.rust_image,
+            levels,
+            filter_type.value,
+            min_size,
+            options.rust_options if options else None,
+        )
+        return [ImageData._from_rust_image(image) for image in rust_images]
+
+    def tiles(
+            self,
+            src_image: ImageData,
+            tile_size: int = 256,
+            overlap: int = 0,
+            layout: TileLayout = TileLayout.deep_zoom,
+            filter_type: FilterType = FilterType.box,
+            options: Optional[ResizeOptions] = None
+    ) -> Iterator[Tuple[int, int, int, ImageData]]:
+        """Cut an image pyramid built from the source image into tiles
+        with the given size and overlap.
+
+        Levels are yielded from the level with the full size of the source
+        image to the level 0. Each level is a half of the previous one
+        (rounded up) and is resized from it by the convolution with
+        the given filter, so only one level is kept in memory.
+        Tiles of the level with the full size are cut from the source image.
+
+        Resize algorithm, cropping, padding and the region of the destination
+        image from options are not used. Reordering and selecting of channels
+        and the background from options are applied once to every level,
+        including the level with the full size. Overlap can't be used with
+        `TileLayout.xyz`, pixels of its tiles outside the level are filled
+        with the background color, if it is set, or zeros.
+
+        :return: Iterator over tuples `(level, col, row, tile)`.
+        """
+        rust_tiles = self._rust_resizer.tiles(
+            src_image.rust_image,
+            tile_size,
+            overlap,
+            layout.value,
+            filter_type.value,
+            options.rust_options if options else None,
+        )
+        for level, col, row, rust_image in rust_tiles:
+            yield level, col, row, ImageData._from_rust_image(rust_image)
+
+    def tiles_pil(
+            self,
+            src_image: 'PilImage.Image',
+            tile_size: int = 256,
+            overlap: int = 0,
+            layout: TileLayout = TileLayout.deep_zoom,
+            filter_type: FilterType = FilterType.box,
+            options: Optional[ResizeOptions] = None
+    ) -> Iterator[Tuple[int, int, int, 'PilImage.Image']]:
+        """Cut an image pyramid built from the source PIL image into tiles.
+        Tiles are PIL images with the same mode as the source image.
+
+        See `Resizer.tiles()` for details.
+
+        :return: Iterator over tuples `(level, col, row, tile)`.
+        """
+        mode = src_image.mode
+        pixel_type = _PIL_TILE_PIXEL_TYPES.get(mode)
+        if pixel_type is None:
+            raise ValueError(f'"{mode}" is unsupported mode of source PIL image')
+        image = ImageData(src_image.width, src_image.height, pixel_type, src_image.tobytes())
+        tiles = self.tiles(image, tile_size, overlap, layout, filter_type, options)
+        for level, col, row, tile in tiles:
+            pil_tile = PilImage.frombytes(mode, (tile.width, tile.height), tile.get_buffer())
+            yield level, col, row, pil_tile
+
     def resize_pil(
             self,
             src_image: 'PilImage.Image',
             dst_image: 'PilImage.Image',
             options: Optional[ResizeOptions] = None
-    ):
+    ) -> ResizeReport:
         """Resize source image into size of destination image and store result
         into buffer of destination image.
+
+        :return: A report about the performed resizing.
         """
         src_image.load()
         src_mode = src_image.mode
@@ -60,18 +220,56 @@ class Resizer:
             raise ValueError(f'"{src_mode}" is unsupported mode of source PIL image')
         dst_mode = dst_image.mode
 
-        if src_mode != dst_mode:
+        # The image is composited onto the background color before
+        # conversion into grayscale.
+        onto_gray_background = (
+                options is not None
+                and options.background is not None
+                and src_mode in ('RGBA', 'RGBa')
+                and dst_mode == 'L'
+        )
+
+        if src_mode != dst_mode and not onto_gray_background:
             if src_mode in ('CMYK', 'I', 'F', 'L') or dst_mode not in (
                     'RGB',
                     'RGBa',
                     'RGBA',
             ):
                 src_image = self._convert(src_image, dst_mode)
+                if (
+                        options
+                        and options.edge_color is not None
+                        and len(options.edge_color) == 1
+                        and src_mode == 'L'
+                        and src_image.mode in ('RGB', 'RGBA', 'RGBa')
+                ):
+                    options = options.copy()
+                    options.edge_color = options.edge_color * 3
                 src_mode = src_image.mode
 
         options = options.copy() if options else ResizeOptions()
 
-        if src_mode == 'RGBA' and dst_mode == 'RGBa':
+        if options.linear_light:
+            if src_mode not in ('RGB', 'RGBA', 'RGBa', 'L'):
+                raise ValueError(
+                    f'Resizing in linear light is unsupported for "{src_mode}" mode'
+                )
+            if src_mode == 'RGBa':
+                # Alpha must be multiplied with linear values of color components
+                src_image = self._alpha_mul_div.divide_alpha_pil(
+                    src_image,
+                    options.thread_pool,
+                )
+                src_mode = 'RGBA'
+        elif src_mode == 'RGBa' and options.background is not None:
+            # Background is composited with the premultiplied result
+            # of resizing of the image with straight alpha
+            src_image = self._alpha_mul_div.divide_alpha_pil(
+                src_image,
+                options.thread_pool,
+            )
+            src_mode = 'RGBA'
+        elif src_mode == 'RGBA' and dst_mode == 'RGBa' and options.background is None:
             resize_alg = options.resize_alg
             if resize_alg.algorithm != Algorithm.nearest:
                 src_image = self._alpha_mul_div.multiply_alpha_pil(
@@ -81,30 +279,56 @@ class Resizer:
                 src_mode = 'RGBa'
 
         src_view = PilImageWrapper(src_image)
-        set_image_mode(dst_image, src_image.mode)
+        if not onto_gray_background:
+            set_image_mode(dst_image, src_image.mode)
         dst_view = PilImageWrapper(dst_image)
 
         if src_mode == 'RGBA':
             options.use_alpha = True
         else:
             options.use_alpha = False
+            # Only RGBA images have colors of transparent pixels
+            options.bleed_alpha = False
+            options.background = None
+        if src_mode == 'RGBa' and options.padding is not None and len(options.padding) == 4:
+            # Padding is stored into the image with premultiplied alpha
+            *colors, alpha = options.padding
+            options.padding = tuple(c * alpha / 255 for c in colors) + (alpha,)
+        if src_mode == 'RGBa' and options.edge_color is not None and len(options.edge_color) == 4:
+            # Edge color is resized as pixels with premultiplied alpha
+            *colors, alpha = options.edge_color
+            options.edge_color = tuple(c * alpha / 255 for c in colors) + (alpha,)
 
-        self._rust_resizer.resize_pil(
-            src_view,
-            dst_view,
-            options.rust_options,
-        )
+        try:
+            report = self._rust_resizer.resize_pil(
+                src_view,
+                dst_view,
+                options.rust_options,
+            )
+        except Exception:
+            set_image_mode(dst_image, dst_mode)
+            raise
 
+        # Pixels outside the region of the destination image are not changed
         if src_mode == 'RGBa' and dst_mode == 'RGBA':
             self._alpha_mul_div.divide_alpha_pil_inplace(
                 dst_image,
                 options.thread_pool,
+                region=options.dst_region,
+            )
+        elif src_mode == 'RGBA' and dst_mode == 'RGBa':
+            self._alpha_mul_div.multiply_alpha_pil_inplace(
+                dst_image,
+                options.thread_pool,
+                region=options.dst_region,
             )
         elif src_mode in ('RGBa', 'RGBA') and dst_mode == 'RGB':
             set_image_mode(dst_image, 'RGB')
         elif src_mode == 'RGB' and dst_mode in ('RGBa', 'RGBA'):
             set_image_mode(dst_image, dst_mode)
 
+        return report
+
     def _convert(
             self,
             image: 'PilImage.Image',
@@ -135,3 +359,124 @@ class Resizer:
             image = image.convert('RGB')
 
         return image.convert(mode)
+
+
+class ResizePlan:
+    """Plan of resizing of many images with fixed sizes and types of pixels
+    with the same options (e.g. frames of a video).
+
+    The thread-pool is selected once while creating the plan. The plan
+    has its own resizer. Blank images with sizes and types of pixels
+    of the plan are resized while creating it, so options are checked
+    and internal buffers of the resizer are allocated only once.
+    """
+
+    def __init__(
+            self,
+            src_width: int,
+            src_height: int,
+            src_pixel_type: PixelType,
+            dst_width: int,
+            dst_height: int,
+            dst_pixel_type: Optional[PixelType] = None,
+            options: Optional[ResizeOptions] = None,
+    ):
+        if dst_pixel_type is None:
+            dst_pixel_type = src_pixel_type
+        self._rust_plan = RustResizePlan(
+            (src_width, src_height),
+            src_pixel_type.value,
+            (dst_width, dst_height),
+            dst_pixel_type.value,
+            options.rust_options if options else None,
+        )
+
+    def run(self, src_image: ImageData, dst_image: ImageData) -> ResizeReport:
+        """Resize source image into destination image. Images must have
+        sizes and types of pixels for which the plan was created.
+
+        :return: A report about the performed resizing.
+        """
+        return self._rust_plan.run(src_image.rust_image, dst_image.rust_image)
+
+
+class StreamingResizer:
+    """Resizer of images that are too large to be loaded into memory.
+
+    Rows of the source image are pushed by bands. Rows of the destination
+    image are calculated as soon as all rows of the source image used
+    by the kernel of the resize algorithm are received, and only these
+    rows of the source image are kept in memory.
+
+    Options that require the whole image (super-sampling, dithering,
+    alpha bleeding, containing in the destination image, a region
+    of the destination image, border modes and crop boxes extending
+    beyond the edges of the source image) are not supported.
+    """
+
+    def __init__(
+            self,
+            src_width: int,
+            src_height: int,
+            src_pixel_type: PixelType,
+            dst_width: int,
+            dst_height: int,
+            dst_pixel_type: Optional[PixelType] = None,
+            options: Optional[ResizeOptions] = None,
+    ):
+        if dst_pixel_type is None:
+            dst_pixel_type = src_pixel_type
+        self._rust_resizer = RustStreamingResizer(
+            (src_width, src_height),
+            src_pixel_type.value,
+            (dst_width, dst_height),
+            dst_pixel_type.value,
+            options.rust_options if options else None,
+        )
+
+    @property
+    def src_rows(self) -> int:
+        """Count of pushed rows of the source image."""
+        return self._rust_resizer.src_rows()
+
+    @property
+    def dst_rows(self) -> int:
+        """Count of calculated rows of the destination image."""
+        return self._rust_resizer.dst_rows()
+
+    def push(self, rows: ImageData) -> Optional[ImageData]:
+        """Pushes the next rows of the source image.
+
+        :return: An image with the next rows of the destination image
+                 that were calculated using the pushed rows, or `None`.
+        """
+        rust_image = self._rust_resizer.push(rows.rust_image)
+        return ImageData._from_rust_image(rust_image) if rust_image else None
+
+    def finish(self) -> Optional[ImageData]:
+        """Returns an image with the rest of rows of the destination image
+        or `None`. All rows of the source image must be pushed.
+        """
+        rust_image = self._rust_resizer.finish()
+        return ImageData._from_rust_image(rust_image) if rust_image else None
+
+    def resize(
+            self,
+            bands: Union[Iterable[ImageData], Callable[[], Optional[ImageData]]],
+    ) -> Iterator[ImageData]:
+        """Resize the source image which rows are received by bands
+        from the iterable or from the callable that returns `None`
+        after the last band.
+
+        :return: An iterator over images with the next rows
+                 of the destination image.
+        """
+        if callable(bands):
+            bands = iter(bands, None)
+        for band in bands:
+            dst_band = self.push(band)
+            if dst_band is not None:
+                yield dst_band
+        dst_band = self.finish()
+        if dst_band is not None:
+            yield dst_band
@@ -1,5 +1,5 @@
 """ This module is the python module implemented in Rust. """
-from typing import Optional, Tuple
+from typing import Iterator, List, Optional, Tuple
 
 from PIL import Image as PilImage
 
@@ -17,8 +17,117 @@ class Image:
 
     def height(self) -> int: ...
 
+    def pixel_type(self) -> int: ...
+
     def buffer(self) -> bytes: ...
 
+    def convert(
+            self,
+            pixel_type: int,
+            clamp: bool = True,
+            dithering: int = 0,
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'Image':
+        """Returns a copy of the image with pixels of the given type."""
+        ...
+
+    def convert_into(
+            self,
+            dst_image: 'Image',
+            clamp: bool = True,
+            dithering: int = 0,
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Converts pixels of the image into pixels of the destination image."""
+        ...
+
+    def swizzle(
+            self,
+            order: List[int],
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'Image':
+        """Returns a copy of the image with channels in the given order."""
+        ...
+
+    def add_alpha(
+            self,
+            value: Optional[float] = None,
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'Image':
+        """Returns a copy of the image with added alpha channel."""
+        ...
+
+    def drop_alpha(
+            self,
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'Image':
+        """Returns a copy of the image without alpha channel."""
+        ...
+
+    def to_grayscale(
+            self,
+            weights: Tuple[float, float, float],
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'Image':
+        """Returns a grayscale copy of the image with luma calculated
+        with help of the given weights of red, green and blue channels.
+
+        Alpha channel is preserved."""
+        ...
+
+    def is_grayscale(
+            self,
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
+        """Returns `True` if all pixels of the image have equal values
+        of red, green and blue channels."""
+        ...
+
+    def is_opaque(
+            self,
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
+        """Returns `True` if all pixels of the image are opaque
+        (or pixels don't have alpha channel)."""
+        ...
+
+    def bleed_alpha(
+            self,
+            iterations: Optional[int] = None,
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'Image':
+        """Returns a copy of the image with color channels of fully
+        transparent pixels filled from the nearest visible pixels."""
+        ...
+
+    def split(
+            self,
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> List['Image']:
+        """Returns images with one channel for each channel of the image."""
+        ...
+
+    @staticmethod
+    def merge(
+            channels: List['Image'],
+            pixel_type: Optional[int] = None,
+            thread_pool: Optional['ResizerThreadPool'] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'Image':
+        """Returns an image with channels from the given images
+        with one channel."""
+        ...
+
 
 class PilImageWrapper:
     def __init__(self, image: PilImage.Image): ...
@@ -28,13 +137,102 @@ class PilImageWrapper:
 
 
 class ResizerThreadPool:
-    def __init__(self, num_threads: Optional[int] = None):
+    def __init__(
+            self,
+            num_threads: Optional[int] = None,
+            thread_name_prefix: Optional[str] = None,
+            stack_size: Optional[int] = None,
+            use_current_thread: bool = False,
+            cpus: Optional[List[int]] = None,
+            affinity: Optional[str] = None,
+    ):
+        """
+        :param num_threads: Number of threads. By default, it is equal to
+                            the number of logical CPUs.
+        :param thread_name_prefix: Names of worker threads will be
+                                   "{thread_name_prefix}-{index}".
+        :param stack_size: Stack size (in bytes) of worker threads.
+        :param use_current_thread: Use the thread that creates the pool
+                                   as one of the workers. The thread stays
+                                   a worker of the pool, so other thread
+                                   pools must not be used from it (it may
+                                   hang). Create such a pool in a dedicated
+                                   thread. The thread that imported
+                                   the module can't be a worker,
+                                   a new thread is spawned instead.
+        :param cpus: List of CPUs to pin worker threads (only on Linux).
+        :param affinity: Strategy of pinning worker threads to CPUs
+                         (only on Linux): "compact" or "spread".
+                         CPUs are selected by their positions in the list,
+                         NUMA nodes and shared caches are not taken
+                         into account.
+        :raises RuntimeError: If a worker thread can't be pinned to a CPU.
+        """
         ...
 
     @property
     def current_num_threads(self) -> int:
         ...
 
+    @property
+    def num_threads(self) -> Optional[int]:
+        ...
+
+    @property
+    def thread_name_prefix(self) -> Optional[str]:
+        ...
+
+    @property
+    def stack_size(self) -> Optional[int]:
+        ...
+
+    @property
+    def use_current_thread(self) -> bool:
+        ...
+
+    @property
+    def cpus(self) -> Optional[List[int]]:
+        ...
+
+    @property
+    def affinity(self) -> Optional[str]:
+        ...
+
+    @property
+    def cpu_affinity(self) -> List[Optional[List[int]]]:
+        """Effective CPU affinity of worker threads."""
+        ...
+
+    @property
+    def is_shutdown(self) -> bool:
+        ...
+
+    def shutdown(self):
+        """Terminates the thread pool and waits until all worker threads exit."""
+        ...
+
+    def __reduce__(self):
+        """The thread pool is pickled as its configuration.
+        A new thread pool with the same configuration is created
+        while unpickling."""
+        ...
+
+    def __copy__(self) -> 'ResizerThreadPool':
+        """Returns a copy that shares the same thread pool."""
+        ...
+
+    def __deepcopy__(self, memo) -> 'ResizerThreadPool':
+        """Returns a copy that shares the same thread pool."""
+        ...
+
+    def __eq__(self, other) -> bool: ...
+
+    def __hash__(self) -> int: ...
+
+    def __enter__(self) -> 'ResizerThreadPool': ...
+
+    def __exit__(self, exc_type, exc_value, traceback) -> bool: ...
+
 
 class RustAlphaMulDiv:
     def __init__(self): ...
@@ -52,7 +250,8 @@ class RustAlphaMulDiv:
             src_image: Image,
             dst_image: Image,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
         """
         Divides RGB-channels of the source image by alpha-channel and store
         a result into the destination image.
@@ -63,7 +262,8 @@ class RustAlphaMulDiv:
             self,
             image: Image,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
         """Divides RGB-channels of the image by alpha-channel inplace."""
         ...
 
@@ -72,7 +272,8 @@ class RustAlphaMulDiv:
             src_image: PilImageWrapper,
             dst_image: PilImageWrapper,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
         """
         Divides RGB-channels of the source image by alpha-channel and store
         a result into the destination image.
@@ -83,8 +284,13 @@ class RustAlphaMulDiv:
             self,
             image: PilImageWrapper,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
-        """Divides RGB-channels of the image by alpha-channel inplace."""
+            threading_threshold: Optional[int] = None,
+            region: Optional[Tuple[int, int, int, int]] = None,
+    ) -> bool:
+        """Divides RGB-channels of the image by alpha-channel inplace.
+        Only pixels of the given region (left, top, width, height)
+        are changed if it is set.
+        """
         ...
 
     def multiply_alpha(
@@ -92,7 +298,8 @@ class RustAlphaMulDiv:
             src_image: Image,
             dst_image: Image,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
         """
         Multiplies RGB-channels of the source image by alpha-channel and store
         a result into the destination image.
@@ -103,7 +310,8 @@ class RustAlphaMulDiv:
             self,
             image: Image,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
         """Multiplies RGB-channels of the image by alpha-channel inplace."""
         ...
 
@@ -112,7 +320,8 @@ class RustAlphaMulDiv:
             src_image: PilImageWrapper,
             dst_image: PilImageWrapper,
             thread_pool: Optional[ResizerThreadPool] = None,
-    ):
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
         """
         Multiplies RGB-channels of the source image by alpha-channel and store
         a result into the destination image.
@@ -123,8 +332,114 @@ class RustAlphaMulDiv:
             self,
             image: PilImageWrapper,
             thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+            region: Optional[Tuple[int, int, int, int]] = None,
+    ) -> bool:
+        """Multiplies RGB-channels of the image by alpha-channel inplace.
+        Only pixels of the given region (left, top, width, height)
+        are changed if it is set.
+        """
+        ...
+
+    def bleed_alpha_pil_inplace(
+            self,
+            image: PilImageWrapper,
+            iterations: Optional[int] = None,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
     ):
-        """Multiplies RGB-channels of the image by alpha-channel inplace."""
+        """Fills RGB-channels of fully transparent pixels of image inplace
+        with colors of the nearest visible pixels."""
+        ...
+
+
+class RustColorMapper:
+    def __init__(self, transfer_function: int):
+        """Transfer function: 1 - sRGB, 2 - gamma 2.2."""
+        ...
+
+    def get_transfer_function(self) -> int:
+        """Returns transfer function."""
+        ...
+
+    def forward_map(
+            self,
+            src_image: Image,
+            dst_image: Image,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps components of source image into linear colorspace and store
+        result into destination image."""
+        ...
+
+    def forward_map_inplace(
+            self,
+            image: Image,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps components of image into linear colorspace inplace."""
+        ...
+
+    def backward_map(
+            self,
+            src_image: Image,
+            dst_image: Image,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps components of source image from linear colorspace and store
+        result into destination image."""
+        ...
+
+    def backward_map_inplace(
+            self,
+            image: Image,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps components of image from linear colorspace inplace."""
+        ...
+
+    def forward_map_pil(
+            self,
+            src_image: PilImageWrapper,
+            dst_image: PilImageWrapper,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps components of source PIL image into linear colorspace and store
+        result into destination PIL image."""
+        ...
+
+    def forward_map_pil_inplace(
+            self,
+            image: PilImageWrapper,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps components of PIL image into linear colorspace inplace."""
+        ...
+
+    def backward_map_pil(
+            self,
+            src_image: PilImageWrapper,
+            dst_image: PilImageWrapper,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps components of source PIL image from linear colorspace and store
+        result into destination PIL image."""
+        ...
+
+    def backward_map_pil_inplace(
+            self,
+            image: PilImageWrapper,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ):
+        """Maps components of PIL image from linear colorspace inplace."""
         ...
 
 
@@ -134,6 +449,16 @@ class RustResizeOptions:
     def copy(self) -> 'RustResizeOptions':
         ...
 
+    def __copy__(self) -> 'RustResizeOptions': ...
+
+    def __getstate__(self) -> dict: ...
+
+    def __setstate__(self, state: dict): ...
+
+    def __eq__(self, other) -> bool: ...
+
+    def __hash__(self) -> int: ...
+
     def get_resize_alg(self) -> Tuple[int, int, int]:
         """Returns resize algorithm."""
         ...
@@ -174,6 +499,80 @@ class RustResizeOptions:
         image without distortions."""
         ...
 
+    def get_edge_mode(self) -> int:
+        """Returns mode of getting pixels outside the edges of the source
+        image that are covered by the crop box."""
+        ...
+
+    def set_edge_mode(self, mode: int) -> 'RustResizeOptions':
+        """Set mode of getting pixels outside the edges of the source image.
+        Mode: 0 - transparent, 1 - constant color, 2 - clamp, 3 - mirror, 4 - wrap."""
+        ...
+
+    def get_edge_color(self) -> Optional[List[float]]:
+        """Returns color of pixels outside the edges of the source image
+        that is used with the constant edge mode."""
+        ...
+
+    def set_edge_color(self, color: Optional[List[float]]) -> 'RustResizeOptions':
+        """Set color (values of components of source pixels with or
+        without alpha channel) of pixels outside the edges of the source
+        image that is used with the constant edge mode. `None` - all
+        components are zero."""
+        ...
+
+    def get_border_mode(self) -> int:
+        """Returns mode of getting pixels outside the edges of the source
+        image that are used by the resize algorithm (0 - none)."""
+        ...
+
+    def set_border_mode(self, mode: int) -> 'RustResizeOptions':
+        """Set mode of getting pixels outside the edges of the source image
+        that are used by the resize algorithm near the edges.
+        Mode: 0 - none (kernels are clipped by the edges), 1 - clamp,
+        2 - mirror, 3 - wrap."""
+        ...
+
+    def get_contain_in_destination_centering(self) -> Optional[Tuple[float, float]]:
+        """Returns centering of the source image contained
+        in the destination image."""
+        ...
+
+    def set_contain_in_destination(
+            self,
+            centering: Optional[Tuple[float, float]] = None,
+    ) -> 'RustResizeOptions':
+        """Resize the whole source image into the biggest region of the destination image
+        with the aspect ratio of the source image. The rest of the destination image
+        is filled with the padding color."""
+        ...
+
+    def get_padding(self) -> Optional[List[float]]:
+        """Returns color that is used to fill the destination image
+        around the contained source image."""
+        ...
+
+    def set_padding(self, color: Optional[List[float]]) -> 'RustResizeOptions':
+        """Set color (values of components of destination pixels with or
+        without alpha channel) that is used to fill the destination image
+        around the contained source image. `None` - the background color
+        or transparency."""
+        ...
+
+    def get_dst_region(self) -> Optional[Tuple[int, int, int, int]]:
+        """Returns region (left, top, width, height) of the destination
+        image into which the source image is resized."""
+        ...
+
+    def set_dst_region(
+            self,
+            region: Optional[Tuple[int, int, int, int]],
+    ) -> 'RustResizeOptions':
+        """Set region (left, top, width, height) of the destination image
+        into which the source image is resized. Pixels outside the region
+        are not changed. `None` - the whole destination image."""
+        ...
+
     def get_use_alpha(self) -> bool:
         ...
 
@@ -187,6 +586,99 @@ class RustResizeOptions:
     def set_thread_pool(self, thread_pool: Optional[ResizerThreadPool]) -> 'RustResizeOptions':
         ...
 
+    def get_threading_threshold(self) -> int:
+        ...
+
+    def set_threading_threshold(self, threshold: int) -> 'RustResizeOptions':
+        """Set the minimal estimated amount of work required to use
+        a thread pool."""
+        ...
+
+    def get_linear_light(self) -> int:
+        """Returns transfer function of colorspace that is used to convert
+        images into linear colorspace before resizing (0 - disabled)."""
+        ...
+
+    def set_linear_light(self, transfer_function: int) -> 'RustResizeOptions':
+        """Enable or disable resizing in linear colorspace.
+        Transfer function: 0 - disabled, 1 - sRGB, 2 - gamma 2.2."""
+        ...
+
+    def get_dithering(self) -> int:
+        """Returns dithering that is used to reduce bit depth of pixels
+        if source and destination images have different types of pixels."""
+        ...
+
+    def set_dithering(self, dithering: int) -> 'RustResizeOptions':
+        """Set dithering that is used to reduce bit depth of pixels.
+        Dithering: 0 - disabled, 1 - ordered, 2 - blue-noise, 3 - Floyd–Steinberg."""
+        ...
+
+    def get_channel_order(self) -> Optional[List[int]]:
+        """Returns order of channels of source pixels that is used
+        to store them into destination pixels."""
+        ...
+
+    def set_channel_order(self, order: Optional[List[int]]) -> 'RustResizeOptions':
+        """Set order of channels of source pixels that is used to store
+        them into destination pixels (e.g. [2, 1, 0] to convert BGR into RGB)."""
+        ...
+
+    def get_selected_channels(self) -> Optional[List[int]]:
+        """Returns channels of the source image that are resized
+        into the same channels of the destination image."""
+        ...
+
+    def set_selected_channels(self, channels: Optional[List[int]]) -> 'RustResizeOptions':
+        """Set channels of the source image that are resized into
+        the same channels of the destination image. Other channels
+        of the destination image are not changed."""
+        ...
+
+    def get_detect_grayscale(self) -> bool:
+        """Returns `True` if a color source image is checked for
+        equality of red, green and blue channels to resize only
+        one channel of it."""
+        ...
+
+    def set_detect_grayscale(self, v: bool) -> 'RustResizeOptions':
+        """Enable checking of a color source image for equality of red,
+        green and blue channels to resize only one channel of it."""
+        ...
+
+    def get_bleed_alpha(self) -> Optional[int]:
+        """Returns max count of iterations of filling color channels of
+        fully transparent pixels of the source image before resizing
+        or `None` if it is disabled."""
+        ...
+
+    def set_bleed_alpha(self, iterations: Optional[int]) -> 'RustResizeOptions':
+        """Set max count of iterations of filling color channels of fully
+        transparent pixels of the source image with colors of the nearest
+        visible pixels before resizing. `None` disables it."""
+        ...
+
+    def get_background(self) -> Optional[List[float]]:
+        """Returns color that is used as background of images with
+        alpha channel or `None` if alpha channel is kept."""
+        ...
+
+    def set_background(self, color: Optional[List[float]]) -> 'RustResizeOptions':
+        """Set color (one or three values of components of destination
+        pixels) onto which the result of resizing of an image with
+        alpha channel is composited. `None` disables it."""
+        ...
+
+
+class ResizeReport:
+    """Report about resizing of an image."""
+
+    grayscale: bool
+    """The source image is grayscale and only one channel of it was resized."""
+    constant_alpha: bool
+    """Alpha channel of the source image is constant, so multiplying
+    and dividing by alpha were skipped."""
+
 
 class RustResizer:
     def __init__(self): ...
@@ -204,20 +696,128 @@ class RustResizer:
             src_image: Image,
             dst_image: Image,
             options: Optional[RustResizeOptions] = None,
-    ):
+    ) -> ResizeReport:
         """Resize source image into destination image."""
         ...
 
+    def resize_multi(
+            self,
+            src_image: Image,
+            dst_images: List[Image],
+            options: Optional[RustResizeOptions] = None,
+    ) -> List[ResizeReport]:
+        """Resize the source image into several destination images.
+
+        Smaller destination images may be resized from larger ones
+        if it doesn't noticeably decrease quality of the result.
+        Independent destination images are resized in parallel
+        by the thread pool."""
+        ...
+
+    def build_pyramid(
+            self,
+            src_image: Image,
+            levels: Optional[int] = None,
+            filter_type: int = 1,
+            min_size: int = 1,
+            options: Optional[RustResizeOptions] = None,
+    ) -> List[Image]:
+        """Build levels of an image pyramid (mipmaps) from the source image.
+
+        Each level is a half of the previous one (rounded down, but not
+        less than one pixel) and resized from it by the convolution
+        with the filter. The source image is not included."""
+        ...
+
+    def tiles(
+            self,
+            src_image: Image,
+            tile_size: int = 256,
+            overlap: int = 0,
+            layout: int = 0,
+            filter_type: int = 1,
+            options: Optional[RustResizeOptions] = None,
+    ) -> RustTiles:
+        """Cut an image pyramid built from the source image into tiles
+        with the given size and overlap.
+
+        Levels are resized one by one from the previous level
+        by the convolution with the filter, each one is a half of
+        the previous level rounded up. Pixels of every level
+        are transformed by options once.
+        Layout of tiles: 0 - Deep Zoom (DZI), 1 - XYZ."""
+        ...
+
     def resize_pil(
             self,
             src_image: PilImageWrapper,
             dst_image: PilImageWrapper,
             options: Optional[RustResizeOptions] = None,
-    ):
+    ) -> ResizeReport:
         """Resize source image into destination image."""
         ...
 
 
+class RustTiles(Iterator[Tuple[int, int, int, Image]]):
+    """Iterator over tiles `(level, col, row, image)` of all levels
+    of an image pyramid from the level with the full size of
+    the source image to the level 0."""
+
+    def __iter__(self) -> 'RustTiles': ...
+
+    def __next__(self) -> Tuple[int, int, int, Image]: ...
+
+
+class RustResizePlan:
+    def __init__(
+            self,
+            src_size: Tuple[int, int],
+            src_pixel_type: int,
+            dst_size: Tuple[int, int],
+            dst_pixel_type: int,
+            options: Optional[RustResizeOptions] = None,
+    ):
+        """Check types of pixels and options by resizing of blank images
+        with the given sizes and types of pixels."""
+        ...
+
+    def run(self, src_image: Image, dst_image: Image) -> ResizeReport:
+        """Resize the source image into the destination image. Images
+        must have sizes and types of pixels for which the plan was created."""
+        ...
+
+
+class RustStreamingResizer:
+    def __init__(
+            self,
+            src_size: Tuple[int, int],
+            src_pixel_type: int,
+            dst_size: Tuple[int, int],
+            dst_pixel_type: int,
+            options: Optional[RustResizeOptions] = None,
+    ): ...
+
+    def src_rows(self) -> int:
+        """Returns count of pushed rows of the source image."""
+        ...
+
+    def dst_rows(self) -> int:
+        """Returns count of calculated rows of the destination image."""
+        ...
+
+    def push(self, rows: Image) -> Optional[Image]:
+        """Pushes the next rows of the source image and returns an image
+        with the next rows of the destination image that were
+        calculated using them, or `None`."""
+        ...
+
+    def finish(self) -> Optional[Image]:
+        """Returns an image with the rest of rows of the destination image
+        or `None`. All rows of the source image must be pushed."""
+        ...
+
+
+
 # variables with complex values
 
 __all__ = [
@@ -226,5 +826,9 @@ __all__ = [
     'ResizerThreadPool',
     'RustResizeOptions',
     'RustResizer',
+    'RustStreamingResizer',
+    'RustResizePlan',
+    'RustTiles',
+    'ResizeReport',
     'RustAlphaMulDiv',
 ]
@@ -4,9 +4,9 @@
 """
 import dataclasses
 from enum import Enum, unique
-from typing import Optional, Tuple, Union
+from typing import List, Optional, Sequence, Tuple, Union
 
-from .rust_lib import Image, RustResizeOptions, ResizerThreadPool
+from .rust_lib import Image, ResizeReport, RustResizeOptions, ResizerThreadPool
 
 
 __all__ = (
@@ -14,10 +14,17 @@ __all__ = (
     'FilterType',
     'CpuExtensions',
     'PixelType',
+    'TransferFunction',
+    'Dithering',
+    'GrayscaleWeights',
+    'EdgeMode',
+    'BorderMode',
+    'TileLayout',
     'ResizeAlg',
     'CropBox',
     'ResizerThreadPool',
     'ResizeOptions',
+    'ResizeReport',
     'ImageData',
 )
 
@@ -72,6 +79,118 @@ class PixelType(Enum):
     F32x4 = 13
 
 
+@unique
+class TransferFunction(Enum):
+    """Transfer function of colorspace of images.
+
+    srgb
+        Standard sRGB colorspace.
+    gamma_22
+        Simplified colorspace with gamma 2.2.
+    """
+    srgb = 1
+    gamma_22 = 2
+
+
+@unique
+class Dithering(Enum):
+    """Dithering that is used to reduce bit depth of pixels while
+    converting U16xN or F32xN pixels into U8xN pixels.
+
+    ordered
+        Ordered dithering with 8x8 Bayer matrix.
+    blue_noise
+        Ordered dithering with blue-noise texture. It produces
+        less noticeable patterns than Bayer matrix.
+    floyd_steinberg
+        Error-diffusion dithering. It is processed in one thread.
+    """
+    none = 0
+    ordered = 1
+    blue_noise = 2
+    floyd_steinberg = 3
+
+
+@unique
+class EdgeMode(Enum):
+    """Mode of getting pixels outside the edges of the source image
+    that are covered by a crop box.
+
+    transparent
+        All components of pixels are zero (transparent or black).
+    constant
+        Pixels have the color set by `ResizeOptions.edge_color`.
+    clamp
+        Pixels on the edges of the image are repeated.
+    mirror
+        The image is reflected at its edges.
+    wrap
+        The image is repeated as tiles.
+    """
+    transparent = 0
+    constant = 1
+    clamp = 2
+    mirror = 3
+    wrap = 4
+
+
+@unique
+class BorderMode(Enum):
+    """Mode of getting pixels outside the edges of the source image
+    that are used by the resize algorithm near the edges.
+
+    normalize
+        Kernels of the resize algorithm are clipped by the edges
+        and their weights are normalized.
+    clamp
+        Pixels on the edges of the image are repeated.
+    mirror
+        The image is reflected at its edges.
+    wrap
+        The image is repeated as tiles, so tileable textures
+        stay seamless after resizing.
+    """
+    normalize = 0
+    clamp = 1
+    mirror = 2
+    wrap = 3
+
+
+@unique
+class TileLayout(Enum):
+    """Layout of tiles of an image pyramid.
+
+    deep_zoom
+        Deep Zoom (DZI) used by OpenSeadragon. The level 0 has size
+        1x1 pixel, tiles on the right and bottom edges are smaller
+        than others, neighbouring tiles may overlap.
+    xyz
+        XYZ (slippy map) used by Leaflet. The level 0 fits into one tile,
+        all tiles have the same size, pixels outside the level
+        are filled with `ResizeOptions.background`, if it is set,
+        or zeros (transparent or black).
+    """
+    deep_zoom = 0
+    xyz = 1
+
+
+@unique
+class GrayscaleWeights(Enum):
+    """Weights of red, green and blue channels that are used
+    to calculate luma of grayscale pixels.
+
+    rec601
+        ITU-R BT.601 (used by Pillow and JPEG).
+    rec709
+        ITU-R BT.709 (sRGB and HDTV).
+    """
+    rec601 = (0.299, 0.587, 0.114)
+    rec709 = (0.2126, 0.7152, 0.0722)
+
+
+# Max count of iterations that means "until all pixels are processed"
+UNLIMITED_ITERATIONS = 2 ** 32 - 1
+
 PIXEL_SIZE = {
     PixelType.U8: 1,
     PixelType.U8x2: 2,
@@ -141,6 +260,9 @@ class ResizeAlg:
                 and self._multiplicity == other._multiplicity
         )
 
+    def __hash__(self):
+        return hash((self._algorithm, self._filter_type, self._multiplicity))
+
     def __str__(self):
         return (
             f'<{self.__class__.__name__} '
@@ -148,6 +270,9 @@ class ResizeAlg:
             f'{self._multiplicity})>'
         )
 
+    def __repr__(self):
+        return self.__str__()
+
 
 @dataclasses.dataclass(frozen=True)
 class CropBox:
@@ -157,8 +282,6 @@ class CropBox:
     height: float
 
     def __post_init__(self):
-        if self.left < 0 or self.top < 0:
-            raise ValueError('"left" and "right" must be greater or equal to zero')
         if self.width < 0 or self.height < 0:
             raise ValueError('"width" and "height" must be greater or equal to zero')
 
@@ -243,6 +366,20 @@ class ResizeOptions:
             crop_box: Optional[CropBox] = None,
             fit_into_destination: Union[bool, Tuple[float, float]] = False,
             thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+            linear_light: Union[bool, TransferFunction] = False,
+            dithering: Dithering = Dithering.none,
+            channel_order: Optional[Sequence[int]] = None,
+            selected_channels: Optional[Sequence[int]] = None,
+            detect_grayscale: bool = False,
+            bleed_alpha: Union[bool, int] = False,
+            background: Union[int, float, Sequence[Union[int, float]], None] = None,
+            contain_in_destination: Union[bool, Tuple[float, float]] = False,
+            padding: Union[int, float, Sequence[Union[int, float]], None] = None,
+            dst_region: Optional[Tuple[int, int, int, int]] = None,
+            edge_mode: EdgeMode = EdgeMode.transparent,
+            edge_color: Union[int, float, Sequence[Union[int, float]], None] = None,
+            border_mode: BorderMode = BorderMode.normalize,
     ):
         self.rust_options = RustResizeOptions()
         if resize_alg:
@@ -256,12 +393,54 @@ class ResizeOptions:
             self.use_alpha = use_alpha
         if thread_pool is not None:
             self.thread_pool = thread_pool
+        if threading_threshold is not None:
+            self.threading_threshold = threading_threshold
+        if linear_light:
+            self.linear_light = linear_light
+        if dithering is not Dithering.none:
+            self.dithering = dithering
+        if channel_order is not None:
+            self.channel_order = channel_order
+        if selected_channels is not None:
+            self.selected_channels = selected_channels
+        if detect_grayscale:
+            self.detect_grayscale = detect_grayscale
+        if bleed_alpha:
+            self.bleed_alpha = bleed_alpha
+        if background is not None:
+            self.background = background
+        if contain_in_destination:
+            centering = (
+                contain_in_destination if isinstance(contain_in_destination, tuple) else None
+            )
+            self.contain_in_destination(centering)
+        if padding is not None:
+            self.padding = padding
+        if dst_region is not None:
+            self.dst_region = dst_region
+        if edge_mode is not EdgeMode.transparent:
+            self.edge_mode = edge_mode
+        if edge_color is not None:
+            self.edge_color = edge_color
+        if border_mode is not BorderMode.normalize:
+            self.border_mode = border_mode
 
     def copy(self) -> 'ResizeOptions':
         copy = self.__class__()
         copy.rust_options = self.rust_options.copy()
         return copy
 
+    def __eq__(self, other):
+        if not isinstance(other, ResizeOptions):
+            return NotImplemented
+        return self.rust_options == other.rust_options
+
+    def __hash__(self):
+        return hash(self.rust_options)
+
+    def __repr__(self):
+        return f'<{self.__class__.__name__} {self.rust_options!r}>'
+
     @property
     def resize_alg(self) -> ResizeAlg:
         algorithm_v, filter_type_v, multiplicity = self.rust_options.get_resize_alg()
@@ -325,6 +504,25 @@ class ResizeOptions:
     def get_fit_into_destination_centering(self) -> Optional[Tuple[float, float]]:
         return self.rust_options.get_fit_into_destination_centering()
 
+    def contain_in_destination(
+            self,
+            centering: Optional[Tuple[float, float]] = None,
+    ):
+        """Resize the whole source image without distortions into
+        the biggest region of destination image with the aspect ratio
+        of source image ("letterbox"). The rest of destination image
+        is filled with `padding` color.
+
+        `centering` controls the position of the region in the same way
+        as for `fit_into_destination()`: `(0.5, 0.5)` - in the center,
+        `(0.0, 0.0)` - in the top left corner.
+        It replaces a crop box and fitting into destination image.
+        """
+        self.rust_options = self.rust_options.set_contain_in_destination(centering)
+
+    def get_contain_in_destination_centering(self) -> Optional[Tuple[float, float]]:
+        return self.rust_options.get_contain_in_destination_centering()
+
     @property
     def use_alpha(self) -> bool:
         return self.rust_options.get_use_alpha()
@@ -342,6 +540,280 @@ class ResizeOptions:
     def thread_pool(self, thread_pool: Optional[ResizerThreadPool]):
         self.rust_options = self.rust_options.set_thread_pool(thread_pool)
 
+    @property
+    def threading_threshold(self) -> int:
+        return self.rust_options.get_threading_threshold()
+
+    @threading_threshold.setter
+    def threading_threshold(self, value: int):
+        """Set the minimal estimated amount of work required to use
+        a thread pool for resizing. One unit of work is approximately
+        one access to a source pixel while calculating a destination pixel.
+
+        Small images are processed faster in the current thread because
+        dispatching work into a thread pool has its own overhead.
+        Use 0 to always use a thread pool.
+
+        Methods that process images pixel by pixel (e.g. `AlphaMulDiv`
+        or `ImageData.convert()`) have their own `threading_threshold`
+        argument that is the minimal count of pixels of an image.
+        """
+        if value < 0:
+            raise ValueError('"threading_threshold" must be greater or equal to zero')
+        self.rust_options = self.rust_options.set_threading_threshold(value)
+
+    @property
+    def linear_light(self) -> Optional[TransferFunction]:
+        value = self.rust_options.get_linear_light()
+        if value:
+            return TransferFunction(value)
+
+    @linear_light.setter
+    def linear_light(self, value: Union[bool, TransferFunction, None]):
+        """Enable or disable resizing in linear colorspace.
+
+        Source image is converted from the colorspace with given transfer
+        function (`True` means sRGB) into linear colorspace, resized and
+        converted back. It is supported only for images with U8xN and
+        U16xN pixels. Alpha channel is not converted.
+        """
+        if value is True:
+            value = TransferFunction.srgb
+        self.rust_options = self.rust_options.set_linear_light(
+            value.value if value else 0
+        )
+
+    @property
+    def dithering(self) -> Dithering:
+        return Dithering(self.rust_options.get_dithering())
+
+    @dithering.setter
+    def dithering(self, value: Dithering):
+        """Set dithering that is used to reduce bit depth of pixels
+        if the destination image has U8xN pixels and the source image
+        has U16xN or F32xN pixels.
+
+        Dithering is applied only to the result of resizing. It is not
+        applied if the image is resized in linear light, but only
+        the destination type of pixels supports it (e.g. F32xN into U8xN).
+        """
+        self.rust_options = self.rust_options.set_dithering(value.value)
+
+    @property
+    def channel_order(self) -> Optional[Tuple[int, ...]]:
+        order = self.rust_options.get_channel_order()
+        if order is not None:
+            return tuple(order)
+
+    @channel_order.setter
+    def channel_order(self, order: Optional[Sequence[int]]):
+        """Set order of channels of source pixels that is used to store
+        them into destination pixels. For example, `(2, 1, 0)` converts
+        BGR into RGB, `(1, 2, 3, 0)` converts ARGB into RGBA and
+        `(0, 1, 2)` drops alpha channel of RGBA pixels.
+
+        Channels are reordered in the smaller of the source and
+        destination images, so no extra pass over the source image
+        is required while downscaling. The exception is moving of alpha
+        channel if `use_alpha` is enabled - the source image is processed
+        before resizing in this case.
+        """
+        self.rust_options = self.rust_options.set_channel_order(
+            list(order) if order is not None else None
+        )
+
+    @property
+    def selected_channels(self) -> Optional[Tuple[int, ...]]:
+        channels = self.rust_options.get_selected_channels()
+        if channels is not None:
+            return tuple(channels)
+
+    @selected_channels.setter
+    def selected_channels(self, channels: Optional[Sequence[int]]):
+        """Set channels of the source image that are resized into
+        the same channels of the destination image. Other channels of
+        the destination image are not changed. For example, `(3,)`
+        resizes only alpha channel of RGBA image.
+
+        Source and destination images must have the same type of pixels.
+        Selected channels are resized as an independent image, so alpha
+        channel is considered only if it is the last of 2 or 4 selected
+        channels.
+        """
+        self.rust_options = self.rust_options.set_selected_channels(
+            list(channels) if channels is not None else None
+        )
+
+    @property
+    def detect_grayscale(self) -> bool:
+        return self.rust_options.get_detect_grayscale()
+
+    @detect_grayscale.setter
+    def detect_grayscale(self, value: bool):
+        """Enable checking of a color source image (with or without alpha
+        channel) for equality of red, green and blue channels of all pixels.
+        Only one color channel of such image is resized and the result
+        is expanded into color channels of the destination image.
+
+        Source and destination images must have the same count of channels.
+        `ResizeReport.grayscale` returned by `Resizer.resize()` shows
+        whether the source image was resized in this way.
+        """
+        self.rust_options = self.rust_options.set_detect_grayscale(value)
+
+    @property
+    def bleed_alpha(self) -> Union[bool, int]:
+        iterations = self.rust_options.get_bleed_alpha()
+        if iterations is None:
+            return False
+        if iterations == UNLIMITED_ITERATIONS:
+            return True
+        return iterations
+
+    @bleed_alpha.setter
+    def bleed_alpha(self, value: Union[bool, int]):
+        """Enable filling of color channels of fully transparent pixels
+        of the source image with colors of the nearest visible pixels
+        before resizing. It prevents dark halos around visible pixels
+        if an image with garbage colors under transparent pixels
+        is resized without considering of alpha channel.
+
+        `True` fills all transparent pixels, an integer value limits
+        the width (in pixels) of the filled area around visible pixels.
+        The source image is not changed, bleeding is performed in a copy
+        of pixels that are used to resize the crop box (the crop box with
+        margins of the resize algorithm).
+        """
+        if value is True:
+            iterations = UNLIMITED_ITERATIONS
+        elif value is False:
+            iterations = None
+        else:
+            if value < 1:
+                raise ValueError('Count of iterations must be greater than zero')
+            iterations = value
+        self.rust_options = self.rust_options.set_bleed_alpha(iterations)
+
+    @property
+    def background(self) -> Optional[Tuple[float, ...]]:
+        color = self.rust_options.get_background()
+        return tuple(color) if color is not None else None
+
+    @background.setter
+    def background(self, value: Union[int, float, Sequence[Union[int, float]], None]):
+        """Set color onto which the result of resizing of an image
+        with alpha channel is composited, so the destination image
+        may have no alpha channel (e.g. RGBA into RGB or LA into L).
+
+        The color has one (for grayscale) or three (for RGB) values
+        in units of components of destination pixels (e.g. `(255, 255, 255)`
+        is white for U8x3 and `(1.0, 1.0, 1.0)` - for F32x3).
+        Alpha channel of destination pixels, if it is present,
+        is filled by the value of opaque alpha.
+        """
+        if isinstance(value, (int, float)):
+            value = (value,)
+        self.rust_options = self.rust_options.set_background(
+            [float(v) for v in value] if value is not None else None
+        )
+
+    @property
+    def padding(self) -> Optional[Tuple[float, ...]]:
+        color = self.rust_options.get_padding()
+        return tuple(color) if color is not None else None
+
+    @padding.setter
+    def padding(self, value: Union[int, float, Sequence[Union[int, float]], None]):
+        """Set color that is used to fill destination image around
+        the source image contained in it (see `contain_in_destination()`).
+
+        The color has values of all components of destination pixels
+        (e.g. `(255, 255, 255, 0)` for U8x4). Alpha channel may be
+        omitted, then the color is opaque. By default, `background`
+        color is used if it is set, otherwise all components are zero
+        (transparent or black).
+        """
+        if isinstance(value, (int, float)):
+            value = (value,)
+        self.rust_options = self.rust_options.set_padding(
+            [float(v) for v in value] if value is not None else None
+        )
+
+    @property
+    def dst_region(self) -> Optional[Tuple[int, int, int, int]]:
+        return self.rust_options.get_dst_region()
+
+    @dst_region.setter
+    def dst_region(self, value: Optional[Tuple[int, int, int, int]]):
+        """Set region `(left, top, width, height)` of destination image
+        into which source image is resized. Pixels of destination image
+        outside the region are not changed, so many images can be resized
+        into one canvas (e.g. a collage or a sprite sheet) without
+        temporary images.
+
+        Other options that refer to destination image (e.g.
+        `fit_into_destination()` and `contain_in_destination()`)
+        are applied to the region.
+        """
+        if value is not None:
+            value = tuple(value)
+            if len(value) != 4:
+                raise ValueError('Region must have four values')
+            if any(v < 0 for v in value):
+                raise ValueError('Values of region must be greater or equal to zero')
+        self.rust_options = self.rust_options.set_dst_region(value)
+
+    @property
+    def edge_mode(self) -> EdgeMode:
+        return EdgeMode(self.rust_options.get_edge_mode())
+
+    @edge_mode.setter
+    def edge_mode(self, value: EdgeMode):
+        """Set mode of getting pixels outside the edges of source image.
+
+        These pixels are used if `crop_box` extends beyond the edges
+        of source image (e.g. it has negative `left` or `top`) and
+        by the resize algorithm near edges of such crop box.
+        """
+        self.rust_options = self.rust_options.set_edge_mode(value.value)
+
+    @property
+    def edge_color(self) -> Optional[Tuple[float, ...]]:
+        color = self.rust_options.get_edge_color()
+        return tuple(color) if color is not None else None
+
+    @edge_color.setter
+    def edge_color(self, value: Union[int, float, Sequence[Union[int, float]], None]):
+        """Set color of pixels outside the edges of source image
+        that is used with `EdgeMode.constant`.
+
+        The color has values of all components of source pixels
+        (e.g. `(255, 255, 255, 0)` for U8x4). Alpha channel may be
+        omitted, then the color is opaque. By default, all components
+        are zero (transparent or black).
+        """
+        if isinstance(value, (int, float)):
+            value = (value,)
+        self.rust_options = self.rust_options.set_edge_color(
+            [float(v) for v in value] if value is not None else None
+        )
+
+    @property
+    def border_mode(self) -> BorderMode:
+        return BorderMode(self.rust_options.get_border_mode())
+
+    @border_mode.setter
+    def border_mode(self, value: BorderMode):
+        """Set mode of getting pixels outside the edges of source image
+        that are used by the resize algorithm near the edges.
+
+        If it is not `BorderMode.normalize`, a copy of source image
+        extended by the size of kernel is resized. If `crop_box` extends
+        beyond the edges of source image, pixels outside the edges are
+        got in accordance with `edge_mode`.
+        """
+        self.rust_options = self.rust_options.set_border_mode(value.value)
+
 
 class ImageData:
     __slots__ = ('rust_image',)
@@ -364,6 +836,12 @@ class ImageData:
                 )
         self.rust_image = Image(width, height, pixel_type.value, pixels)
 
+    @classmethod
+    def _from_rust_image(cls, rust_image: Image) -> 'ImageData':
+        image = cls.__new__(cls)
+        image.rust_image = rust_image
+        return image
+
     @property
     def width(self) -> int:
         return self.rust_image.width()
@@ -372,6 +850,205 @@ class ImageData:
     def height(self) -> int:
         return self.rust_image.height()
 
+    @property
+    def pixel_type(self) -> PixelType:
+        return PixelType(self.rust_image.pixel_type())
+
+    def convert(
+            self,
+            pixel_type: PixelType,
+            clamp: bool = True,
+            dithering: Dithering = Dithering.none,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+            dst_image: Optional['ImageData'] = None,
+    ) -> 'ImageData':
+        """Converts the image into an image with pixels of the given type.
+
+        Source and destination pixel types must have the same count
+        of components (`I32` can be converted only into types with
+        one component and back).
+
+        Values of integer components are scaled into the range of
+        the destination type. Values of `F32` components are expected
+        in the range [0.0, 1.0] ([-1.0, 1.0] for `I32`). Values outside
+        of the range are clamped, or `RuntimeError` is raised if `clamp`
+        is `False`.
+
+        :param dithering: Dithering that is used if U16xN or F32xN pixels
+                          are converted into U8xN pixels.
+        :param dst_image: An existing image to store the result. It must have
+                          the same size as the source image.
+        :return: A new image or the given destination image.
+        """
+        if dst_image is None:
+            rust_image = self.rust_image.convert(
+                pixel_type.value,
+                clamp,
+                dithering.value,
+                thread_pool,
+                threading_threshold,
+            )
+            return self._from_rust_image(rust_image)
+
+        if dst_image.pixel_type is not pixel_type:
+            raise ValueError(
+                f'Pixel type of "dst_image" ({dst_image.pixel_type}) '
+                f'is not equal to "pixel_type" ({pixel_type})'
+            )
+        self.rust_image.convert_into(
+            dst_image.rust_image,
+            clamp,
+            dithering.value,
+            thread_pool,
+            threading_threshold,
+        )
+        return dst_image
+
+    def swizzle(
+            self,
+            order: Sequence[int],
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'ImageData':
+        """Returns a copy of the image with channels in the given order.
+
+        Every item of `order` is an index of channel of the source image.
+        Count of items is count of channels of the result image.
+        For example, `(2, 1, 0)` converts BGR into RGB and `(1, 2, 3, 0)`
+        converts ARGB into RGBA.
+        """
+        rust_image = self.rust_image.swizzle(
+            list(order),
+            thread_pool,
+            threading_threshold,
+        )
+        return self._from_rust_image(rust_image)
+
+    def add_alpha(
+            self,
+            value: Union[int, float, None] = None,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'ImageData':
+        """Returns a copy of the image with added alpha channel.
+
+        :param value: Value of alpha channel. By default, it is the value
+                      of opaque alpha (255 for U8, 65535 for U16
+                      and 1.0 for F32 components).
+        """
+        rust_image = self.rust_image.add_alpha(
+            value,
+            thread_pool,
+            threading_threshold,
+        )
+        return self._from_rust_image(rust_image)
+
+    def drop_alpha(
+            self,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'ImageData':
+        """Returns a copy of the image without alpha channel."""
+        rust_image = self.rust_image.drop_alpha(thread_pool, threading_threshold)
+        return self._from_rust_image(rust_image)
+
+    def to_grayscale(
+            self,
+            weights: Union[GrayscaleWeights, Tuple[float, float, float]] = GrayscaleWeights.rec601,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'ImageData':
+        """Returns a grayscale copy of the image with three or four
+        channels (e.g. U8x3 image is converted into U8 image
+        and U16x4 image - into U16x2 image with alpha channel).
+
+        :param weights: Weights of red, green and blue channels that are used
+                        to calculate luma. Values of integer components
+                        are rounded and clamped into the range of the type.
+        """
+        if isinstance(weights, GrayscaleWeights):
+            weights = weights.value
+        rust_image = self.rust_image.to_grayscale(
+            tuple(weights),
+            thread_pool,
+            threading_threshold,
+        )
+        return self._from_rust_image(rust_image)
+
+    def is_grayscale(
+            self,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
+        """Returns `True` if all pixels of the image with three or four
+        channels have equal values of red, green and blue channels."""
+        return self.rust_image.is_grayscale(thread_pool, threading_threshold)
+
+    def is_opaque(
+            self,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> bool:
+        """Returns `True` if all pixels of the image are opaque
+        (or pixels don't have alpha channel)."""
+        return self.rust_image.is_opaque(thread_pool, threading_threshold)
+
+    def bleed_alpha(
+            self,
+            iterations: Optional[int] = None,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'ImageData':
+        """Returns a copy of the image with color channels of fully
+        transparent pixels filled with colors of the nearest visible
+        pixels. Values of alpha channel are not changed.
+
+        :param iterations: Every iteration extends the filled area around
+                           visible pixels by one pixel. All transparent
+                           pixels are filled if it is `None`.
+        """
+        if iterations is not None and iterations < 1:
+            raise ValueError('Count of iterations must be greater than zero')
+        rust_image = self.rust_image.bleed_alpha(iterations, thread_pool, threading_threshold)
+        return self._from_rust_image(rust_image)
+
+    def split(
+            self,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> List['ImageData']:
+        """Returns images with one channel for each channel of the image.
+
+        All channels are copied in one pass over rows of the image.
+        """
+        rust_images = self.rust_image.split(thread_pool, threading_threshold)
+        return [self._from_rust_image(rust_image) for rust_image in rust_images]
+
+    @classmethod
+    def merge(
+            cls,
+            channels: Sequence['ImageData'],
+            pixel_type: Optional[PixelType] = None,
+            thread_pool: Optional[ResizerThreadPool] = None,
+            threading_threshold: Optional[int] = None,
+    ) -> 'ImageData':
+        """Returns an image with channels from the given images with
+        one channel. All images must have the same size and type of pixels.
+
+        :param pixel_type: Type of pixels of the result image. By default,
+                           it has the same type of components as the images
+                           of channels. Components are converted if the type
+                           of components is different.
+        """
+        rust_image = Image.merge(
+            [channel.rust_image for channel in channels],
+            pixel_type.value if pixel_type else None,
+            thread_pool,
+            threading_threshold,
+        )
+        return cls._from_rust_image(rust_image)
+
     def get_buffer(self) -> bytes:
         """Returns copy of internal buffer with pixels"""
         return self.rust_image.buffer()
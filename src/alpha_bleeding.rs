@@ -0,0 +1,192 @@
+//! Filling of color channels of fully transparent pixels
+//! with colors of nearest visible pixels.
+use std::slice;
+
+use fast_image_resize as fr;
+use fast_image_resize::pixels::{F32x2, F32x4, U8x2, U8x4, U16x2, U16x4};
+use fast_image_resize::{ImageView, ImageViewMut, IntoImageViewMut, PixelTrait, PixelType};
+use rayon::prelude::*;
+
+/// Pixel component of images which can be processed by alpha bleeding.
+trait BleedComponent: Copy + PartialEq + Send + Sync {
+    const TRANSPARENT: Self;
+
+    fn to_f32(self) -> f32;
+
+    fn from_f32(value: f32) -> Self;
+}
+
+impl BleedComponent for u8 {
+    const TRANSPARENT: Self = 0;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value.round() as u8
+    }
+}
+
+impl BleedComponent for u16 {
+    const TRANSPARENT: Self = 0;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value.round() as u16
+    }
+}
+
+impl BleedComponent for f32 {
+    const TRANSPARENT: Self = 0.;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+/// Pixels of an image stored as mutable rows with random access.
+struct Pixels<'a, P> {
+    rows: Vec<&'a mut [P]>,
+    width: usize,
+}
+
+impl<P: PixelTrait> Pixels<'_, P> {
+    fn get(&self, index: usize) -> &P {
+        &self.rows[index / self.width][index % self.width]
+    }
+
+    fn get_mut(&mut self, index: usize) -> &mut P {
+        &mut self.rows[index / self.width][index % self.width]
+    }
+
+    /// Returns indexes of 8-connected neighbours of the pixel.
+    fn neighbours(&self, index: usize) -> impl Iterator<Item = usize> {
+        let (width, height) = (self.width as isize, self.rows.len() as isize);
+        let (x, y) = ((index % self.width) as isize, (index / self.width) as isize);
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            .filter(move |&(nx, ny)| {
+                (nx, ny) != (x, y) && (0..width).contains(&nx) && (0..height).contains(&ny)
+            })
+            .map(move |(nx, ny)| (ny * width + nx) as usize)
+    }
+}
+
+/// Returns a copy of the transparent pixel with color channels set
+/// to the average color of its filled neighbours.
+fn bled_pixel<P>(pixels: &Pixels<P>, is_filled: &[bool], index: usize) -> P
+where
+    P: PixelTrait<Component: BleedComponent>,
+{
+    let color_channels = P::count_of_components() - 1;
+    let mut sums = [0f32; 3];
+    let mut count = 0f32;
+    for neighbour in pixels.neighbours(index).filter(|&i| is_filled[i]) {
+        let components = P::components(slice::from_ref(pixels.get(neighbour)));
+        for (sum, component) in sums.iter_mut().zip(&components[..color_channels]) {
+            *sum += component.to_f32();
+        }
+        count += 1.;
+    }
+    let mut pixel = *pixels.get(index);
+    let components = P::components_mut(slice::from_mut(&mut pixel));
+    for (component, sum) in components[..color_channels].iter_mut().zip(sums) {
+        *component = BleedComponent::from_f32(sum / count);
+    }
+    pixel
+}
+
+fn bleed_alpha_typed<P>(image: &mut impl IntoImageViewMut, iterations: u32) -> Result<(), String>
+where
+    P: PixelTrait<Component: BleedComponent>,
+{
+    let Some(mut view) = image.image_view_mut::<P>() else {
+        return Err(fr::ImageError::UnsupportedPixelType.to_string());
+    };
+    let width = view.width() as usize;
+    let mut pixels = Pixels {
+        rows: view.iter_rows_mut(0).map(|row| &mut row[..width]).collect(),
+        width,
+    };
+    let count_of_pixels = width * pixels.rows.len();
+    let alpha_channel = P::count_of_components() - 1;
+    let mut is_filled: Vec<bool> = (0..count_of_pixels)
+        .map(|i| {
+            let components = P::components(slice::from_ref(pixels.get(i)));
+            components[alpha_channel] != BleedComponent::TRANSPARENT
+        })
+        .collect();
+    // Filled pixels and pixels that are already added into the front
+    let mut is_queued = is_filled.clone();
+
+    // The front contains transparent pixels that have filled neighbours
+    let mut front: Vec<usize> = (0..count_of_pixels)
+        .filter(|&i| !is_filled[i] && pixels.neighbours(i).any(|n| is_filled[n]))
+        .collect();
+    for &i in front.iter() {
+        is_queued[i] = true;
+    }
+
+    for _ in 0..iterations {
+        if front.is_empty() {
+            break;
+        }
+        let bled_pixels: Vec<P> = if rayon::current_num_threads() > 1 {
+            front
+                .par_iter()
+                .map(|&i| bled_pixel(&pixels, &is_filled, i))
+                .collect()
+        } else {
+            front
+                .iter()
+                .map(|&i| bled_pixel(&pixels, &is_filled, i))
+                .collect()
+        };
+        for (&i, pixel) in front.iter().zip(bled_pixels) {
+            *pixels.get_mut(i) = pixel;
+            is_filled[i] = true;
+        }
+        let mut next_front = Vec::new();
+        for &i in front.iter() {
+            for neighbour in pixels.neighbours(i) {
+                if !is_queued[neighbour] {
+                    is_queued[neighbour] = true;
+                    next_front.push(neighbour);
+                }
+            }
+        }
+        front = next_front;
+    }
+    Ok(())
+}
+
+/// Fills color channels of fully transparent pixels of the image
+/// with the average color of neighbour pixels that are visible
+/// or have been filled by previous iterations.
+///
+/// Every iteration extends the filled area by one pixel.
+/// Values of alpha channel are not changed.
+pub(crate) fn bleed_alpha(
+    image: &mut impl IntoImageViewMut,
+    iterations: u32,
+) -> Result<(), String> {
+    use PixelType as PT;
+
+    match image.pixel_type() {
+        Some(PT::U8x2) => bleed_alpha_typed::<U8x2>(image, iterations),
+        Some(PT::U8x4) => bleed_alpha_typed::<U8x4>(image, iterations),
+        Some(PT::U16x2) => bleed_alpha_typed::<U16x2>(image, iterations),
+        Some(PT::U16x4) => bleed_alpha_typed::<U16x4>(image, iterations),
+        Some(PT::F32x2) => bleed_alpha_typed::<F32x2>(image, iterations),
+        Some(PT::F32x4) => bleed_alpha_typed::<F32x4>(image, iterations),
+        _ => Err(fr::ImageError::UnsupportedPixelType.to_string()),
+    }
+}
@@ -2,12 +2,16 @@ use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 
 use fast_image_resize as fir;
+use fast_image_resize::{IntoImageView, IntoImageViewMut};
 use pyo3::prelude::*;
 use pyo3::types::PyInt;
 
+use crate::alpha_bleeding::bleed_alpha;
+use crate::channels::copy_pixels;
 use crate::image_view::Image;
+use crate::opacity::{AlphaState, scan_alpha};
 use crate::pil_image_wrapper::{PilImageWrapper, RgbMode};
-use crate::thread_pool::ResizerThreadPool;
+use crate::thread_pool::{ResizerThreadPool, thread_pool_for_image};
 use crate::utils::{cpu_extensions_from_u8, cpu_extensions_to_u8, result2pyresult};
 
 #[pyclass]
@@ -48,14 +52,17 @@ impl RustAlphaMulDiv {
     /// Multiplies RGB-channels of source image by alpha-channel and store
     /// result into destination image.
     /// The images are represented as instances of ImageView.
-    #[pyo3(signature = (src_image, dst_image, thread_pool=None))]
+    #[pyo3(signature = (src_image, dst_image, thread_pool=None, threading_threshold=None))]
     fn multiply_alpha(
         &self,
         py: Python,
         src_image: &Image,
         dst_image: &mut Image,
         thread_pool: Option<ResizerThreadPool>,
-    ) -> PyResult<()> {
+        threading_threshold: Option<u64>,
+    ) -> PyResult<bool> {
+        let thread_pool =
+            thread_pool_for_image(thread_pool, threading_threshold, src_image.src_image_view());
         let mul_div_mutex = self.mul_div.clone();
         py.detach(move || {
             let src_image_view = src_image.src_image_view();
@@ -64,22 +71,25 @@ impl RustAlphaMulDiv {
             let mul_div = mul_div_guard.deref();
             if let Some(thread_pool) = thread_pool {
                 return thread_pool.run_within(|| {
-                    result2pyresult(mul_div.multiply_alpha(src_image_view, dst_image_view))
+                    result2pyresult(multiply_alpha(mul_div, src_image_view, dst_image_view))
                 });
             }
-            result2pyresult(mul_div.multiply_alpha(src_image_view, dst_image_view))
+            result2pyresult(multiply_alpha(mul_div, src_image_view, dst_image_view))
         })
     }
 
     /// Multiplies RGB-channels of image by alpha-channel inplace.
     /// The image is represented as instances of ImageView.
-    #[pyo3(signature = (image, thread_pool=None))]
+    #[pyo3(signature = (image, thread_pool=None, threading_threshold=None))]
     fn multiply_alpha_inplace(
         &self,
         py: Python,
         image: &mut Image,
         thread_pool: Option<ResizerThreadPool>,
-    ) -> PyResult<()> {
+        threading_threshold: Option<u64>,
+    ) -> PyResult<bool> {
+        let thread_pool =
+            thread_pool_for_image(thread_pool, threading_threshold, image.src_image_view());
         let mul_div_mutex = self.mul_div.clone();
         py.detach(move || {
             let dst_image_view = image.dst_image_view();
@@ -87,78 +97,94 @@ impl RustAlphaMulDiv {
             let mul_div = mul_div_guard.deref();
             if let Some(thread_pool) = thread_pool {
                 return thread_pool.run_within(|| {
-                    result2pyresult(mul_div.multiply_alpha_inplace(dst_image_view))
+                    result2pyresult(multiply_alpha_inplace(mul_div, dst_image_view))
                 });
             }
-            result2pyresult(mul_div.multiply_alpha_inplace(dst_image_view))
+            result2pyresult(multiply_alpha_inplace(mul_div, dst_image_view))
         })
     }
 
     /// Multiplies RGB-channels of source image by alpha-channel and store
     /// result into destination image.
     /// The images are represented as instances of PilImageView.
-    #[pyo3(signature = (src_image, dst_image, thread_pool=None))]
+    #[pyo3(signature = (src_image, dst_image, thread_pool=None, threading_threshold=None))]
     fn multiply_alpha_pil(
         &self,
         py: Python,
         src_image: &PilImageWrapper,
         dst_image: &mut PilImageWrapper,
         thread_pool: Option<ResizerThreadPool>,
-    ) -> PyResult<()> {
+        threading_threshold: Option<u64>,
+    ) -> PyResult<bool> {
         if !src_image.is_rgb_mode(py)? {
             return result2pyresult(Err("Invalid mode of source PIL image"));
         }
         if !dst_image.is_rgb_mode(py)? {
             return result2pyresult(Err("Invalid mode of destination PIL image"));
         }
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, src_image);
         let mul_div_mutex = self.mul_div.clone();
         py.detach(move || {
             let mul_div_guard = result2pyresult(mul_div_mutex.lock())?;
             let mul_div = mul_div_guard.deref();
             if let Some(thread_pool) = thread_pool {
                 return thread_pool
-                    .run_within(|| result2pyresult(mul_div.multiply_alpha(src_image, dst_image)));
+                    .run_within(|| result2pyresult(multiply_alpha(mul_div, src_image, dst_image)));
             }
-            result2pyresult(mul_div.multiply_alpha(src_image, dst_image))
+            result2pyresult(multiply_alpha(mul_div, src_image, dst_image))
         })
     }
 
     /// Multiplies RGB-channels of image by alpha-channel inplace.
     /// The image is represented as instance of PilImageView.
-    #[pyo3(signature = (image, thread_pool=None))]
+    /// Only pixels of the given region (left, top, width, height)
+    /// are changed if it is set.
+    #[pyo3(signature = (image, thread_pool=None, threading_threshold=None, region=None))]
     fn multiply_alpha_pil_inplace(
         &self,
         py: Python,
         image: &mut PilImageWrapper,
         thread_pool: Option<ResizerThreadPool>,
-    ) -> PyResult<()> {
+        threading_threshold: Option<u64>,
+        region: Option<Region>,
+    ) -> PyResult<bool> {
         if !image.is_rgb_mode(py)? {
             return result2pyresult(Err("Invalid mode of PIL image"));
         }
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, image);
         let mul_div_mutex = self.mul_div.clone();
-        py.detach(|| {
+        let is_opaque = py.detach(|| {
             let mul_div_guard = result2pyresult(mul_div_mutex.lock())?;
             let mul_div = mul_div_guard.deref();
+            let mut process = || {
+                result2pyresult(in_region(image, region, |view| {
+                    multiply_alpha_inplace(mul_div, view)
+                }))
+            };
             if let Some(thread_pool) = thread_pool {
-                thread_pool.run_within(|| result2pyresult(mul_div.multiply_alpha_inplace(image)))
+                thread_pool.run_within(process)
             } else {
-                result2pyresult(mul_div.multiply_alpha_inplace(image))
+                process()
             }
         })?;
-        image.set_rgb_mode(py, RgbMode::Rgba)
+        image.set_rgb_mode(py, RgbMode::Rgba)?;
+        Ok(is_opaque)
     }
 
     /// Divides RGB-channels of source image by alpha-channel and store
     /// result into destination image.
     /// The images are represented as instances of ImageView.
-    #[pyo3(signature = (src_image, dst_image, thread_pool=None))]
+    #[pyo3(signature = (src_image, dst_image, thread_pool=None, threading_threshold=None))]
     fn divide_alpha(
         &self,
         py: Python,
         src_image: &Image,
         dst_image: &mut Image,
         thread_pool: Option<ResizerThreadPool>,
-    ) -> PyResult<()> {
+        threading_threshold: Option<u64>,
+    ) -> PyResult<bool> {
+        let thread_pool =
+            thread_pool_for_image(thread_pool, threading_threshold, src_image.src_image_view());
         let mul_div_mutex = self.mul_div.clone();
         py.detach(move || {
             let src_image_view = src_image.src_image_view();
@@ -167,22 +193,25 @@ impl RustAlphaMulDiv {
             let mul_div = mul_div_guard.deref();
             if let Some(thread_pool) = thread_pool {
                 return thread_pool.run_within(|| {
-                    result2pyresult(mul_div.divide_alpha(src_image_view, dst_image_view))
+                    result2pyresult(divide_alpha(mul_div, src_image_view, dst_image_view))
                 });
             }
-            result2pyresult(mul_div.divide_alpha(src_image_view, dst_image_view))
+            result2pyresult(divide_alpha(mul_div, src_image_view, dst_image_view))
         })
     }
 
     /// Divides RGB-channels of image by alpha-channel inplace.
     /// The image is represented as instances of ImageView.
-    #[pyo3(signature = (image, thread_pool=None))]
+    #[pyo3(signature = (image, thread_pool=None, threading_threshold=None))]
     fn divide_alpha_inplace(
         &self,
         py: Python,
         image: &mut Image,
         thread_pool: Option<ResizerThreadPool>,
-    ) -> PyResult<()> {
+        threading_threshold: Option<u64>,
+    ) -> PyResult<bool> {
+        let thread_pool =
+            thread_pool_for_image(thread_pool, threading_threshold, image.src_image_view());
         let mul_div_mutex = self.mul_div.clone();
         py.detach(move || {
             let dst_image_view = image.dst_image_view();
@@ -190,63 +219,199 @@ impl RustAlphaMulDiv {
             let mul_div = mul_div_guard.deref();
             if let Some(thread_pool) = thread_pool {
                 return thread_pool
-                    .run_within(|| result2pyresult(mul_div.divide_alpha_inplace(dst_image_view)));
+                    .run_within(|| result2pyresult(divide_alpha_inplace(mul_div, dst_image_view)));
             }
-            result2pyresult(mul_div.divide_alpha_inplace(dst_image_view))
+            result2pyresult(divide_alpha_inplace(mul_div, dst_image_view))
         })
     }
 
     /// Divides RGB-channels of source image by alpha-channel and store
     /// result into destination image.
     /// The images are represented as instances of PilImageView.
-    #[pyo3(signature = (src_image, dst_image, thread_pool=None))]
+    #[pyo3(signature = (src_image, dst_image, thread_pool=None, threading_threshold=None))]
     fn divide_alpha_pil(
         &self,
         py: Python,
         src_image: &PilImageWrapper,
         dst_image: &mut PilImageWrapper,
         thread_pool: Option<ResizerThreadPool>,
-    ) -> PyResult<()> {
+        threading_threshold: Option<u64>,
+    ) -> PyResult<bool> {
         if !src_image.is_rgb_mode(py)? {
             return result2pyresult(Err("Invalid mode of source PIL image"));
         }
         if !dst_image.is_rgb_mode(py)? {
             return result2pyresult(Err("Invalid mode of destination PIL image"));
         }
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, src_image);
         let mul_div_mutex = self.mul_div.clone();
         py.detach(move || {
             let mul_div_guard = result2pyresult(mul_div_mutex.lock())?;
             let mul_div = mul_div_guard.deref();
             if let Some(thread_pool) = thread_pool {
                 return thread_pool
-                    .run_within(|| result2pyresult(mul_div.divide_alpha(src_image, dst_image)));
+                    .run_within(|| result2pyresult(divide_alpha(mul_div, src_image, dst_image)));
             }
-            result2pyresult(mul_div.divide_alpha(src_image, dst_image))
+            result2pyresult(divide_alpha(mul_div, src_image, dst_image))
         })
     }
 
     /// Divides RGB-channels of image by alpha-channel inplace.
     /// The image is represented as instance of PilImageView.
-    #[pyo3(signature = (image, thread_pool=None))]
+    /// Only pixels of the given region (left, top, width, height)
+    /// are changed if it is set.
+    #[pyo3(signature = (image, thread_pool=None, threading_threshold=None, region=None))]
     fn divide_alpha_pil_inplace(
         &self,
         py: Python,
         image: &mut PilImageWrapper,
         thread_pool: Option<ResizerThreadPool>,
-    ) -> PyResult<()> {
+        threading_threshold: Option<u64>,
+        region: Option<Region>,
+    ) -> PyResult<bool> {
         if !image.is_rgb_mode(py)? {
             return result2pyresult(Err("Invalid mode of PIL image"));
         }
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, image);
         let mul_div_mutex = self.mul_div.clone();
-        py.detach(|| {
+        let is_opaque = py.detach(|| {
             let mul_div_guard = result2pyresult(mul_div_mutex.lock())?;
             let mul_div = mul_div_guard.deref();
+            let mut process = || {
+                result2pyresult(in_region(image, region, |view| {
+                    divide_alpha_inplace(mul_div, view)
+                }))
+            };
             if let Some(thread_pool) = thread_pool {
-                thread_pool.run_within(|| result2pyresult(mul_div.divide_alpha_inplace(image)))
+                thread_pool.run_within(process)
             } else {
-                result2pyresult(mul_div.divide_alpha_inplace(image))
+                process()
             }
         })?;
-        image.set_rgb_mode(py, RgbMode::RgbA)
+        image.set_rgb_mode(py, RgbMode::RgbA)?;
+        Ok(is_opaque)
+    }
+
+    /// Fills RGB-channels of fully transparent pixels of image inplace
+    /// with colors of the nearest visible pixels.
+    /// The image is represented as instance of PilImageView.
+    #[pyo3(signature = (image, iterations=None, thread_pool=None, threading_threshold=None))]
+    fn bleed_alpha_pil_inplace(
+        &self,
+        py: Python,
+        image: &mut PilImageWrapper,
+        iterations: Option<u32>,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        if !image.is_rgb_mode(py)? {
+            return result2pyresult(Err("Invalid mode of PIL image"));
+        }
+        let iterations = iterations.unwrap_or(u32::MAX);
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, image);
+        py.detach(|| {
+            if let Some(thread_pool) = thread_pool {
+                return thread_pool.run_within(|| result2pyresult(bleed_alpha(image, iterations)));
+            }
+            result2pyresult(bleed_alpha(image, iterations))
+        })
+    }
+}
+
+/// Region (left, top, width, height) of an image.
+type Region = (u32, u32, u32, u32);
+
+/// Calls the function with a view of the region of the image
+/// or of the whole image if the region is not given.
+fn in_region<I, R>(
+    image: &mut I,
+    region: Option<Region>,
+    func: impl FnOnce(&mut fir::images::CroppedImageMut<'_, I>) -> Result<R, String>,
+) -> Result<R, String>
+where
+    I: IntoImageViewMut,
+{
+    let (left, top, width, height) = region.unwrap_or((0, 0, image.width(), image.height()));
+    let mut view = fir::images::CroppedImageMut::new(image, left, top, width, height)
+        .map_err(|err| err.to_string())?;
+    func(&mut view)
+}
+
+/// Returns `true` if all pixels of the image have alpha channel
+/// and are opaque, so multiplying and dividing by alpha don't change
+/// values of pixels.
+fn is_opaque_with_alpha(image: &impl IntoImageView) -> bool {
+    scan_alpha(image) == Some(AlphaState::Opaque)
+}
+
+/// Multiplies RGB-channels of the source image by alpha-channel and
+/// stores the result into the destination image. Pixels of an opaque
+/// image are copied without multiplication.
+///
+/// Returns `true` if the source image is opaque.
+fn multiply_alpha(
+    mul_div: &fir::MulDiv,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+) -> Result<bool, String> {
+    if is_opaque_with_alpha(src_image) {
+        copy_pixels(src_image, dst_image)?;
+        return Ok(true);
+    }
+    mul_div
+        .multiply_alpha(src_image, dst_image)
+        .map_err(|err| err.to_string())?;
+    Ok(false)
+}
+
+/// Multiplies RGB-channels of the image by alpha-channel inplace.
+///
+/// Returns `true` if the image is opaque and isn't changed.
+fn multiply_alpha_inplace(
+    mul_div: &fir::MulDiv,
+    image: &mut impl IntoImageViewMut,
+) -> Result<bool, String> {
+    if is_opaque_with_alpha(image) {
+        return Ok(true);
+    }
+    mul_div
+        .multiply_alpha_inplace(image)
+        .map_err(|err| err.to_string())?;
+    Ok(false)
+}
+
+/// Divides RGB-channels of the source image by alpha-channel and
+/// stores the result into the destination image. Pixels of an opaque
+/// image are copied without division.
+///
+/// Returns `true` if the source image is opaque.
+fn divide_alpha(
+    mul_div: &fir::MulDiv,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+) -> Result<bool, String> {
+    if is_opaque_with_alpha(src_image) {
+        copy_pixels(src_image, dst_image)?;
+        return Ok(true);
+    }
+    mul_div
+        .divide_alpha(src_image, dst_image)
+        .map_err(|err| err.to_string())?;
+    Ok(false)
+}
+
+/// Divides RGB-channels of the image by alpha-channel inplace.
+///
+/// Returns `true` if the image is opaque and isn't changed.
+fn divide_alpha_inplace(
+    mul_div: &fir::MulDiv,
+    image: &mut impl IntoImageViewMut,
+) -> Result<bool, String> {
+    if is_opaque_with_alpha(image) {
+        return Ok(true);
     }
+    mul_div
+        .divide_alpha_inplace(image)
+        .map_err(|err| err.to_string())?;
+    Ok(false)
 }
@@ -0,0 +1,550 @@
+//! Reordering, adding and removing of channels of images.
+use fast_image_resize as fr;
+use fast_image_resize::pixels::{
+    F32, F32x2, F32x3, F32x4, I32, U8, U8x2, U8x3, U8x4, U16, U16x2, U16x3, U16x4,
+};
+use fast_image_resize::{
+    ImageView, ImageViewMut, IntoImageView, IntoImageViewMut, PixelTrait, PixelType,
+};
+
+use crate::threading::{try_for_each_row, try_for_each_row_pair, try_for_each_split_row};
+
+/// Source of a channel of destination pixels: an index of channel
+/// of source pixels or `None` to fill the channel with a value.
+pub(crate) type ChannelSource = Option<usize>;
+
+/// Pixel component that can be used to fill a channel.
+trait FillComponent: Copy + Send + Sync {
+    /// Value of an opaque alpha channel.
+    const OPAQUE: Self;
+
+    fn from_f64(value: f64) -> Self;
+}
+
+impl FillComponent for u8 {
+    const OPAQUE: Self = u8::MAX;
+
+    fn from_f64(value: f64) -> Self {
+        value.round() as u8
+    }
+}
+
+impl FillComponent for u16 {
+    const OPAQUE: Self = u16::MAX;
+
+    fn from_f64(value: f64) -> Self {
+        value.round() as u16
+    }
+}
+
+impl FillComponent for i32 {
+    const OPAQUE: Self = i32::MAX;
+
+    fn from_f64(value: f64) -> Self {
+        value.round() as i32
+    }
+}
+
+impl FillComponent for f32 {
+    const OPAQUE: Self = 1.;
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+/// Returns count of channels of pixels.
+pub(crate) fn count_of_channels(pixel_type: PixelType) -> usize {
+    match pixel_type {
+        PixelType::U8x2 | PixelType::U16x2 | PixelType::F32x2 => 2,
+        PixelType::U8x3 | PixelType::U16x3 | PixelType::F32x3 => 3,
+        PixelType::U8x4 | PixelType::U16x4 | PixelType::F32x4 => 4,
+        _ => 1,
+    }
+}
+
+/// Returns type of pixels with the same type of components
+/// and the given count of channels.
+pub(crate) fn pixel_type_with_channels(
+    pixel_type: PixelType,
+    channels: usize,
+) -> Option<PixelType> {
+    use PixelType as PT;
+
+    let family = match pixel_type {
+        PT::U8 | PT::U8x2 | PT::U8x3 | PT::U8x4 => [PT::U8, PT::U8x2, PT::U8x3, PT::U8x4],
+        PT::U16 | PT::U16x2 | PT::U16x3 | PT::U16x4 => [PT::U16, PT::U16x2, PT::U16x3, PT::U16x4],
+        PT::F32 | PT::F32x2 | PT::F32x3 | PT::F32x4 => [PT::F32, PT::F32x2, PT::F32x3, PT::F32x4],
+        PT::I32 if channels == 1 => return Some(PT::I32),
+        _ => return None,
+    };
+    channels.checked_sub(1).and_then(|i| family.get(i)).copied()
+}
+
+/// Returns `true` if the last channel of pixels with
+/// the given count of channels is an alpha channel.
+pub(crate) fn has_alpha(channels: usize) -> bool {
+    channels == 2 || channels == 4
+}
+
+/// Operation with channels of pixels of source and destination
+/// images with the same type of pixel components.
+trait ChannelsOperation: Sync {
+    fn process<S, D>(
+        &self,
+        src_view: &impl ImageView<Pixel = S>,
+        dst_view: &mut impl ImageViewMut<Pixel = D>,
+    ) -> Result<(), String>
+    where
+        S: PixelTrait<Component: FillComponent>,
+        D: PixelTrait<Component = S::Component>;
+}
+
+/// Copies channels of source pixels in the given order.
+struct Swizzle<'a> {
+    order: &'a [ChannelSource],
+    fill_value: Option<f64>,
+}
+
+impl ChannelsOperation for Swizzle<'_> {
+    fn process<S, D>(
+        &self,
+        src_view: &impl ImageView<Pixel = S>,
+        dst_view: &mut impl ImageViewMut<Pixel = D>,
+    ) -> Result<(), String>
+    where
+        S: PixelTrait<Component: FillComponent>,
+        D: PixelTrait<Component = S::Component>,
+    {
+        let fill_value = self.fill_value.map_or(
+            <S::Component as FillComponent>::OPAQUE,
+            <S::Component as FillComponent>::from_f64,
+        );
+        let src_channels = S::count_of_components();
+        let dst_channels = D::count_of_components();
+        try_for_each_row_pair(src_view, dst_view, |src_row, dst_row| {
+            let src_pixels = S::components(src_row).chunks_exact(src_channels);
+            let dst_pixels = D::components_mut(dst_row).chunks_exact_mut(dst_channels);
+            for (src_pixel, dst_pixel) in src_pixels.zip(dst_pixels) {
+                for (dst, source) in dst_pixel.iter_mut().zip(self.order) {
+                    *dst = match *source {
+                        Some(channel) => src_pixel[channel],
+                        None => fill_value,
+                    };
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Copies one channel of source pixels into one channel
+/// of destination pixels. Other channels of destination pixels
+/// are not changed.
+struct CopyChannel {
+    src_channel: usize,
+    dst_channel: usize,
+}
+
+impl ChannelsOperation for CopyChannel {
+    fn process<S, D>(
+        &self,
+        src_view: &impl ImageView<Pixel = S>,
+        dst_view: &mut impl ImageViewMut<Pixel = D>,
+    ) -> Result<(), String>
+    where
+        S: PixelTrait<Component: FillComponent>,
+        D: PixelTrait<Component = S::Component>,
+    {
+        let src_channels = S::count_of_components();
+        let dst_channels = D::count_of_components();
+        try_for_each_row_pair(src_view, dst_view, |src_row, dst_row| {
+            let src_pixels = S::components(src_row).chunks_exact(src_channels);
+            let dst_pixels = D::components_mut(dst_row).chunks_exact_mut(dst_channels);
+            for (src_pixel, dst_pixel) in src_pixels.zip(dst_pixels) {
+                dst_pixel[self.dst_channel] = src_pixel[self.src_channel];
+            }
+            Ok(())
+        })
+    }
+}
+
+fn process_typed<S, D>(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    operation: &impl ChannelsOperation,
+) -> Result<(), String>
+where
+    S: PixelTrait<Component: FillComponent>,
+    D: PixelTrait<Component = S::Component>,
+{
+    let (Some(src_view), Some(mut dst_view)) =
+        (src_image.image_view::<S>(), dst_image.image_view_mut::<D>())
+    else {
+        return Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string());
+    };
+    operation.process(&src_view, &mut dst_view)
+}
+
+/// Applies the operation to images with the same type
+/// of pixel components.
+fn process_channels(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    operation: &impl ChannelsOperation,
+) -> Result<(), String> {
+    if src_image.width() != dst_image.width() || src_image.height() != dst_image.height() {
+        return Err(fr::MappingError::DifferentDimensions.to_string());
+    }
+    let (Some(src_pixel_type), Some(dst_pixel_type)) =
+        (src_image.pixel_type(), dst_image.pixel_type())
+    else {
+        return Err(fr::ImageError::UnsupportedPixelType.to_string());
+    };
+
+    use PixelType as PT;
+
+    // Names of variants of `PixelType` are the same as names of pixel types
+    macro_rules! process_into {
+        ($src: ident, [$($dst: ident),+]) => {
+            match dst_pixel_type {
+                $(PT::$dst => process_typed::<$src, $dst>(src_image, dst_image, operation),)+
+                _ => Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string()),
+            }
+        };
+    }
+
+    match src_pixel_type {
+        PT::U8 => process_into!(U8, [U8, U8x2, U8x3, U8x4]),
+        PT::U8x2 => process_into!(U8x2, [U8, U8x2, U8x3, U8x4]),
+        PT::U8x3 => process_into!(U8x3, [U8, U8x2, U8x3, U8x4]),
+        PT::U8x4 => process_into!(U8x4, [U8, U8x2, U8x3, U8x4]),
+        PT::U16 => process_into!(U16, [U16, U16x2, U16x3, U16x4]),
+        PT::U16x2 => process_into!(U16x2, [U16, U16x2, U16x3, U16x4]),
+        PT::U16x3 => process_into!(U16x3, [U16, U16x2, U16x3, U16x4]),
+        PT::U16x4 => process_into!(U16x4, [U16, U16x2, U16x3, U16x4]),
+        PT::I32 => process_into!(I32, [I32]),
+        PT::F32 => process_into!(F32, [F32, F32x2, F32x3, F32x4]),
+        PT::F32x2 => process_into!(F32x2, [F32, F32x2, F32x3, F32x4]),
+        PT::F32x3 => process_into!(F32x3, [F32, F32x2, F32x3, F32x4]),
+        PT::F32x4 => process_into!(F32x4, [F32, F32x2, F32x3, F32x4]),
+        _ => Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string()),
+    }
+}
+
+/// Copies channels of pixels of the source image into channels
+/// of pixels of the destination image in the given order.
+///
+/// Both images must have the same type of pixel components.
+/// Channels without a source are filled with `fill_value`
+/// (the value of opaque alpha channel by default).
+pub(crate) fn swizzle_pixels(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    order: &[ChannelSource],
+    fill_value: Option<f64>,
+) -> Result<(), String> {
+    if let (Some(src_pixel_type), Some(dst_pixel_type)) =
+        (src_image.pixel_type(), dst_image.pixel_type())
+    {
+        check_order(src_pixel_type, dst_pixel_type, order)?;
+    }
+    process_channels(src_image, dst_image, &Swizzle { order, fill_value })
+}
+
+/// Copies pixels of the source image into the destination image
+/// with the same type of pixels.
+pub(crate) fn copy_pixels(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+) -> Result<(), String> {
+    let pixel_type = src_image
+        .pixel_type()
+        .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string())?;
+    if dst_image.pixel_type() != Some(pixel_type) {
+        return Err(fr::ResizeError::PixelTypesAreDifferent.to_string());
+    }
+    let order: Vec<ChannelSource> = (0..count_of_channels(pixel_type)).map(Some).collect();
+    let operation = Swizzle {
+        order: &order,
+        fill_value: None,
+    };
+    process_channels(src_image, dst_image, &operation)
+}
+
+/// Copies one channel of pixels of the source image into one channel
+/// of pixels of the destination image with the same type
+/// of pixel components.
+pub(crate) fn copy_channel(
+    src_image: &impl IntoImageView,
+    src_channel: usize,
+    dst_image: &mut impl IntoImageViewMut,
+    dst_channel: usize,
+) -> Result<(), String> {
+    for (image_pixel_type, channel) in [
+        (src_image.pixel_type(), src_channel),
+        (dst_image.pixel_type(), dst_channel),
+    ] {
+        if let Some(pixel_type) = image_pixel_type
+            && channel >= count_of_channels(pixel_type)
+        {
+            return Err(format!(
+                "Index of channel {channel} is out of range of {pixel_type:?} pixels"
+            ));
+        }
+    }
+    let operation = CopyChannel {
+        src_channel,
+        dst_channel,
+    };
+    process_channels(src_image, dst_image, &operation)
+}
+
+#[inline(always)]
+fn split_pixels<C: Copy, const N: usize>(src_row: &[C], dst_rows: &mut [&mut [C]; N]) {
+    for (x, pixel) in src_row.chunks_exact(N).enumerate() {
+        for (dst_row, &component) in dst_rows.iter_mut().zip(pixel) {
+            dst_row[x] = component;
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+fn split_pixels_avx2<C: Copy, const N: usize>(src_row: &[C], dst_rows: &mut [&mut [C]; N]) {
+    split_pixels(src_row, dst_rows)
+}
+
+/// Copies every channel of the source row into the destination row
+/// with the same index in one pass.
+///
+/// The loop is vectorized by the compiler. On x86_64 the code compiled
+/// with AVX2 is selected at runtime, because SSE2 (the baseline
+/// of x86_64) has no shuffles to vectorize deinterleaving of channels
+/// (it is 7-16 times faster for U8 pixels).
+fn split_row<C: Copy, const N: usize>(
+    src_row: &[C],
+    dst_rows: Vec<&mut [C]>,
+) -> Result<(), String> {
+    let width = src_row.len() / N;
+    let count = dst_rows.len();
+    let Ok(mut dst_rows) = <[&mut [C]; N]>::try_from(dst_rows) else {
+        return Err(format!(
+            "{N} rows are required to split pixels, got {count}"
+        ));
+    };
+    for dst_row in dst_rows.iter_mut() {
+        if dst_row.len() < width {
+            return Err(fr::MappingError::DifferentDimensions.to_string());
+        }
+        *dst_row = &mut std::mem::take(dst_row)[..width];
+    }
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        // SAFETY: the CPU supports AVX2
+        unsafe { split_pixels_avx2(src_row, &mut dst_rows) };
+        return Ok(());
+    }
+    split_pixels(src_row, &mut dst_rows);
+    Ok(())
+}
+
+fn split_typed<S, D, const N: usize>(
+    src_image: &impl IntoImageView,
+    dst_images: &mut [impl IntoImageViewMut],
+) -> Result<(), String>
+where
+    S: PixelTrait,
+    D: PixelTrait<Component = S::Component>,
+{
+    let Some(src_view) = src_image.image_view::<S>() else {
+        return Err(fr::ImageError::UnsupportedPixelType.to_string());
+    };
+    let Some(mut dst_views) = dst_images
+        .iter_mut()
+        .map(|image| image.image_view_mut::<D>())
+        .collect::<Option<Vec<_>>>()
+    else {
+        return Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string());
+    };
+    try_for_each_split_row(&src_view, &mut dst_views, |src_row, dst_rows| {
+        let dst_rows = dst_rows.into_iter().map(D::components_mut).collect();
+        split_row::<_, N>(S::components(src_row), dst_rows)
+    })
+}
+
+/// Copies every channel of pixels of the source image into
+/// the destination image with one channel and the same type
+/// of pixel components.
+///
+/// All channels are copied in one pass over rows of the source image.
+pub(crate) fn split_channels(
+    src_image: &impl IntoImageView,
+    dst_images: &mut [impl IntoImageViewMut],
+) -> Result<(), String> {
+    let pixel_type = src_image
+        .pixel_type()
+        .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string())?;
+    let channels = count_of_channels(pixel_type);
+    if dst_images.len() != channels {
+        return Err(format!(
+            "{channels} images are required to split {pixel_type:?} pixels"
+        ));
+    }
+    for dst_image in dst_images.iter() {
+        if src_image.width() != dst_image.width() || src_image.height() != dst_image.height() {
+            return Err(fr::MappingError::DifferentDimensions.to_string());
+        }
+    }
+
+    use PixelType as PT;
+
+    match pixel_type {
+        PT::U8 => split_typed::<U8, U8, 1>(src_image, dst_images),
+        PT::U8x2 => split_typed::<U8x2, U8, 2>(src_image, dst_images),
+        PT::U8x3 => split_typed::<U8x3, U8, 3>(src_image, dst_images),
+        PT::U8x4 => split_typed::<U8x4, U8, 4>(src_image, dst_images),
+        PT::U16 => split_typed::<U16, U16, 1>(src_image, dst_images),
+        PT::U16x2 => split_typed::<U16x2, U16, 2>(src_image, dst_images),
+        PT::U16x3 => split_typed::<U16x3, U16, 3>(src_image, dst_images),
+        PT::U16x4 => split_typed::<U16x4, U16, 4>(src_image, dst_images),
+        PT::I32 => split_typed::<I32, I32, 1>(src_image, dst_images),
+        PT::F32 => split_typed::<F32, F32, 1>(src_image, dst_images),
+        PT::F32x2 => split_typed::<F32x2, F32, 2>(src_image, dst_images),
+        PT::F32x3 => split_typed::<F32x3, F32, 3>(src_image, dst_images),
+        PT::F32x4 => split_typed::<F32x4, F32, 4>(src_image, dst_images),
+        _ => Err(fr::ImageError::UnsupportedPixelType.to_string()),
+    }
+}
+
+fn fill_typed<P>(image: &mut impl IntoImageViewMut, color: &[f64]) -> Result<(), String>
+where
+    P: PixelTrait<Component: FillComponent>,
+{
+    let Some(mut view) = image.image_view_mut::<P>() else {
+        return Err(fr::ImageError::UnsupportedPixelType.to_string());
+    };
+    let color: Vec<P::Component> = color.iter().map(|&v| FillComponent::from_f64(v)).collect();
+    try_for_each_row(&mut view, |row| {
+        for pixel in P::components_mut(row).chunks_exact_mut(color.len()) {
+            pixel.copy_from_slice(&color);
+        }
+        Ok(())
+    })
+}
+
+/// Fills all pixels of the image with the color.
+///
+/// The color must have a value for every channel of pixels.
+pub(crate) fn fill_pixels(image: &mut impl IntoImageViewMut, color: &[f64]) -> Result<(), String> {
+    let pixel_type = image
+        .pixel_type()
+        .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string())?;
+    let channels = count_of_channels(pixel_type);
+    if color.len() != channels {
+        return Err(format!(
+            "Color must have {channels} components for {pixel_type:?} pixels"
+        ));
+    }
+
+    use PixelType as PT;
+
+    match pixel_type {
+        PT::U8 => fill_typed::<U8>(image, color),
+        PT::U8x2 => fill_typed::<U8x2>(image, color),
+        PT::U8x3 => fill_typed::<U8x3>(image, color),
+        PT::U8x4 => fill_typed::<U8x4>(image, color),
+        PT::U16 => fill_typed::<U16>(image, color),
+        PT::U16x2 => fill_typed::<U16x2>(image, color),
+        PT::U16x3 => fill_typed::<U16x3>(image, color),
+        PT::U16x4 => fill_typed::<U16x4>(image, color),
+        PT::I32 => fill_typed::<I32>(image, color),
+        PT::F32 => fill_typed::<F32>(image, color),
+        PT::F32x2 => fill_typed::<F32x2>(image, color),
+        PT::F32x3 => fill_typed::<F32x3>(image, color),
+        PT::F32x4 => fill_typed::<F32x4>(image, color),
+        _ => Err(fr::ImageError::UnsupportedPixelType.to_string()),
+    }
+}
+
+/// Returns bands (left, top, width, height) of the outer region
+/// around the inner region: the top and bottom bands with the width
+/// of the outer region, then the left and right bands with the height
+/// of the inner region.
+fn bands_around(
+    outer: (u32, u32, u32, u32),
+    inner: (u32, u32, u32, u32),
+) -> [(u32, u32, u32, u32); 4] {
+    let (outer_left, outer_top, outer_width, outer_height) = outer;
+    let (left, top, width, height) = inner;
+    let outer_bottom = outer_top + outer_height;
+    let outer_right = outer_left + outer_width;
+    let bottom = top + height;
+    let right = left + width;
+    [
+        (outer_left, outer_top, outer_width, top - outer_top),
+        (outer_left, bottom, outer_width, outer_bottom - bottom),
+        (outer_left, top, left - outer_left, height),
+        (right, top, outer_right - right, height),
+    ]
+}
+
+/// Fills bands of the outer region (left, top, width, height)
+/// of the image around the inner region with the color.
+pub(crate) fn fill_around_region(
+    image: &mut impl IntoImageViewMut,
+    outer: (u32, u32, u32, u32),
+    inner: (u32, u32, u32, u32),
+    color: &[f64],
+) -> Result<(), String> {
+    for (left, top, width, height) in bands_around(outer, inner) {
+        if width == 0 || height == 0 {
+            continue;
+        }
+        let mut band = fr::images::CroppedImageMut::new(image, left, top, width, height)
+            .map_err(|err| err.to_string())?;
+        fill_pixels(&mut band, color)?;
+    }
+    Ok(())
+}
+
+/// Checks that the order of channels is valid for the given
+/// source and destination types of pixels.
+pub(crate) fn check_order(
+    src_pixel_type: PixelType,
+    dst_pixel_type: PixelType,
+    order: &[ChannelSource],
+) -> Result<(), String> {
+    let dst_channels = count_of_channels(dst_pixel_type);
+    if order.len() != dst_channels {
+        return Err(format!(
+            "Order of channels must have {dst_channels} items for {dst_pixel_type:?} pixels"
+        ));
+    }
+    let src_channels = count_of_channels(src_pixel_type);
+    if let Some(channel) = order.iter().flatten().find(|&&c| c >= src_channels) {
+        return Err(format!(
+            "Index of channel {channel} is out of range of {src_pixel_type:?} pixels"
+        ));
+    }
+    Ok(())
+}
+
+/// Returns `true` if reordering of channels keeps meaning of
+/// the alpha channel, so the image can be resized with considering
+/// of alpha channel before reordering of channels.
+pub(crate) fn is_alpha_preserved(src_channels: usize, order: &[ChannelSource]) -> bool {
+    let src_alpha = has_alpha(src_channels).then(|| src_channels - 1);
+    let (color_channels, dst_alpha) = match order.split_last() {
+        Some((&alpha, colors)) if has_alpha(order.len()) => (colors, Some(alpha)),
+        _ => (order, None),
+    };
+    if src_alpha.is_some() && color_channels.contains(&src_alpha) {
+        return false;
+    }
+    match src_alpha {
+        Some(_) => dst_alpha == Some(src_alpha),
+        // Filled alpha channel is opaque, so it doesn't affect resizing
+        None => dst_alpha.is_none_or(|alpha| alpha.is_none()),
+    }
+}
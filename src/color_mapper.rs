@@ -0,0 +1,713 @@
+//! Conversion of images between colorspaces with different transfer functions.
+use std::slice;
+use std::sync::OnceLock;
+
+use fast_image_resize as fr;
+use fast_image_resize::pixels::{
+    F32, F32x2, F32x3, F32x4, U8, U8x2, U8x3, U8x4, U16, U16x2, U16x3, U16x4,
+};
+use fast_image_resize::{IntoImageView, IntoImageViewMut, PixelTrait};
+use pyo3::prelude::*;
+
+use crate::image_view::Image;
+use crate::pil_image_wrapper::PilImageWrapper;
+use crate::thread_pool::{ResizerThreadPool, thread_pool_for_image};
+use crate::threading::{try_for_each_row, try_for_each_row_pair};
+use crate::utils::result2pyresult;
+
+/// Transfer function of a colorspace of images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TransferFunction {
+    Srgb,
+    Gamma22,
+}
+
+impl TransferFunction {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Srgb),
+            2 => Some(Self::Gamma22),
+            _ => None,
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            Self::Srgb => 1,
+            Self::Gamma22 => 2,
+        }
+    }
+
+    /// Returns a mapper to convert an image into linear colorspace and back.
+    ///
+    /// Mappers contain big tables, so they are created only once.
+    pub fn mapper(&self) -> &'static fr::PixelComponentMapper {
+        static SRGB: OnceLock<fr::PixelComponentMapper> = OnceLock::new();
+        static GAMMA_22: OnceLock<fr::PixelComponentMapper> = OnceLock::new();
+        match self {
+            Self::Srgb => SRGB.get_or_init(fr::create_srgb_mapper),
+            Self::Gamma22 => GAMMA_22.get_or_init(fr::create_gamma_22_mapper),
+        }
+    }
+
+    /// Maps values of components of the source image into linear
+    /// colorspace and stores them into the destination image.
+    pub(crate) fn forward_map(
+        &self,
+        src_image: &impl IntoImageView,
+        dst_image: &mut impl IntoImageViewMut,
+    ) -> Result<(), String> {
+        map_image(src_image, dst_image, *self, Direction::Forward)
+    }
+
+    /// Maps values of components of the source image from linear
+    /// colorspace and stores them into the destination image.
+    pub(crate) fn backward_map(
+        &self,
+        src_image: &impl IntoImageView,
+        dst_image: &mut impl IntoImageViewMut,
+    ) -> Result<(), String> {
+        map_image(src_image, dst_image, *self, Direction::Backward)
+    }
+
+    fn map_func(&self, direction: Direction) -> fn(f32) -> f32 {
+        match (self, direction) {
+            (Self::Srgb, Direction::Forward) => srgb_to_linear,
+            (Self::Srgb, Direction::Backward) => linear_to_srgb,
+            (Self::Gamma22, Direction::Forward) => gamma_into_linear,
+            (Self::Gamma22, Direction::Backward) => linear_into_gamma,
+        }
+    }
+}
+
+fn gamma_into_linear(input: f32) -> f32 {
+    input.max(0.).powf(2.2)
+}
+
+fn linear_into_gamma(input: f32) -> f32 {
+    input.max(0.).powf(1.0 / 2.2)
+}
+
+/// https://en.wikipedia.org/wiki/SRGB#From_sRGB_to_CIE_XYZ
+fn srgb_to_linear(input: f32) -> f32 {
+    if input < 0.04045 {
+        input / 12.92
+    } else {
+        const A: f32 = 0.055;
+        ((input + A) / (1. + A)).powf(2.4)
+    }
+}
+
+/// https://en.wikipedia.org/wiki/SRGB#From_CIE_XYZ_to_sRGB
+fn linear_to_srgb(input: f32) -> f32 {
+    if input < 0.0031308 {
+        12.92 * input
+    } else {
+        const A: f32 = 0.055;
+        (1. + A) * input.powf(1. / 2.4) - A
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// From the colorspace into linear colorspace.
+    Forward,
+    /// From linear colorspace into the colorspace.
+    Backward,
+}
+
+/// Component of a pixel that can be mapped with a transfer function.
+///
+/// Values of components are normalized into the range `[0.0, 1.0]`.
+trait MappedComponent: Copy + Send + Sync + 'static {
+    /// Size of a lookup table with mapped values of all possible
+    /// values of the component. Zero means that values must be mapped
+    /// with the function directly.
+    const TABLE_SIZE: usize;
+    /// Images with such components are supported by `fr::PixelComponentMapper`.
+    const HAS_FR_MAPPER: bool;
+
+    fn to_unit(self) -> f32;
+
+    fn from_unit(value: f32) -> Self;
+
+    fn map(self, table: &[f32], func: fn(f32) -> f32) -> f32;
+}
+
+impl MappedComponent for u8 {
+    const TABLE_SIZE: usize = 1 << 8;
+    const HAS_FR_MAPPER: bool = true;
+
+    #[inline(always)]
+    fn to_unit(self) -> f32 {
+        self as f32 / u8::MAX as f32
+    }
+
+    #[inline(always)]
+    fn from_unit(value: f32) -> Self {
+        (value.clamp(0., 1.) * u8::MAX as f32).round() as u8
+    }
+
+    #[inline(always)]
+    fn map(self, table: &[f32], _func: fn(f32) -> f32) -> f32 {
+        table[self as usize]
+    }
+}
+
+impl MappedComponent for u16 {
+    const TABLE_SIZE: usize = 1 << 16;
+    const HAS_FR_MAPPER: bool = true;
+
+    #[inline(always)]
+    fn to_unit(self) -> f32 {
+        self as f32 / u16::MAX as f32
+    }
+
+    #[inline(always)]
+    fn from_unit(value: f32) -> Self {
+        (value.clamp(0., 1.) * u16::MAX as f32).round() as u16
+    }
+
+    #[inline(always)]
+    fn map(self, table: &[f32], _func: fn(f32) -> f32) -> f32 {
+        table[self as usize]
+    }
+}
+
+impl MappedComponent for f32 {
+    const TABLE_SIZE: usize = 0;
+    const HAS_FR_MAPPER: bool = false;
+
+    #[inline(always)]
+    fn to_unit(self) -> f32 {
+        self
+    }
+
+    #[inline(always)]
+    fn from_unit(value: f32) -> Self {
+        value
+    }
+
+    #[inline(always)]
+    fn map(self, _table: &[f32], func: fn(f32) -> f32) -> f32 {
+        func(self)
+    }
+}
+
+/// Function and lookup table to map values of components of
+/// the source image.
+struct Mapping {
+    func: fn(f32) -> f32,
+    table: &'static [f32],
+}
+
+impl Mapping {
+    fn new<C: MappedComponent>(transfer_function: TransferFunction, direction: Direction) -> Self {
+        // Tables are filled lazily and shared between all mappers.
+        static TABLES: [OnceLock<Box<[f32]>>; 8] = [const { OnceLock::new() }; 8];
+
+        let func = transfer_function.map_func(direction);
+        let table: &'static [f32] = if C::TABLE_SIZE == 0 {
+            &[]
+        } else {
+            let index = (transfer_function.as_u8() as usize - 1) * 4
+                + (direction as usize) * 2
+                + (C::TABLE_SIZE > 256) as usize;
+            TABLES[index].get_or_init(|| {
+                let max_value = (C::TABLE_SIZE - 1) as f32;
+                (0..C::TABLE_SIZE)
+                    .map(|value| func(value as f32 / max_value))
+                    .collect()
+            })
+        };
+        Self { func, table }
+    }
+}
+
+#[inline]
+fn has_alpha(count_of_components: usize) -> bool {
+    count_of_components == 2 || count_of_components == 4
+}
+
+fn map_row<S, D>(src_row: &[S], dst_row: &mut [D], mapping: &Mapping)
+where
+    S: PixelTrait<Component: MappedComponent>,
+    D: PixelTrait<Component: MappedComponent>,
+{
+    let count = S::count_of_components();
+    let color_count = if has_alpha(count) { count - 1 } else { count };
+    let src_components = S::components(src_row);
+    let dst_components = D::components_mut(dst_row);
+    for (src_pixel, dst_pixel) in src_components
+        .chunks_exact(count)
+        .zip(dst_components.chunks_exact_mut(count))
+    {
+        for i in 0..color_count {
+            let value = src_pixel[i].map(mapping.table, mapping.func);
+            dst_pixel[i] = D::Component::from_unit(value);
+        }
+        // Alpha channel is only converted into the type of destination component
+        for i in color_count..count {
+            dst_pixel[i] = D::Component::from_unit(src_pixel[i].to_unit());
+        }
+    }
+}
+
+fn map_row_inplace<P>(row: &mut [P], mapping: &Mapping)
+where
+    P: PixelTrait<Component: MappedComponent>,
+{
+    let count = P::count_of_components();
+    let color_count = if has_alpha(count) { count - 1 } else { count };
+    for pixel in P::components_mut(row).chunks_exact_mut(count) {
+        for component in pixel[..color_count].iter_mut() {
+            *component = P::Component::from_unit(component.map(mapping.table, mapping.func));
+        }
+    }
+}
+
+fn pixels_as_bytes<P: PixelTrait>(pixels: &[P]) -> &[u8] {
+    // Pixels are arrays of primitive components without padding.
+    unsafe { slice::from_raw_parts(pixels.as_ptr().cast(), size_of_val(pixels)) }
+}
+
+fn pixels_as_bytes_mut<P: PixelTrait>(pixels: &mut [P]) -> &mut [u8] {
+    unsafe { slice::from_raw_parts_mut(pixels.as_mut_ptr().cast(), size_of_val(pixels)) }
+}
+
+/// Maps a row of pixels with tables of `fr::PixelComponentMapper`.
+fn map_row_with_fr_mapper<S, D>(
+    src_row: &[S],
+    dst_row: &mut [D],
+    transfer_function: TransferFunction,
+    direction: Direction,
+) -> Result<(), String>
+where
+    S: PixelTrait,
+    D: PixelTrait,
+{
+    let width = src_row.len() as u32;
+    let src_image = fr::images::ImageRef::new(width, 1, pixels_as_bytes(src_row), S::pixel_type())
+        .map_err(|err| err.to_string())?;
+    let mut dst_image =
+        fr::images::Image::from_slice_u8(width, 1, pixels_as_bytes_mut(dst_row), D::pixel_type())
+            .map_err(|err| err.to_string())?;
+    let mapper = transfer_function.mapper();
+    match direction {
+        Direction::Forward => mapper.forward_map(&src_image, &mut dst_image),
+        Direction::Backward => mapper.backward_map(&src_image, &mut dst_image),
+    }
+    .map_err(|err| err.to_string())
+}
+
+/// Maps a row of pixels in place with tables of `fr::PixelComponentMapper`.
+fn map_row_inplace_with_fr_mapper<P: PixelTrait>(
+    row: &mut [P],
+    transfer_function: TransferFunction,
+    direction: Direction,
+) -> Result<(), String> {
+    let width = row.len() as u32;
+    let mut image =
+        fr::images::Image::from_slice_u8(width, 1, pixels_as_bytes_mut(row), P::pixel_type())
+            .map_err(|err| err.to_string())?;
+    let mapper = transfer_function.mapper();
+    match direction {
+        Direction::Forward => mapper.forward_map_inplace(&mut image),
+        Direction::Backward => mapper.backward_map_inplace(&mut image),
+    }
+    .map_err(|err| err.to_string())
+}
+
+/// Maps the source image into the destination image.
+///
+/// Images with `U8xN` and `U16xN` pixels are mapped with
+/// `fr::PixelComponentMapper`, the rest ones - with own
+/// lookup tables or the transfer function directly.
+fn map_image_typed<S, D>(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    transfer_function: TransferFunction,
+    direction: Direction,
+) -> Result<(), String>
+where
+    S: PixelTrait<Component: MappedComponent>,
+    D: PixelTrait<Component: MappedComponent>,
+{
+    let (Some(src_view), Some(mut dst_view)) =
+        (src_image.image_view::<S>(), dst_image.image_view_mut::<D>())
+    else {
+        return Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string());
+    };
+    if S::Component::HAS_FR_MAPPER && D::Component::HAS_FR_MAPPER {
+        return try_for_each_row_pair(&src_view, &mut dst_view, |src_row, dst_row| {
+            map_row_with_fr_mapper(src_row, dst_row, transfer_function, direction)
+        });
+    }
+    let mapping = Mapping::new::<S::Component>(transfer_function, direction);
+    try_for_each_row_pair(&src_view, &mut dst_view, |src_row, dst_row| {
+        map_row(src_row, dst_row, &mapping);
+        Ok(())
+    })
+}
+
+/// Maps the source image into the destination image with
+/// the same count of components and pixels of the given types.
+fn map_image_into<S, D8, D16, D32>(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    transfer_function: TransferFunction,
+    direction: Direction,
+) -> Result<(), String>
+where
+    S: PixelTrait<Component: MappedComponent>,
+    D8: PixelTrait<Component: MappedComponent>,
+    D16: PixelTrait<Component: MappedComponent>,
+    D32: PixelTrait<Component: MappedComponent>,
+{
+    match dst_image.pixel_type() {
+        Some(pixel_type) if pixel_type == D8::pixel_type() => {
+            map_image_typed::<S, D8>(src_image, dst_image, transfer_function, direction)
+        }
+        Some(pixel_type) if pixel_type == D16::pixel_type() => {
+            map_image_typed::<S, D16>(src_image, dst_image, transfer_function, direction)
+        }
+        Some(pixel_type) if pixel_type == D32::pixel_type() => {
+            map_image_typed::<S, D32>(src_image, dst_image, transfer_function, direction)
+        }
+        _ => Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string()),
+    }
+}
+
+fn map_image(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    transfer_function: TransferFunction,
+    direction: Direction,
+) -> Result<(), String> {
+    if src_image.width() != dst_image.width() || src_image.height() != dst_image.height() {
+        return Err(fr::MappingError::DifferentDimensions.to_string());
+    }
+    use fr::PixelType as PT;
+
+    macro_rules! map_into {
+        ($s: ty, $d8: ty, $d16: ty, $d32: ty) => {
+            map_image_into::<$s, $d8, $d16, $d32>(
+                src_image,
+                dst_image,
+                transfer_function,
+                direction,
+            )
+        };
+    }
+
+    match src_image.pixel_type() {
+        Some(PT::U8) => map_into!(U8, U8, U16, F32),
+        Some(PT::U8x2) => map_into!(U8x2, U8x2, U16x2, F32x2),
+        Some(PT::U8x3) => map_into!(U8x3, U8x3, U16x3, F32x3),
+        Some(PT::U8x4) => map_into!(U8x4, U8x4, U16x4, F32x4),
+        Some(PT::U16) => map_into!(U16, U8, U16, F32),
+        Some(PT::U16x2) => map_into!(U16x2, U8x2, U16x2, F32x2),
+        Some(PT::U16x3) => map_into!(U16x3, U8x3, U16x3, F32x3),
+        Some(PT::U16x4) => map_into!(U16x4, U8x4, U16x4, F32x4),
+        Some(PT::F32) => map_into!(F32, U8, U16, F32),
+        Some(PT::F32x2) => map_into!(F32x2, U8x2, U16x2, F32x2),
+        Some(PT::F32x3) => map_into!(F32x3, U8x3, U16x3, F32x3),
+        Some(PT::F32x4) => map_into!(F32x4, U8x4, U16x4, F32x4),
+        _ => Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string()),
+    }
+}
+
+fn map_image_inplace_typed<P>(
+    image: &mut impl IntoImageViewMut,
+    transfer_function: TransferFunction,
+    direction: Direction,
+) -> Result<(), String>
+where
+    P: PixelTrait<Component: MappedComponent>,
+{
+    let Some(mut view) = image.image_view_mut::<P>() else {
+        return Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string());
+    };
+    if P::Component::HAS_FR_MAPPER {
+        return try_for_each_row(&mut view, |row| {
+            map_row_inplace_with_fr_mapper(row, transfer_function, direction)
+        });
+    }
+    let mapping = Mapping::new::<P::Component>(transfer_function, direction);
+    try_for_each_row(&mut view, |row| {
+        map_row_inplace(row, &mapping);
+        Ok(())
+    })
+}
+
+fn map_image_inplace(
+    image: &mut impl IntoImageViewMut,
+    transfer_function: TransferFunction,
+    direction: Direction,
+) -> Result<(), String> {
+    use fr::PixelType as PT;
+
+    macro_rules! map_inplace {
+        ($p: ty) => {
+            map_image_inplace_typed::<$p>(image, transfer_function, direction)
+        };
+    }
+
+    match image.pixel_type() {
+        Some(PT::U8) => map_inplace!(U8),
+        Some(PT::U8x2) => map_inplace!(U8x2),
+        Some(PT::U8x3) => map_inplace!(U8x3),
+        Some(PT::U8x4) => map_inplace!(U8x4),
+        Some(PT::U16) => map_inplace!(U16),
+        Some(PT::U16x2) => map_inplace!(U16x2),
+        Some(PT::U16x3) => map_inplace!(U16x3),
+        Some(PT::U16x4) => map_inplace!(U16x4),
+        Some(PT::F32) => map_inplace!(F32),
+        Some(PT::F32x2) => map_inplace!(F32x2),
+        Some(PT::F32x3) => map_inplace!(F32x3),
+        Some(PT::F32x4) => map_inplace!(F32x4),
+        _ => Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string()),
+    }
+}
+
+/// Maps values of pixel components between a colorspace with
+/// the given transfer function and linear colorspace.
+///
+/// Images with `U8xN` and `U16xN` pixels are mapped with
+/// `fr::PixelComponentMapper`. Unlike it, images with `F32xN` pixels
+/// are also supported and images are processed in a thread pool.
+/// Alpha channel is not mapped, it is only converted into
+/// the type of components of the destination image.
+#[pyclass]
+pub struct RustColorMapper {
+    transfer_function: TransferFunction,
+}
+
+impl RustColorMapper {
+    fn map(
+        &self,
+        py: Python,
+        src_image: &(impl IntoImageView + Sync),
+        dst_image: &mut (impl IntoImageViewMut + Send),
+        direction: Direction,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, src_image);
+        let transfer_function = self.transfer_function;
+        py.detach(move || {
+            if let Some(thread_pool) = thread_pool {
+                return thread_pool.run_within(|| {
+                    result2pyresult(map_image(
+                        src_image,
+                        dst_image,
+                        transfer_function,
+                        direction,
+                    ))
+                });
+            }
+            result2pyresult(map_image(
+                src_image,
+                dst_image,
+                transfer_function,
+                direction,
+            ))
+        })
+    }
+
+    fn map_inplace(
+        &self,
+        py: Python,
+        image: &mut (impl IntoImageViewMut + Send),
+        direction: Direction,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, image);
+        let transfer_function = self.transfer_function;
+        py.detach(move || {
+            if let Some(thread_pool) = thread_pool {
+                return thread_pool.run_within(|| {
+                    result2pyresult(map_image_inplace(image, transfer_function, direction))
+                });
+            }
+            result2pyresult(map_image_inplace(image, transfer_function, direction))
+        })
+    }
+}
+
+#[pymethods]
+impl RustColorMapper {
+    /// Transfer function: 1 - sRGB, 2 - gamma 2.2.
+    #[new]
+    #[pyo3(signature = (transfer_function))]
+    fn new(transfer_function: u8) -> PyResult<Self> {
+        let transfer_function = TransferFunction::from_u8(transfer_function).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown transfer function {transfer_function}"
+            ))
+        })?;
+        Ok(Self { transfer_function })
+    }
+
+    /// Returns transfer function.
+    fn get_transfer_function(&self) -> u8 {
+        self.transfer_function.as_u8()
+    }
+
+    /// Maps components of source image into linear colorspace and store
+    /// result into destination image.
+    #[pyo3(signature = (src_image, dst_image, thread_pool=None, threading_threshold=None))]
+    fn forward_map(
+        &self,
+        py: Python,
+        src_image: &Image,
+        dst_image: &mut Image,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        self.map(
+            py,
+            src_image.src_image_view(),
+            dst_image.dst_image_view(),
+            Direction::Forward,
+            thread_pool,
+            threading_threshold,
+        )
+    }
+
+    /// Maps components of image into linear colorspace inplace.
+    #[pyo3(signature = (image, thread_pool=None, threading_threshold=None))]
+    fn forward_map_inplace(
+        &self,
+        py: Python,
+        image: &mut Image,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        self.map_inplace(
+            py,
+            image.dst_image_view(),
+            Direction::Forward,
+            thread_pool,
+            threading_threshold,
+        )
+    }
+
+    /// Maps components of source image from linear colorspace and store
+    /// result into destination image.
+    #[pyo3(signature = (src_image, dst_image, thread_pool=None, threading_threshold=None))]
+    fn backward_map(
+        &self,
+        py: Python,
+        src_image: &Image,
+        dst_image: &mut Image,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        self.map(
+            py,
+            src_image.src_image_view(),
+            dst_image.dst_image_view(),
+            Direction::Backward,
+            thread_pool,
+            threading_threshold,
+        )
+    }
+
+    /// Maps components of image from linear colorspace inplace.
+    #[pyo3(signature = (image, thread_pool=None, threading_threshold=None))]
+    fn backward_map_inplace(
+        &self,
+        py: Python,
+        image: &mut Image,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        self.map_inplace(
+            py,
+            image.dst_image_view(),
+            Direction::Backward,
+            thread_pool,
+            threading_threshold,
+        )
+    }
+
+    /// Maps components of source PIL image into linear colorspace and store
+    /// result into destination PIL image.
+    #[pyo3(signature = (src_image, dst_image, thread_pool=None, threading_threshold=None))]
+    fn forward_map_pil(
+        &self,
+        py: Python,
+        src_image: &PilImageWrapper,
+        dst_image: &mut PilImageWrapper,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        self.map(
+            py,
+            src_image,
+            dst_image,
+            Direction::Forward,
+            thread_pool,
+            threading_threshold,
+        )
+    }
+
+    /// Maps components of PIL image into linear colorspace inplace.
+    #[pyo3(signature = (image, thread_pool=None, threading_threshold=None))]
+    fn forward_map_pil_inplace(
+        &self,
+        py: Python,
+        image: &mut PilImageWrapper,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        self.map_inplace(
+            py,
+            image,
+            Direction::Forward,
+            thread_pool,
+            threading_threshold,
+        )
+    }
+
+    /// Maps components of source PIL image from linear colorspace and store
+    /// result into destination PIL image.
+    #[pyo3(signature = (src_image, dst_image, thread_pool=None, threading_threshold=None))]
+    fn backward_map_pil(
+        &self,
+        py: Python,
+        src_image: &PilImageWrapper,
+        dst_image: &mut PilImageWrapper,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        self.map(
+            py,
+            src_image,
+            dst_image,
+            Direction::Backward,
+            thread_pool,
+            threading_threshold,
+        )
+    }
+
+    /// Maps components of PIL image from linear colorspace inplace.
+    #[pyo3(signature = (image, thread_pool=None, threading_threshold=None))]
+    fn backward_map_pil_inplace(
+        &self,
+        py: Python,
+        image: &mut PilImageWrapper,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        self.map_inplace(
+            py,
+            image,
+            Direction::Backward,
+            thread_pool,
+            threading_threshold,
+        )
+    }
+}
@@ -0,0 +1,255 @@
+//! Compositing of images with alpha channel onto a background color.
+use fast_image_resize as fr;
+use fast_image_resize::pixels::{
+    F32, F32x2, F32x3, F32x4, U8, U8x2, U8x3, U8x4, U16, U16x2, U16x3, U16x4,
+};
+use fast_image_resize::{IntoImageView, IntoImageViewMut, PixelTrait, PixelType};
+
+use crate::channels::{count_of_channels, has_alpha};
+use crate::threading::{try_for_each_row, try_for_each_row_pair};
+
+/// Pixel component that can be composited onto a background.
+trait CompositeComponent: Copy + Send + Sync {
+    /// Value of an opaque alpha channel.
+    const OPAQUE: f32;
+
+    fn to_f32(self) -> f32;
+
+    fn from_f32(value: f32) -> Self;
+}
+
+impl CompositeComponent for u8 {
+    const OPAQUE: f32 = u8::MAX as f32;
+
+    #[inline(always)]
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    #[inline(always)]
+    fn from_f32(value: f32) -> Self {
+        // Float to integer casting saturates values out of range
+        value.round() as u8
+    }
+}
+
+impl CompositeComponent for u16 {
+    const OPAQUE: f32 = u16::MAX as f32;
+
+    #[inline(always)]
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    #[inline(always)]
+    fn from_f32(value: f32) -> Self {
+        value.round() as u16
+    }
+}
+
+impl CompositeComponent for f32 {
+    const OPAQUE: f32 = 1.;
+
+    #[inline(always)]
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    #[inline(always)]
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+/// Returns the value of an opaque alpha channel of pixels
+/// of the given type.
+pub(crate) fn opaque_value(pixel_type: PixelType) -> f64 {
+    use PixelType as PT;
+
+    match pixel_type {
+        PT::U8 | PT::U8x2 | PT::U8x3 | PT::U8x4 => u8::MAX as f64,
+        PT::U16 | PT::U16x2 | PT::U16x3 | PT::U16x4 => u16::MAX as f64,
+        PT::I32 => i32::MAX as f64,
+        _ => 1.,
+    }
+}
+
+/// Composites the color component with the given alpha
+/// (normalized into the range `[0.0, 1.0]`) onto the background.
+#[inline(always)]
+fn composite<C: CompositeComponent>(
+    color: C,
+    alpha: f32,
+    background: f32,
+    premultiplied: bool,
+) -> C {
+    let color = if premultiplied {
+        color.to_f32()
+    } else {
+        color.to_f32() * alpha
+    };
+    C::from_f32(color + background * (1. - alpha))
+}
+
+fn flatten_typed<S, D>(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    background: &[f32],
+    premultiplied: bool,
+) -> Result<(), String>
+where
+    S: PixelTrait<Component: CompositeComponent>,
+    D: PixelTrait<Component = S::Component>,
+{
+    let (Some(src_view), Some(mut dst_view)) =
+        (src_image.image_view::<S>(), dst_image.image_view_mut::<D>())
+    else {
+        return Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string());
+    };
+    let src_channels = S::count_of_components();
+    let dst_channels = D::count_of_components();
+    let opaque = <S::Component as CompositeComponent>::OPAQUE;
+    try_for_each_row_pair(&src_view, &mut dst_view, |src_row, dst_row| {
+        let src_pixels = S::components(src_row).chunks_exact(src_channels);
+        let dst_pixels = D::components_mut(dst_row).chunks_exact_mut(dst_channels);
+        for (src_pixel, dst_pixel) in src_pixels.zip(dst_pixels) {
+            let alpha = src_pixel[src_channels - 1].to_f32() / opaque;
+            let colors = &src_pixel[..src_channels - 1];
+            for ((dst, &color), &background) in dst_pixel.iter_mut().zip(colors).zip(background) {
+                *dst = composite(color, alpha, background, premultiplied);
+            }
+            if has_alpha(dst_channels) {
+                dst_pixel[dst_channels - 1] = CompositeComponent::from_f32(opaque);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Composites pixels of the source image with alpha channel onto
+/// the background color and stores the result into the destination
+/// image with the same type of pixel components.
+///
+/// The background color must have a value for every color channel
+/// of source pixels. Color channels of source pixels may be
+/// premultiplied by alpha. Alpha channel of destination pixels
+/// (if it is present) is filled with the value of opaque alpha.
+pub(crate) fn flatten_alpha(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    background: &[f64],
+    premultiplied: bool,
+) -> Result<(), String> {
+    if src_image.width() != dst_image.width() || src_image.height() != dst_image.height() {
+        return Err(fr::MappingError::DifferentDimensions.to_string());
+    }
+    let (Some(src_pixel_type), Some(dst_pixel_type)) =
+        (src_image.pixel_type(), dst_image.pixel_type())
+    else {
+        return Err(fr::ImageError::UnsupportedPixelType.to_string());
+    };
+    let src_channels = count_of_channels(src_pixel_type);
+    if !has_alpha(src_channels) {
+        return Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string());
+    }
+    let color_channels = src_channels - 1;
+    if background.len() != color_channels {
+        return Err(format!(
+            "Background color must have {color_channels} components \
+            for {src_pixel_type:?} pixels"
+        ));
+    }
+    let background: Vec<f32> = background.iter().map(|&v| v as f32).collect();
+
+    use PixelType as PT;
+
+    // Names of variants of `PixelType` are the same as names of pixel types
+    macro_rules! flatten_into {
+        ($src: ident, [$($dst: ident),+]) => {
+            match dst_pixel_type {
+                $(PT::$dst => flatten_typed::<$src, $dst>(
+                    src_image,
+                    dst_image,
+                    &background,
+                    premultiplied,
+                ),)+
+                _ => Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string()),
+            }
+        };
+    }
+
+    match src_pixel_type {
+        PT::U8x2 => flatten_into!(U8x2, [U8, U8x2]),
+        PT::U8x4 => flatten_into!(U8x4, [U8x3, U8x4]),
+        PT::U16x2 => flatten_into!(U16x2, [U16, U16x2]),
+        PT::U16x4 => flatten_into!(U16x4, [U16x3, U16x4]),
+        PT::F32x2 => flatten_into!(F32x2, [F32, F32x2]),
+        PT::F32x4 => flatten_into!(F32x4, [F32x3, F32x4]),
+        _ => Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string()),
+    }
+}
+
+fn flatten_inplace_typed<P>(
+    image: &mut impl IntoImageViewMut,
+    background: &[f32],
+    premultiplied: bool,
+) -> Result<(), String>
+where
+    P: PixelTrait<Component: CompositeComponent>,
+{
+    let Some(mut view) = image.image_view_mut::<P>() else {
+        return Err(fr::ImageError::UnsupportedPixelType.to_string());
+    };
+    let channels = P::count_of_components();
+    let opaque = <P::Component as CompositeComponent>::OPAQUE;
+    try_for_each_row(&mut view, |row| {
+        for pixel in P::components_mut(row).chunks_exact_mut(channels) {
+            let (alpha, colors) = pixel.split_last_mut().unwrap();
+            let alpha_value = alpha.to_f32() / opaque;
+            for (color, &background) in colors.iter_mut().zip(background) {
+                *color = composite(*color, alpha_value, background, premultiplied);
+            }
+            *alpha = CompositeComponent::from_f32(opaque);
+        }
+        Ok(())
+    })
+}
+
+/// Composites pixels of the image with alpha channel onto
+/// the background color in place.
+///
+/// The same as `flatten_alpha()` with the destination image that
+/// has the same type of pixels as the source image.
+pub(crate) fn flatten_alpha_inplace(
+    image: &mut impl IntoImageViewMut,
+    background: &[f64],
+    premultiplied: bool,
+) -> Result<(), String> {
+    let pixel_type = image
+        .pixel_type()
+        .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string())?;
+    let channels = count_of_channels(pixel_type);
+    if !has_alpha(channels) {
+        return Err(fr::ImageError::UnsupportedPixelType.to_string());
+    }
+    let color_channels = channels - 1;
+    if background.len() != color_channels {
+        return Err(format!(
+            "Background color must have {color_channels} components \
+            for {pixel_type:?} pixels"
+        ));
+    }
+    let background: Vec<f32> = background.iter().map(|&v| v as f32).collect();
+
+    use PixelType as PT;
+
+    match pixel_type {
+        PT::U8x2 => flatten_inplace_typed::<U8x2>(image, &background, premultiplied),
+        PT::U8x4 => flatten_inplace_typed::<U8x4>(image, &background, premultiplied),
+        PT::U16x2 => flatten_inplace_typed::<U16x2>(image, &background, premultiplied),
+        PT::U16x4 => flatten_inplace_typed::<U16x4>(image, &background, premultiplied),
+        PT::F32x2 => flatten_inplace_typed::<F32x2>(image, &background, premultiplied),
+        PT::F32x4 => flatten_inplace_typed::<F32x4>(image, &background, premultiplied),
+        _ => Err(fr::ImageError::UnsupportedPixelType.to_string()),
+    }
+}
@@ -0,0 +1,273 @@
+//! Conversion of images between pixel types.
+use std::fmt::Debug;
+
+use fast_image_resize as fr;
+use fast_image_resize::pixels::{
+    F32, F32x2, F32x3, F32x4, I32, IntoPixelComponent as _, U8, U8x2, U8x3, U8x4, U16, U16x2,
+    U16x3, U16x4,
+};
+use fast_image_resize::{IntoImageView, IntoImageViewMut, PixelTrait};
+
+use crate::dithering::{Dithering, dither_pixels};
+use crate::threading::try_for_each_row_pair;
+
+/// Range of normalized values of a pixel component.
+///
+/// Values of integer components are normalized in the same way
+/// as they are converted into `F32` components.
+pub(crate) trait ComponentRange: Copy + Debug + Send + Sync {
+    const MIN: f32;
+    const MAX: f32;
+
+    fn normalized(self) -> f32;
+}
+
+impl ComponentRange for u8 {
+    const MIN: f32 = 0.;
+    const MAX: f32 = 1.;
+
+    #[inline(always)]
+    fn normalized(self) -> f32 {
+        self as f32 / u8::MAX as f32
+    }
+}
+
+impl ComponentRange for u16 {
+    const MIN: f32 = 0.;
+    const MAX: f32 = 1.;
+
+    #[inline(always)]
+    fn normalized(self) -> f32 {
+        self as f32 / u16::MAX as f32
+    }
+}
+
+impl ComponentRange for i32 {
+    const MIN: f32 = -1.;
+    const MAX: f32 = 1.;
+
+    #[inline(always)]
+    fn normalized(self) -> f32 {
+        // Division in f64 gives the same values as conversion
+        // of corresponding `U8` and `U16` components
+        (self as f64 / i32::MAX as f64) as f32
+    }
+}
+
+impl ComponentRange for f32 {
+    const MIN: f32 = f32::NEG_INFINITY;
+    const MAX: f32 = f32::INFINITY;
+
+    #[inline(always)]
+    fn normalized(self) -> f32 {
+        self
+    }
+}
+
+/// Returns an error if some of the components has value out of range
+/// of components of the destination type of pixels.
+pub(crate) fn check_range<C, D>(components: &[C]) -> Result<(), String>
+where
+    C: ComponentRange,
+    D: PixelTrait<Component: ComponentRange>,
+{
+    if C::MIN >= <D::Component as ComponentRange>::MIN
+        && C::MAX <= <D::Component as ComponentRange>::MAX
+    {
+        return Ok(());
+    }
+    let dst_range = <D::Component as ComponentRange>::MIN..=<D::Component as ComponentRange>::MAX;
+    match components
+        .iter()
+        .find(|value| !dst_range.contains(&value.normalized()))
+    {
+        Some(value) => Err(format!(
+            "Value {value:?} of pixel component is out of range of {:?} pixels",
+            D::pixel_type()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Conversion of a pixel component into a component of other type.
+///
+/// Most of conversions are delegated to `fr::pixels::IntoPixelComponent`.
+/// Conversions of `I32` components are implemented here because
+/// `fast_image_resize` changes sign of negative values in conversions
+/// between `I32` and `F32` components, and scales `U8` and `U16`
+/// components into `I32` with shifts, so the maximal value of `U8`
+/// component doesn't become `i32::MAX`. All `I32` conversions use
+/// `i32::MAX` as the maximal value of components. Also `U16` components
+/// are rounded to the nearest `U8` values instead of truncating.
+pub(crate) trait ConvertComponent<D> {
+    fn convert_component(self) -> D;
+}
+
+macro_rules! convert_with_fr {
+    ($src: ty => [$($dst: ty),+]) => {
+        $(
+            impl ConvertComponent<$dst> for $src {
+                #[inline(always)]
+                fn convert_component(self) -> $dst {
+                    self.into_component()
+                }
+            }
+        )+
+    };
+}
+
+convert_with_fr!(u8 => [u8, u16, f32]);
+convert_with_fr!(u16 => [u16, f32]);
+convert_with_fr!(i32 => [i32]);
+convert_with_fr!(f32 => [u8, u16, f32]);
+
+/// Conversions between unsigned integer components and `I32` components
+/// with rounding to the nearest value.
+macro_rules! convert_with_i32 {
+    ($($src: ty),+) => {
+        $(
+            impl ConvertComponent<i32> for $src {
+                #[inline(always)]
+                fn convert_component(self) -> i32 {
+                    const MAX: i64 = <$src>::MAX as i64;
+                    ((self as i64 * i32::MAX as i64 + MAX / 2) / MAX) as i32
+                }
+            }
+
+            impl ConvertComponent<$src> for i32 {
+                #[inline(always)]
+                fn convert_component(self) -> $src {
+                    const MAX: i64 = <$src>::MAX as i64;
+                    // Negative values are clamped to zero
+                    ((self.max(0) as i64 * MAX + i32::MAX as i64 / 2) / i32::MAX as i64) as $src
+                }
+            }
+        )+
+    };
+}
+
+convert_with_i32!(u8, u16);
+
+impl ConvertComponent<u8> for u16 {
+    #[inline(always)]
+    fn convert_component(self) -> u8 {
+        // `fast_image_resize` drops the low byte without rounding
+        ((self as u32 * u8::MAX as u32 + u16::MAX as u32 / 2) / u16::MAX as u32) as u8
+    }
+}
+
+impl ConvertComponent<f32> for i32 {
+    #[inline(always)]
+    fn convert_component(self) -> f32 {
+        self.normalized().clamp(-1., 1.)
+    }
+}
+
+impl ConvertComponent<i32> for f32 {
+    #[inline(always)]
+    fn convert_component(self) -> i32 {
+        // NaN is converted into zero
+        (self.clamp(-1., 1.) as f64 * i32::MAX as f64).round() as i32
+    }
+}
+
+fn convert_typed<S, D>(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    clamp: bool,
+) -> Result<(), String>
+where
+    S: PixelTrait<Component: ComponentRange + ConvertComponent<D::Component>>,
+    D: PixelTrait<Component: ComponentRange>,
+{
+    let (Some(src_view), Some(mut dst_view)) =
+        (src_image.image_view::<S>(), dst_image.image_view_mut::<D>())
+    else {
+        return Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string());
+    };
+    try_for_each_row_pair(&src_view, &mut dst_view, |src_row, dst_row| {
+        let src_components = S::components(src_row);
+        if !clamp {
+            check_range::<S::Component, D>(src_components)?;
+        }
+        let dst_components = D::components_mut(dst_row);
+        for (&src, dst) in src_components.iter().zip(dst_components) {
+            *dst = src.convert_component();
+        }
+        Ok(())
+    })
+}
+
+/// Returns `true` if pixels of the source type can be converted
+/// into pixels of the destination type.
+pub(crate) fn is_convertible(src_pixel_type: fr::PixelType, dst_pixel_type: fr::PixelType) -> bool {
+    use fr::PixelType as PT;
+
+    let group = |pixel_type| match pixel_type {
+        PT::U8 | PT::U16 | PT::I32 | PT::F32 => 1,
+        PT::U8x2 | PT::U16x2 | PT::F32x2 => 2,
+        PT::U8x3 | PT::U16x3 | PT::F32x3 => 3,
+        PT::U8x4 | PT::U16x4 | PT::F32x4 => 4,
+        _ => 0,
+    };
+    let src_group = group(src_pixel_type);
+    src_group > 0 && src_group == group(dst_pixel_type)
+}
+
+/// Converts pixels of the source image into pixels of the destination
+/// image with the same count of components per pixel.
+///
+/// Values of integer components are scaled into the range of
+/// the destination type. Values of `F32` components are expected in
+/// the range `[0.0, 1.0]` (`[-1.0, 1.0]` for `I32`). Values outside
+/// the range are clamped or, if `clamp` is false, produce an error.
+///
+/// The given dithering is applied if `U16` or `F32` components
+/// are converted into `U8` components.
+pub(crate) fn convert_pixels(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    clamp: bool,
+    dithering: Dithering,
+) -> Result<(), String> {
+    if src_image.width() != dst_image.width() || src_image.height() != dst_image.height() {
+        return Err(fr::MappingError::DifferentDimensions.to_string());
+    }
+    if let Some(result) = dither_pixels(src_image, dst_image, clamp, dithering) {
+        return result;
+    }
+    let (Some(src_pixel_type), Some(dst_pixel_type)) =
+        (src_image.pixel_type(), dst_image.pixel_type())
+    else {
+        return Err(fr::ImageError::UnsupportedPixelType.to_string());
+    };
+
+    use fr::PixelType as PT;
+
+    // Names of variants of `PixelType` are the same as names of pixel types
+    macro_rules! convert_into {
+        ($src: ident, [$($dst: ident),+]) => {
+            match dst_pixel_type {
+                $(PT::$dst => convert_typed::<$src, $dst>(src_image, dst_image, clamp),)+
+                _ => Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string()),
+            }
+        };
+    }
+
+    match src_pixel_type {
+        PT::U8 => convert_into!(U8, [U8, U16, I32, F32]),
+        PT::U16 => convert_into!(U16, [U8, U16, I32, F32]),
+        PT::I32 => convert_into!(I32, [U8, U16, I32, F32]),
+        PT::F32 => convert_into!(F32, [U8, U16, I32, F32]),
+        PT::U8x2 => convert_into!(U8x2, [U8x2, U16x2, F32x2]),
+        PT::U16x2 => convert_into!(U16x2, [U8x2, U16x2, F32x2]),
+        PT::F32x2 => convert_into!(F32x2, [U8x2, U16x2, F32x2]),
+        PT::U8x3 => convert_into!(U8x3, [U8x3, U16x3, F32x3]),
+        PT::U16x3 => convert_into!(U16x3, [U8x3, U16x3, F32x3]),
+        PT::F32x3 => convert_into!(F32x3, [U8x3, U16x3, F32x3]),
+        PT::U8x4 => convert_into!(U8x4, [U8x4, U16x4, F32x4]),
+        PT::U16x4 => convert_into!(U16x4, [U8x4, U16x4, F32x4]),
+        PT::F32x4 => convert_into!(F32x4, [U8x4, U16x4, F32x4]),
+        _ => Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string()),
+    }
+}
@@ -0,0 +1,109 @@
+//! Functions to pin threads to CPUs.
+//!
+//! Only Linux is supported, on other platforms functions return an error.
+
+/// Strategy of automatic distribution of worker threads between CPUs.
+///
+/// CPUs are selected by their positions in the list of CPUs,
+/// NUMA nodes and shared caches are not taken into account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AffinityStrategy {
+    /// Worker threads occupy neighbouring CPUs.
+    Compact,
+    /// Worker threads are evenly distributed among all available CPUs.
+    Spread,
+}
+
+impl AffinityStrategy {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "compact" => Ok(Self::Compact),
+            "spread" => Ok(Self::Spread),
+            _ => Err(format!(
+                "Unknown CPU affinity strategy '{name}', expected 'compact' or 'spread'"
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Compact => "compact",
+            Self::Spread => "spread",
+        }
+    }
+
+    /// Returns CPU for the worker thread with given index.
+    pub fn select_cpu(&self, cpus: &[usize], thread_index: usize, num_threads: usize) -> usize {
+        let index = match self {
+            Self::Compact => thread_index,
+            Self::Spread => {
+                if num_threads < cpus.len() {
+                    thread_index * cpus.len() / num_threads
+                } else {
+                    thread_index
+                }
+            }
+        };
+        cpus[index % cpus.len()]
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::io;
+    use std::mem;
+
+    fn cpus_from_set(cpu_set: &libc::cpu_set_t) -> Vec<usize> {
+        (0..libc::CPU_SETSIZE as usize)
+            .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, cpu_set) })
+            .collect()
+    }
+
+    /// Returns CPUs which the current thread is allowed to run on.
+    pub fn current_thread_cpus() -> io::Result<Vec<usize>> {
+        let mut cpu_set: libc::cpu_set_t = unsafe { mem::zeroed() };
+        let res =
+            unsafe { libc::sched_getaffinity(0, mem::size_of::<libc::cpu_set_t>(), &mut cpu_set) };
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(cpus_from_set(&cpu_set))
+    }
+
+    /// Pins the current thread to the given CPU.
+    pub fn pin_current_thread(cpu: usize) -> io::Result<()> {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+        let mut cpu_set: libc::cpu_set_t = unsafe { mem::zeroed() };
+        unsafe { libc::CPU_SET(cpu, &mut cpu_set) };
+        let res =
+            unsafe { libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &cpu_set) };
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use std::io;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "CPU affinity is supported only on Linux",
+        )
+    }
+
+    pub fn current_thread_cpus() -> io::Result<Vec<usize>> {
+        Err(unsupported())
+    }
+
+    pub fn pin_current_thread(_cpu: usize) -> io::Result<()> {
+        Err(unsupported())
+    }
+}
+
+pub(crate) use imp::{current_thread_cpus, pin_current_thread};
@@ -0,0 +1,369 @@
+//! Dithering of pixel components while reducing bit depth of images.
+use std::sync::OnceLock;
+
+use fast_image_resize as fr;
+use fast_image_resize::pixels::{
+    F32, F32x2, F32x3, F32x4, U8, U8x2, U8x3, U8x4, U16, U16x2, U16x3, U16x4,
+};
+use fast_image_resize::{ImageView, ImageViewMut, IntoImageView, IntoImageViewMut, PixelTrait};
+
+use crate::convert::{ComponentRange, check_range};
+use crate::threading::try_for_each_indexed_row_pair;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Dithering {
+    #[default]
+    None,
+    /// Ordered dithering with 8x8 Bayer matrix.
+    Ordered,
+    /// Ordered dithering with 64x64 blue-noise texture.
+    BlueNoise,
+    /// Error-diffusion dithering with Floyd–Steinberg kernel.
+    FloydSteinberg,
+}
+
+impl Dithering {
+    pub(crate) fn from_u8(dithering: u8) -> Option<Self> {
+        match dithering {
+            0 => Some(Self::None),
+            1 => Some(Self::Ordered),
+            2 => Some(Self::BlueNoise),
+            3 => Some(Self::FloydSteinberg),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Ordered => 1,
+            Self::BlueNoise => 2,
+            Self::FloydSteinberg => 3,
+        }
+    }
+}
+
+const BAYER_MATRIX: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+#[inline(always)]
+fn bayer_threshold(x: usize, y: usize) -> f32 {
+    (BAYER_MATRIX[y & 7][x & 7] as f32 + 0.5) / 64.
+}
+
+const BLUE_NOISE_SIZE: usize = 64;
+
+#[inline(always)]
+fn blue_noise_threshold(texture: &[f32], x: usize, y: usize) -> f32 {
+    texture[(y % BLUE_NOISE_SIZE) * BLUE_NOISE_SIZE + x % BLUE_NOISE_SIZE]
+}
+
+/// Returns thresholds of blue-noise texture generated once
+/// with the void-and-cluster method.
+fn blue_noise_texture() -> &'static [f32] {
+    static TEXTURE: OnceLock<Box<[f32]>> = OnceLock::new();
+    TEXTURE.get_or_init(|| {
+        let ranks = void_and_cluster(BLUE_NOISE_SIZE);
+        let count = ranks.len() as f32;
+        ranks
+            .into_iter()
+            .map(|rank| (rank as f32 + 0.5) / count)
+            .collect()
+    })
+}
+
+/// Energy of points of a binary pattern on a torus
+/// calculated with Gaussian filter.
+struct Energy {
+    size: usize,
+    kernel: Vec<f32>,
+    values: Vec<f32>,
+}
+
+impl Energy {
+    fn new(size: usize) -> Self {
+        const SIGMA: f32 = 1.5;
+        let mut kernel = vec![0.; size * size];
+        for dy in 0..size {
+            for dx in 0..size {
+                let x = dx.min(size - dx) as f32;
+                let y = dy.min(size - dy) as f32;
+                kernel[dy * size + dx] = (-(x * x + y * y) / (2. * SIGMA * SIGMA)).exp();
+            }
+        }
+        Self {
+            size,
+            kernel,
+            values: vec![0.; size * size],
+        }
+    }
+
+    fn update(&mut self, index: usize, sign: f32) {
+        let size = self.size;
+        let (px, py) = (index % size, index / size);
+        for (y, row) in self.values.chunks_exact_mut(size).enumerate() {
+            let dy = (y + size - py) % size;
+            let kernel_row = &self.kernel[dy * size..(dy + 1) * size];
+            // Kernel row is shifted by `px` with wrapping
+            let (left, right) = row.split_at_mut(px);
+            let (kernel_left, kernel_right) = kernel_row.split_at(size - px);
+            for (value, &k) in right.iter_mut().zip(kernel_left) {
+                *value += sign * k;
+            }
+            for (value, &k) in left.iter_mut().zip(kernel_right) {
+                *value += sign * k;
+            }
+        }
+    }
+
+    /// Returns index of the point with maximal energy (the tightest
+    /// cluster) or minimal energy (the largest void).
+    fn find(&self, pattern: &[bool], value: bool, tightest: bool) -> usize {
+        let candidates = self
+            .values
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| pattern[i] == value);
+        let found = if tightest {
+            candidates.max_by(|a, b| a.1.total_cmp(b.1))
+        } else {
+            candidates.min_by(|a, b| a.1.total_cmp(b.1))
+        };
+        found.map(|(i, _)| i).unwrap_or_default()
+    }
+}
+
+/// Returns ranks of points of a dither array with blue-noise
+/// properties (R. Ulichney, "The void-and-cluster method
+/// for dither array generation").
+fn void_and_cluster(size: usize) -> Vec<u32> {
+    let count = size * size;
+    let mut pattern = vec![false; count];
+    let mut energy = Energy::new(size);
+
+    // Initial random pattern, it is deterministic for reproducible results
+    let mut state: u32 = 0x9E37_79B9;
+    let mut ones = 0;
+    while ones < count / 10 {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let index = state as usize % count;
+        if !pattern[index] {
+            pattern[index] = true;
+            energy.update(index, 1.);
+            ones += 1;
+        }
+    }
+    // Distribute points of the initial pattern uniformly
+    loop {
+        let cluster = energy.find(&pattern, true, true);
+        pattern[cluster] = false;
+        energy.update(cluster, -1.);
+        let void = energy.find(&pattern, false, false);
+        pattern[void] = true;
+        energy.update(void, 1.);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0; count];
+    let initial_pattern = pattern.clone();
+    let initial_energy = energy.values.clone();
+    // Ranks of points of the initial pattern
+    for rank in (0..ones).rev() {
+        let cluster = energy.find(&pattern, true, true);
+        pattern[cluster] = false;
+        energy.update(cluster, -1.);
+        ranks[cluster] = rank as u32;
+    }
+    // Ranks of remaining points
+    pattern = initial_pattern;
+    energy.values = initial_energy;
+    for rank in ones..count {
+        let void = energy.find(&pattern, false, false);
+        pattern[void] = true;
+        energy.update(void, 1.);
+        ranks[void] = rank as u32;
+    }
+    ranks
+}
+
+/// Returns value of a pixel component normalized into
+/// the range of `u8` values.
+#[inline(always)]
+fn u8_value<C: ComponentRange>(component: C) -> f32 {
+    let value = component.normalized();
+    if value.is_nan() {
+        return 0.;
+    }
+    value.clamp(0., 1.) * u8::MAX as f32
+}
+
+fn dither_ordered<S, D>(
+    src_view: &impl ImageView<Pixel = S>,
+    dst_view: &mut impl ImageViewMut<Pixel = D>,
+    clamp: bool,
+    threshold: impl Fn(usize, usize) -> f32 + Sync,
+) -> Result<(), String>
+where
+    S: PixelTrait<Component: ComponentRange>,
+    D: PixelTrait<Component = u8>,
+{
+    let count_of_components = S::count_of_components();
+    try_for_each_indexed_row_pair(src_view, dst_view, |y, src_row, dst_row| {
+        let src_components = S::components(src_row);
+        if !clamp {
+            check_range::<S::Component, D>(src_components)?;
+        }
+        let dst_components = D::components_mut(dst_row);
+        let pixels = src_components
+            .chunks_exact(count_of_components)
+            .zip(dst_components.chunks_exact_mut(count_of_components));
+        for (x, (src_pixel, dst_pixel)) in pixels.enumerate() {
+            let threshold = threshold(x, y as usize);
+            for (&src, dst) in src_pixel.iter().zip(dst_pixel) {
+                *dst = (u8_value(src) + threshold) as u8;
+            }
+        }
+        Ok(())
+    })
+}
+
+fn dither_floyd_steinberg<S, D>(
+    src_view: &impl ImageView<Pixel = S>,
+    dst_view: &mut impl ImageViewMut<Pixel = D>,
+    clamp: bool,
+) -> Result<(), String>
+where
+    S: PixelTrait<Component: ComponentRange>,
+    D: PixelTrait<Component = u8>,
+{
+    let count_of_components = S::count_of_components();
+    let width = src_view.width() as usize;
+    // Errors of the current and the next rows with one extra
+    // pixel at each side.
+    let row_len = (width + 2) * count_of_components;
+    let mut errors = vec![0f32; row_len];
+    let mut next_errors = vec![0f32; row_len];
+    for (src_row, dst_row) in src_view.iter_rows(0).zip(dst_view.iter_rows_mut(0)) {
+        let src_components = S::components(&src_row[..width]);
+        if !clamp {
+            check_range::<S::Component, D>(src_components)?;
+        }
+        let dst_components = D::components_mut(&mut dst_row[..width]);
+        for (i, (&src, dst)) in src_components.iter().zip(dst_components).enumerate() {
+            let e = i + count_of_components;
+            let value = u8_value(src) + errors[e];
+            let quantized = value.round().clamp(0., u8::MAX as f32);
+            *dst = quantized as u8;
+            let error = value - quantized;
+            errors[e + count_of_components] += error * (7. / 16.);
+            next_errors[e - count_of_components] += error * (3. / 16.);
+            next_errors[e] += error * (5. / 16.);
+            next_errors[e + count_of_components] += error * (1. / 16.);
+        }
+        std::mem::swap(&mut errors, &mut next_errors);
+        next_errors.fill(0.);
+    }
+    Ok(())
+}
+
+fn dither_typed<S, D>(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    clamp: bool,
+    dithering: Dithering,
+) -> Result<(), String>
+where
+    S: PixelTrait<Component: ComponentRange>,
+    D: PixelTrait<Component = u8>,
+{
+    let (Some(src_view), Some(mut dst_view)) =
+        (src_image.image_view::<S>(), dst_image.image_view_mut::<D>())
+    else {
+        return Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string());
+    };
+    match dithering {
+        Dithering::None | Dithering::Ordered => {
+            dither_ordered(&src_view, &mut dst_view, clamp, bayer_threshold)
+        }
+        Dithering::BlueNoise => {
+            let texture = blue_noise_texture();
+            dither_ordered(&src_view, &mut dst_view, clamp, |x, y| {
+                blue_noise_threshold(texture, x, y)
+            })
+        }
+        Dithering::FloydSteinberg => dither_floyd_steinberg(&src_view, &mut dst_view, clamp),
+    }
+}
+
+/// Converts pixels of the source image with `U16` or `F32` components
+/// into pixels of the destination image with `U8` components
+/// using dithering.
+///
+/// Returns `None` if dithering is not applicable to the given
+/// types of pixels.
+pub(crate) fn dither_pixels(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    clamp: bool,
+    dithering: Dithering,
+) -> Option<Result<(), String>> {
+    use fr::PixelType as PT;
+
+    if dithering == Dithering::None {
+        return None;
+    }
+    let result = match (src_image.pixel_type()?, dst_image.pixel_type()?) {
+        (PT::U16, PT::U8) => dither_typed::<U16, U8>(src_image, dst_image, clamp, dithering),
+        (PT::F32, PT::U8) => dither_typed::<F32, U8>(src_image, dst_image, clamp, dithering),
+        (PT::U16x2, PT::U8x2) => {
+            dither_typed::<U16x2, U8x2>(src_image, dst_image, clamp, dithering)
+        }
+        (PT::F32x2, PT::U8x2) => {
+            dither_typed::<F32x2, U8x2>(src_image, dst_image, clamp, dithering)
+        }
+        (PT::U16x3, PT::U8x3) => {
+            dither_typed::<U16x3, U8x3>(src_image, dst_image, clamp, dithering)
+        }
+        (PT::F32x3, PT::U8x3) => {
+            dither_typed::<F32x3, U8x3>(src_image, dst_image, clamp, dithering)
+        }
+        (PT::U16x4, PT::U8x4) => {
+            dither_typed::<U16x4, U8x4>(src_image, dst_image, clamp, dithering)
+        }
+        (PT::F32x4, PT::U8x4) => {
+            dither_typed::<F32x4, U8x4>(src_image, dst_image, clamp, dithering)
+        }
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Returns `true` if conversion of pixels of the source type into
+/// pixels of the destination type reduces bit depth of components,
+/// so dithering can be applied.
+pub(crate) fn is_dithering_applicable(
+    src_pixel_type: fr::PixelType,
+    dst_pixel_type: fr::PixelType,
+) -> bool {
+    use fr::PixelType as PT;
+
+    matches!(
+        (src_pixel_type, dst_pixel_type),
+        (PT::U16 | PT::F32, PT::U8)
+            | (PT::U16x2 | PT::F32x2, PT::U8x2)
+            | (PT::U16x3 | PT::F32x3, PT::U8x3)
+            | (PT::U16x4 | PT::F32x4, PT::U8x4)
+    )
+}
@@ -0,0 +1,223 @@
+//! Extending of images beyond their edges.
+use fast_image_resize as fr;
+use fast_image_resize::pixels::{
+    F32, F32x2, F32x3, F32x4, I32, U8, U8x2, U8x3, U8x4, U16, U16x2, U16x3, U16x4,
+};
+use fast_image_resize::{ImageView, IntoImageView, IntoImageViewMut, PixelTrait, PixelType};
+
+use crate::channels::{fill_around_region, fill_pixels};
+use crate::threading::try_for_each_indexed_row;
+
+/// Mode of getting pixels outside the edges of an image.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum EdgeMode {
+    /// All components of pixels are zero.
+    #[default]
+    Transparent,
+    /// Pixels have a constant color.
+    Constant,
+    /// Pixels on the edges are repeated.
+    Clamp,
+    /// The image is reflected at its edges (the edge pixels are repeated).
+    Mirror,
+    /// The image is repeated as tiles.
+    Wrap,
+}
+
+impl EdgeMode {
+    pub(crate) fn from_u8(mode: u8) -> Option<Self> {
+        match mode {
+            0 => Some(Self::Transparent),
+            1 => Some(Self::Constant),
+            2 => Some(Self::Clamp),
+            3 => Some(Self::Mirror),
+            4 => Some(Self::Wrap),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_u8(self) -> u8 {
+        match self {
+            Self::Transparent => 0,
+            Self::Constant => 1,
+            Self::Clamp => 2,
+            Self::Mirror => 3,
+            Self::Wrap => 4,
+        }
+    }
+
+    /// Returns mode of getting pixels outside the edges of an image
+    /// that are used by the resize algorithm, 0 - `None`.
+    pub(crate) fn border_from_u8(mode: u8) -> Option<Self> {
+        match mode {
+            1 => Some(Self::Clamp),
+            2 => Some(Self::Mirror),
+            3 => Some(Self::Wrap),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn border_as_u8(mode: Option<Self>) -> u8 {
+        match mode {
+            Some(Self::Clamp) => 1,
+            Some(Self::Mirror) => 2,
+            Some(Self::Wrap) => 3,
+            _ => 0,
+        }
+    }
+
+    /// Returns the coordinate of a pixel of the image with given size
+    /// that is used for the coordinate that may be outside the image,
+    /// or `None` if the pixel is filled with a color.
+    fn source_coordinate(self, coordinate: i64, size: u32) -> Option<u32> {
+        let size = size as i64;
+        if (0..size).contains(&coordinate) {
+            return Some(coordinate as u32);
+        }
+        if size == 0 {
+            return None;
+        }
+        let coordinate = match self {
+            Self::Transparent | Self::Constant => return None,
+            Self::Clamp => coordinate.clamp(0, size - 1),
+            Self::Mirror => {
+                let coordinate = coordinate.rem_euclid(2 * size);
+                if coordinate < size {
+                    coordinate
+                } else {
+                    2 * size - 1 - coordinate
+                }
+            }
+            Self::Wrap => coordinate.rem_euclid(size),
+        };
+        Some(coordinate as u32)
+    }
+}
+
+fn extend_typed<P: PixelTrait>(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    columns: &[Option<u32>],
+    rows: &[Option<u32>],
+) -> Result<(), String> {
+    let (Some(src_view), Some(mut dst_view)) =
+        (src_image.image_view::<P>(), dst_image.image_view_mut::<P>())
+    else {
+        return Err(fr::ResizeError::PixelTypesAreDifferent.to_string());
+    };
+    // Consecutive columns are copied as slices:
+    // (destination start, source start, length).
+    let mut runs: Vec<(usize, usize, usize)> = Vec::new();
+    for (dst_x, src_x) in columns.iter().enumerate() {
+        let Some(src_x) = src_x.map(|x| x as usize) else {
+            continue;
+        };
+        match runs.last_mut() {
+            Some((dst_start, src_start, len))
+                if *dst_start + *len == dst_x && *src_start + *len == src_x =>
+            {
+                *len += 1;
+            }
+            _ => runs.push((dst_x, src_x, 1)),
+        }
+    }
+    let src_rows: Vec<&[P]> = src_view.iter_rows(0).collect();
+    try_for_each_indexed_row(&mut dst_view, |y, dst_row| {
+        let Some(src_row) = rows[y as usize].map(|src_y| src_rows[src_y as usize]) else {
+            return Ok(());
+        };
+        for &(dst_start, src_start, len) in &runs {
+            dst_row[dst_start..dst_start + len]
+                .copy_from_slice(&src_row[src_start..src_start + len]);
+        }
+        Ok(())
+    })
+}
+
+/// Copies pixels of the region of the source image with the top-left
+/// corner at (left, top) and the size of the destination image into
+/// the destination image with the same type of pixels.
+///
+/// The region may extend beyond the edges of the source image.
+/// Pixels outside the source image are got in accordance with
+/// the edge mode or filled with the color that must have a value
+/// for every channel of pixels.
+pub(crate) fn extend_image(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    left: i64,
+    top: i64,
+    mode: EdgeMode,
+    color: &[f64],
+) -> Result<(), String> {
+    let columns: Vec<i64> = (left..left + dst_image.width() as i64).collect();
+    let rows: Vec<i64> = (top..top + dst_image.height() as i64).collect();
+    sample_image(src_image, dst_image, &columns, &rows, mode, color)
+}
+
+/// Copies pixels of the source image with the given coordinates
+/// of columns and rows into the destination image with the same type
+/// of pixels.
+///
+/// Coordinates must be sorted, they may be outside the source image.
+/// Pixels outside the source image are got in accordance with
+/// the edge mode or filled with the color that must have a value
+/// for every channel of pixels.
+pub(crate) fn sample_image(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    columns: &[i64],
+    rows: &[i64],
+    mode: EdgeMode,
+    color: &[f64],
+) -> Result<(), String> {
+    let pixel_type = src_image
+        .pixel_type()
+        .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string())?;
+    if dst_image.pixel_type() != Some(pixel_type) {
+        return Err(fr::ResizeError::PixelTypesAreDifferent.to_string());
+    }
+    if columns.len() != dst_image.width() as usize || rows.len() != dst_image.height() as usize {
+        return Err("Count of coordinates must match the size of the image".to_string());
+    }
+    let columns: Vec<Option<u32>> = columns
+        .iter()
+        .map(|&x| mode.source_coordinate(x, src_image.width()))
+        .collect();
+    let rows: Vec<Option<u32>> = rows
+        .iter()
+        .map(|&y| mode.source_coordinate(y, src_image.height()))
+        .collect();
+    // Only pixels that are not copied from the source image are filled
+    let copied_range = |coordinates: &[Option<u32>]| {
+        let start = coordinates.iter().position(Option::is_some)?;
+        let end = coordinates.iter().rposition(Option::is_some)? + 1;
+        Some((start as u32, (end - start) as u32))
+    };
+    let outer = (0, 0, dst_image.width(), dst_image.height());
+    match (copied_range(&columns), copied_range(&rows)) {
+        (Some((left, width)), Some((top, height))) => {
+            fill_around_region(dst_image, outer, (left, top, width, height), color)?;
+        }
+        _ => return fill_pixels(dst_image, color),
+    }
+
+    use PixelType as PT;
+
+    match pixel_type {
+        PT::U8 => extend_typed::<U8>(src_image, dst_image, &columns, &rows),
+        PT::U8x2 => extend_typed::<U8x2>(src_image, dst_image, &columns, &rows),
+        PT::U8x3 => extend_typed::<U8x3>(src_image, dst_image, &columns, &rows),
+        PT::U8x4 => extend_typed::<U8x4>(src_image, dst_image, &columns, &rows),
+        PT::U16 => extend_typed::<U16>(src_image, dst_image, &columns, &rows),
+        PT::U16x2 => extend_typed::<U16x2>(src_image, dst_image, &columns, &rows),
+        PT::U16x3 => extend_typed::<U16x3>(src_image, dst_image, &columns, &rows),
+        PT::U16x4 => extend_typed::<U16x4>(src_image, dst_image, &columns, &rows),
+        PT::I32 => extend_typed::<I32>(src_image, dst_image, &columns, &rows),
+        PT::F32 => extend_typed::<F32>(src_image, dst_image, &columns, &rows),
+        PT::F32x2 => extend_typed::<F32x2>(src_image, dst_image, &columns, &rows),
+        PT::F32x3 => extend_typed::<F32x3>(src_image, dst_image, &columns, &rows),
+        PT::F32x4 => extend_typed::<F32x4>(src_image, dst_image, &columns, &rows),
+        _ => Err(fr::ImageError::UnsupportedPixelType.to_string()),
+    }
+}
@@ -0,0 +1,151 @@
+//! Conversion of color images into grayscale and detection
+//! of grayscale images.
+use fast_image_resize as fr;
+use fast_image_resize::pixels::{
+    F32, F32x2, F32x3, F32x4, U8, U8x2, U8x3, U8x4, U16, U16x2, U16x3, U16x4,
+};
+use fast_image_resize::{IntoImageView, IntoImageViewMut, PixelTrait, PixelType};
+
+use crate::channels::{count_of_channels, has_alpha, pixel_type_with_channels};
+use crate::threading::{all_rows, try_for_each_row_pair};
+
+/// Weights of red, green and blue channels to calculate luma.
+pub(crate) type LumaWeights = [f32; 3];
+
+/// Weights of ITU-R BT.601 (used by Pillow to convert images into "L" mode).
+pub(crate) const REC601_WEIGHTS: LumaWeights = [0.299, 0.587, 0.114];
+
+/// Pixel component that can be used to store luma.
+trait LumaComponent: Copy + PartialEq + Send + Sync {
+    fn luma(rgb: &[Self], weights: &LumaWeights) -> Self;
+}
+
+impl LumaComponent for u8 {
+    #[inline(always)]
+    fn luma(rgb: &[Self], weights: &LumaWeights) -> Self {
+        let value = rgb[0] as f32 * weights[0] + rgb[1] as f32 * weights[1];
+        // Float to integer casting saturates values out of range
+        (value + rgb[2] as f32 * weights[2]).round() as u8
+    }
+}
+
+impl LumaComponent for u16 {
+    #[inline(always)]
+    fn luma(rgb: &[Self], weights: &LumaWeights) -> Self {
+        let value = rgb[0] as f32 * weights[0] + rgb[1] as f32 * weights[1];
+        (value + rgb[2] as f32 * weights[2]).round() as u16
+    }
+}
+
+impl LumaComponent for f32 {
+    #[inline(always)]
+    fn luma(rgb: &[Self], weights: &LumaWeights) -> Self {
+        rgb[0] * weights[0] + rgb[1] * weights[1] + rgb[2] * weights[2]
+    }
+}
+
+/// Returns type of grayscale pixels (with alpha channel if
+/// it is present) for the given type of color pixels.
+pub(crate) fn grayscale_pixel_type(pixel_type: PixelType) -> Option<PixelType> {
+    match count_of_channels(pixel_type) {
+        3 => pixel_type_with_channels(pixel_type, 1),
+        4 => pixel_type_with_channels(pixel_type, 2),
+        _ => None,
+    }
+}
+
+fn to_grayscale_typed<S, D>(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    weights: &LumaWeights,
+) -> Result<(), String>
+where
+    S: PixelTrait<Component: LumaComponent>,
+    D: PixelTrait<Component = S::Component>,
+{
+    let (Some(src_view), Some(mut dst_view)) =
+        (src_image.image_view::<S>(), dst_image.image_view_mut::<D>())
+    else {
+        return Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string());
+    };
+    let src_channels = S::count_of_components();
+    let dst_channels = D::count_of_components();
+    try_for_each_row_pair(&src_view, &mut dst_view, |src_row, dst_row| {
+        let src_pixels = S::components(src_row).chunks_exact(src_channels);
+        let dst_pixels = D::components_mut(dst_row).chunks_exact_mut(dst_channels);
+        for (src_pixel, dst_pixel) in src_pixels.zip(dst_pixels) {
+            dst_pixel[0] = LumaComponent::luma(&src_pixel[..3], weights);
+            if has_alpha(dst_channels) {
+                dst_pixel[1] = src_pixel[3];
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Converts color pixels of the source image into grayscale pixels
+/// of the destination image with help of the given weights of channels.
+///
+/// Alpha channel of source pixels is copied into alpha channel
+/// of destination pixels.
+pub(crate) fn to_grayscale(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    weights: &LumaWeights,
+) -> Result<(), String> {
+    if src_image.width() != dst_image.width() || src_image.height() != dst_image.height() {
+        return Err(fr::MappingError::DifferentDimensions.to_string());
+    }
+    let (Some(src_pixel_type), Some(dst_pixel_type)) =
+        (src_image.pixel_type(), dst_image.pixel_type())
+    else {
+        return Err(fr::ImageError::UnsupportedPixelType.to_string());
+    };
+
+    use PixelType as PT;
+
+    match (src_pixel_type, dst_pixel_type) {
+        (PT::U8x3, PT::U8) => to_grayscale_typed::<U8x3, U8>(src_image, dst_image, weights),
+        (PT::U8x4, PT::U8x2) => to_grayscale_typed::<U8x4, U8x2>(src_image, dst_image, weights),
+        (PT::U16x3, PT::U16) => to_grayscale_typed::<U16x3, U16>(src_image, dst_image, weights),
+        (PT::U16x4, PT::U16x2) => to_grayscale_typed::<U16x4, U16x2>(src_image, dst_image, weights),
+        (PT::F32x3, PT::F32) => to_grayscale_typed::<F32x3, F32>(src_image, dst_image, weights),
+        (PT::F32x4, PT::F32x2) => to_grayscale_typed::<F32x4, F32x2>(src_image, dst_image, weights),
+        _ => Err(fr::MappingError::UnsupportedCombinationOfImageTypes.to_string()),
+    }
+}
+
+fn is_grayscale_typed<P>(image: &impl IntoImageView) -> bool
+where
+    P: PixelTrait<Component: LumaComponent>,
+{
+    let Some(view) = image.image_view::<P>() else {
+        return false;
+    };
+    let channels = P::count_of_components();
+    all_rows(&view, |row| {
+        // Rows are checked without short-circuiting to allow
+        // the compiler to vectorize comparisons.
+        P::components(row)
+            .chunks_exact(channels)
+            .fold(true, |is_gray, pixel| {
+                is_gray & (pixel[0] == pixel[1]) & (pixel[1] == pixel[2])
+            })
+    })
+}
+
+/// Returns `true` if all pixels of the color image have equal values
+/// of red, green and blue channels.
+pub(crate) fn is_grayscale(image: &impl IntoImageView) -> bool {
+    use PixelType as PT;
+
+    match image.pixel_type() {
+        Some(PT::U8x3) => is_grayscale_typed::<U8x3>(image),
+        Some(PT::U8x4) => is_grayscale_typed::<U8x4>(image),
+        Some(PT::U16x3) => is_grayscale_typed::<U16x3>(image),
+        Some(PT::U16x4) => is_grayscale_typed::<U16x4>(image),
+        Some(PT::F32x3) => is_grayscale_typed::<F32x3>(image),
+        Some(PT::F32x4) => is_grayscale_typed::<F32x4>(image),
+        _ => false,
+    }
+}
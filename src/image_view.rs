@@ -1,8 +1,19 @@
 use fast_image_resize::images::Image as FirImage;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::PyBytes;
 
-use crate::utils::{pixel_type_from_u8, result2pyresult};
+use crate::alpha_bleeding::bleed_alpha;
+use crate::channels::{
+    ChannelSource, copy_channel, count_of_channels, has_alpha, pixel_type_with_channels,
+    split_channels, swizzle_pixels,
+};
+use crate::convert::{convert_pixels, is_convertible};
+use crate::dithering::Dithering;
+use crate::grayscale::{LumaWeights, grayscale_pixel_type, is_grayscale, to_grayscale};
+use crate::opacity::is_opaque;
+use crate::thread_pool::{ResizerThreadPool, thread_pool_for_image};
+use crate::utils::{pixel_type_from_u8, pixel_type_to_u8, result2pyresult};
 
 #[pyclass]
 pub struct Image(FirImage<'static>);
@@ -43,6 +54,312 @@ impl Image {
         self.0.height()
     }
 
+    fn pixel_type(&self) -> u8 {
+        pixel_type_to_u8(self.0.pixel_type())
+    }
+
+    /// Returns a copy of the image with pixels of the given type.
+    #[pyo3(signature = (pixel_type, clamp=true, dithering=0, thread_pool=None, threading_threshold=None))]
+    fn convert(
+        &self,
+        py: Python,
+        pixel_type: u8,
+        clamp: bool,
+        dithering: u8,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<Self> {
+        let mut dst_image = Self(FirImage::new(
+            self.0.width(),
+            self.0.height(),
+            pixel_type_from_u8(pixel_type),
+        ));
+        self.convert_into(
+            py,
+            &mut dst_image,
+            clamp,
+            dithering,
+            thread_pool,
+            threading_threshold,
+        )?;
+        Ok(dst_image)
+    }
+
+    /// Converts pixels of the image into pixels of the destination image.
+    #[pyo3(signature = (dst_image, clamp=true, dithering=0, thread_pool=None, threading_threshold=None))]
+    fn convert_into(
+        &self,
+        py: Python,
+        dst_image: &mut Image,
+        clamp: bool,
+        dithering: u8,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<()> {
+        let dithering = Dithering::from_u8(dithering)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown dithering {dithering}")))?;
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, &self.0);
+        let src_image = &self.0;
+        let dst_image = &mut dst_image.0;
+        py.detach(move || {
+            if let Some(thread_pool) = thread_pool {
+                return thread_pool.run_within(|| {
+                    result2pyresult(convert_pixels(src_image, dst_image, clamp, dithering))
+                });
+            }
+            result2pyresult(convert_pixels(src_image, dst_image, clamp, dithering))
+        })
+    }
+
+    /// Returns a copy of the image with channels in the given order.
+    #[pyo3(signature = (order, thread_pool=None, threading_threshold=None))]
+    fn swizzle(
+        &self,
+        py: Python,
+        order: Vec<usize>,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<Self> {
+        let order: Vec<ChannelSource> = order.into_iter().map(Some).collect();
+        self.swizzle_channels(py, &order, None, thread_pool, threading_threshold)
+    }
+
+    /// Returns a copy of the image with added alpha channel.
+    #[pyo3(signature = (value=None, thread_pool=None, threading_threshold=None))]
+    fn add_alpha(
+        &self,
+        py: Python,
+        value: Option<f64>,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<Self> {
+        let channels = count_of_channels(self.0.pixel_type());
+        if has_alpha(channels) {
+            return Err(PyValueError::new_err("Image already has alpha channel"));
+        }
+        let mut order: Vec<ChannelSource> = (0..channels).map(Some).collect();
+        order.push(None);
+        self.swizzle_channels(py, &order, value, thread_pool, threading_threshold)
+    }
+
+    /// Returns a copy of the image without alpha channel.
+    #[pyo3(signature = (thread_pool=None, threading_threshold=None))]
+    fn drop_alpha(
+        &self,
+        py: Python,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<Self> {
+        let channels = count_of_channels(self.0.pixel_type());
+        if !has_alpha(channels) {
+            return Err(PyValueError::new_err("Image has not alpha channel"));
+        }
+        let order: Vec<ChannelSource> = (0..channels - 1).map(Some).collect();
+        self.swizzle_channels(py, &order, None, thread_pool, threading_threshold)
+    }
+
+    /// Returns a grayscale copy of the image with luma calculated
+    /// with help of the given weights of red, green and blue channels.
+    ///
+    /// Alpha channel is preserved.
+    #[pyo3(signature = (weights, thread_pool=None, threading_threshold=None))]
+    fn to_grayscale(
+        &self,
+        py: Python,
+        weights: (f32, f32, f32),
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<Self> {
+        let weights: LumaWeights = weights.into();
+        if weights.iter().any(|w| !w.is_finite()) {
+            return Err(PyValueError::new_err(format!(
+                "Invalid weights of channels: {weights:?}"
+            )));
+        }
+        let pixel_type = self.0.pixel_type();
+        let gray_pixel_type = grayscale_pixel_type(pixel_type).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Image with {pixel_type:?} pixels can't be converted into grayscale"
+            ))
+        })?;
+        let mut dst_image = FirImage::new(self.0.width(), self.0.height(), gray_pixel_type);
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, &self.0);
+        let src_image = &self.0;
+        py.detach(|| {
+            if let Some(thread_pool) = thread_pool {
+                return thread_pool.run_within(|| {
+                    result2pyresult(to_grayscale(src_image, &mut dst_image, &weights))
+                });
+            }
+            result2pyresult(to_grayscale(src_image, &mut dst_image, &weights))
+        })?;
+        Ok(Self(dst_image))
+    }
+
+    /// Returns `true` if all pixels of the image have equal values
+    /// of red, green and blue channels.
+    #[pyo3(signature = (thread_pool=None, threading_threshold=None))]
+    fn is_grayscale(
+        &self,
+        py: Python,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<bool> {
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, &self.0);
+        let image = &self.0;
+        py.detach(|| match thread_pool {
+            Some(thread_pool) => thread_pool.run_within(|| Ok(is_grayscale(image))),
+            None => Ok(is_grayscale(image)),
+        })
+    }
+
+    /// Returns `true` if all pixels of the image are opaque
+    /// (or pixels don't have alpha channel).
+    #[pyo3(signature = (thread_pool=None, threading_threshold=None))]
+    fn is_opaque(
+        &self,
+        py: Python,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<bool> {
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, &self.0);
+        let image = &self.0;
+        py.detach(|| match thread_pool {
+            Some(thread_pool) => thread_pool.run_within(|| Ok(is_opaque(image))),
+            None => Ok(is_opaque(image)),
+        })
+    }
+
+    /// Returns a copy of the image with color channels of fully
+    /// transparent pixels filled from the nearest visible pixels.
+    ///
+    /// Every iteration extends the filled area by one pixel.
+    /// All transparent pixels reachable from visible ones are filled
+    /// if `iterations` is `None`.
+    #[pyo3(signature = (iterations=None, thread_pool=None, threading_threshold=None))]
+    fn bleed_alpha(
+        &self,
+        py: Python,
+        iterations: Option<u32>,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<Self> {
+        let pixel_type = self.0.pixel_type();
+        if !has_alpha(count_of_channels(pixel_type)) {
+            return Err(PyValueError::new_err("Image has not alpha channel"));
+        }
+        let mut dst_image = self.0.copy();
+        let iterations = iterations.unwrap_or(u32::MAX);
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, &self.0);
+        py.detach(|| {
+            if let Some(thread_pool) = thread_pool {
+                return thread_pool
+                    .run_within(|| result2pyresult(bleed_alpha(&mut dst_image, iterations)));
+            }
+            result2pyresult(bleed_alpha(&mut dst_image, iterations))
+        })?;
+        Ok(Self(dst_image))
+    }
+
+    /// Returns images with one channel for each channel of the image.
+    #[pyo3(signature = (thread_pool=None, threading_threshold=None))]
+    fn split(
+        &self,
+        py: Python,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<Vec<Self>> {
+        let pixel_type = self.0.pixel_type();
+        let channel_pixel_type = pixel_type_with_channels(pixel_type, 1).ok_or_else(|| {
+            PyValueError::new_err(format!("Image with {pixel_type:?} pixels can't be split"))
+        })?;
+        let mut channel_images: Vec<FirImage<'static>> = (0..count_of_channels(pixel_type))
+            .map(|_| FirImage::new(self.0.width(), self.0.height(), channel_pixel_type))
+            .collect();
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, &self.0);
+        let src_image = &self.0;
+        let mut split = || result2pyresult(split_channels(src_image, &mut channel_images));
+        py.detach(|| match thread_pool {
+            Some(thread_pool) => thread_pool.run_within(split),
+            None => split(),
+        })?;
+        Ok(channel_images.into_iter().map(Self).collect())
+    }
+
+    /// Returns an image with channels from the given images
+    /// with one channel.
+    #[staticmethod]
+    #[pyo3(signature = (channels, pixel_type=None, thread_pool=None, threading_threshold=None))]
+    fn merge(
+        py: Python,
+        channels: Vec<PyRef<Image>>,
+        pixel_type: Option<u8>,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<Self> {
+        let Some(first_image) = channels.first().map(|image| &image.0) else {
+            return Err(PyValueError::new_err("At least one channel is required"));
+        };
+        let channel_pixel_type = first_image.pixel_type();
+        for image in channels.iter().map(|image| &image.0) {
+            if count_of_channels(image.pixel_type()) != 1 {
+                return Err(PyValueError::new_err(
+                    "Images of channels must have one channel",
+                ));
+            }
+            if image.pixel_type() != channel_pixel_type {
+                return Err(PyValueError::new_err(
+                    "Images of channels must have the same type of pixels",
+                ));
+            }
+            if image.width() != first_image.width() || image.height() != first_image.height() {
+                return Err(PyValueError::new_err(
+                    "Images of channels must have the same dimensions",
+                ));
+            }
+        }
+        let merged_pixel_type = pixel_type_with_channels(channel_pixel_type, channels.len())
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Image with {channel_pixel_type:?} pixels can't have {} channels",
+                    channels.len()
+                ))
+            })?;
+        let dst_pixel_type = pixel_type.map_or(merged_pixel_type, pixel_type_from_u8);
+        if !is_convertible(merged_pixel_type, dst_pixel_type) {
+            return Err(PyValueError::new_err(format!(
+                "Image with {dst_pixel_type:?} pixels can't be merged from {} channels",
+                channels.len()
+            )));
+        }
+
+        let (width, height) = (first_image.width(), first_image.height());
+        let mut merged_image = FirImage::new(width, height, merged_pixel_type);
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, first_image);
+        let channel_images: Vec<&FirImage<'static>> =
+            channels.iter().map(|image| &image.0).collect();
+        let mut merge = || {
+            let mut result =
+                channel_images
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(channel, src_image)| {
+                        copy_channel(*src_image, 0, &mut merged_image, channel)
+                    });
+            if result.is_ok() && merged_pixel_type != dst_pixel_type {
+                let mut dst_image = FirImage::new(width, height, dst_pixel_type);
+                result = convert_pixels(&merged_image, &mut dst_image, true, Dithering::None);
+                merged_image = dst_image;
+            }
+            result2pyresult(result)
+        };
+        py.detach(|| match thread_pool {
+            Some(thread_pool) => thread_pool.run_within(merge),
+            None => merge(),
+        })?;
+        Ok(Self(merged_image))
+    }
+
     fn buffer<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
         let image_buffer = self.0.buffer();
         PyBytes::new_with(py, image_buffer.len(), |dst_buffer| {
@@ -53,6 +370,36 @@ impl Image {
 }
 
 impl Image {
+    fn swizzle_channels(
+        &self,
+        py: Python,
+        order: &[ChannelSource],
+        fill_value: Option<f64>,
+        thread_pool: Option<ResizerThreadPool>,
+        threading_threshold: Option<u64>,
+    ) -> PyResult<Self> {
+        let src_pixel_type = self.0.pixel_type();
+        let dst_pixel_type =
+            pixel_type_with_channels(src_pixel_type, order.len()).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "{src_pixel_type:?} pixels can't have {} channels",
+                    order.len()
+                ))
+            })?;
+        let mut dst_image = FirImage::new(self.0.width(), self.0.height(), dst_pixel_type);
+        let thread_pool = thread_pool_for_image(thread_pool, threading_threshold, &self.0);
+        let src_image = &self.0;
+        py.detach(|| {
+            if let Some(thread_pool) = thread_pool {
+                return thread_pool.run_within(|| {
+                    result2pyresult(swizzle_pixels(src_image, &mut dst_image, order, fill_value))
+                });
+            }
+            result2pyresult(swizzle_pixels(src_image, &mut dst_image, order, fill_value))
+        })?;
+        Ok(Self(dst_image))
+    }
+
     pub(crate) fn src_image_view(&self) -> &FirImage<'static> {
         &self.0
     }
@@ -61,3 +408,9 @@ impl Image {
         &mut self.0
     }
 }
+
+impl From<FirImage<'static>> for Image {
+    fn from(image: FirImage<'static>) -> Self {
+        Self(image)
+    }
+}
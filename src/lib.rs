@@ -1,19 +1,38 @@
 use pyo3::prelude::*;
 
 use crate::alpha_mul_div::RustAlphaMulDiv;
+use crate::color_mapper::RustColorMapper;
 use crate::image_view::Image;
 use crate::pil_image_wrapper::PilImageWrapper;
-use crate::resizer::{RustResizeOptions, RustResizer};
-use crate::thread_pool::ResizerThreadPool;
+use crate::plan::RustResizePlan;
+use crate::resizer::{ResizeReport, RustResizeOptions, RustResizer};
+use crate::streaming::RustStreamingResizer;
+use crate::thread_pool::{ResizerThreadPool, register_at_fork_handler};
+use crate::tiles::RustTiles;
 
 #[macro_use]
 mod utils;
 
+mod alpha_bleeding;
 mod alpha_mul_div;
+mod channels;
+mod color_mapper;
+mod compositing;
+mod convert;
+mod cpu_affinity;
+mod dithering;
+mod edges;
+mod grayscale;
 mod image_view;
+mod linear_light;
+mod opacity;
 mod pil_image_wrapper;
+mod plan;
 mod resizer;
+mod streaming;
 mod thread_pool;
+mod threading;
+mod tiles;
 
 /// This module is a python module implemented in Rust.
 #[pymodule]
@@ -24,13 +43,19 @@ fn rust_lib(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
         .use_current_thread()
         .build_global()
         .unwrap();
+    register_at_fork_handler();
 
     m.add_class::<ResizerThreadPool>()?;
     m.add_class::<Image>()?;
     m.add_class::<PilImageWrapper>()?;
     m.add_class::<RustResizeOptions>()?;
     m.add_class::<RustResizer>()?;
+    m.add_class::<ResizeReport>()?;
+    m.add_class::<RustStreamingResizer>()?;
+    m.add_class::<RustResizePlan>()?;
+    m.add_class::<RustTiles>()?;
     m.add_class::<RustAlphaMulDiv>()?;
+    m.add_class::<RustColorMapper>()?;
 
     Ok(())
 }
@@ -0,0 +1,88 @@
+//! Resizing of images in linear colorspace.
+use fast_image_resize as fr;
+use fast_image_resize::pixels::PixelType;
+use fast_image_resize::{IntoImageView, IntoImageViewMut};
+
+use crate::color_mapper::TransferFunction;
+
+/// Returns the type of pixels of an intermediate image that
+/// stores pixels in linear colorspace.
+///
+/// Components of the intermediate image must have higher precision
+/// than components of the source image to keep dark tones.
+fn linear_pixel_type(pixel_type: PixelType) -> Option<PixelType> {
+    match pixel_type {
+        PixelType::U8 => Some(PixelType::U16),
+        PixelType::U8x2 => Some(PixelType::U16x2),
+        PixelType::U8x3 => Some(PixelType::U16x3),
+        PixelType::U8x4 => Some(PixelType::U16x4),
+        PixelType::U16 => Some(PixelType::F32),
+        PixelType::U16x2 => Some(PixelType::F32x2),
+        PixelType::U16x3 => Some(PixelType::F32x3),
+        PixelType::U16x4 => Some(PixelType::F32x4),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the intermediate image in linear colorspace
+/// has `F32xN` pixels that are not supported by `fr::PixelComponentMapper`.
+fn is_float_linear_pixel_type(pixel_type: PixelType) -> bool {
+    matches!(
+        pixel_type,
+        PixelType::F32 | PixelType::F32x2 | PixelType::F32x3 | PixelType::F32x4
+    )
+}
+
+/// Returns `true` if images with the given type of pixels
+/// can be resized in linear colorspace.
+pub(crate) fn is_supported_in_linear_light(pixel_type: PixelType) -> bool {
+    linear_pixel_type(pixel_type).is_some()
+}
+
+/// Converts the source image into linear colorspace, resizes it and
+/// converts the result back into the destination image.
+///
+/// Intermediate images have `U16xN` pixels for images with `U8xN` pixels
+/// and `F32xN` pixels for images with `U16xN` pixels. Alpha channel
+/// is not mapped, so multiplying by alpha is performed with
+/// linear values of color components.
+pub(crate) fn resize_in_linear_light(
+    resizer: &mut fr::Resizer,
+    transfer_function: TransferFunction,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    options: Option<&fr::ResizeOptions>,
+) -> Result<(), String> {
+    let pixel_type = src_image
+        .pixel_type()
+        .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string())?;
+    if dst_image.pixel_type() != Some(pixel_type) {
+        return Err(fr::ResizeError::PixelTypesAreDifferent.to_string());
+    }
+    let linear_pixel_type = linear_pixel_type(pixel_type).ok_or_else(|| {
+        format!("Resizing in linear light is not supported for images with {pixel_type:?} pixels")
+    })?;
+    let is_float = is_float_linear_pixel_type(linear_pixel_type);
+    let mapper = transfer_function.mapper();
+
+    let mut linear_src_image =
+        fr::images::Image::new(src_image.width(), src_image.height(), linear_pixel_type);
+    if is_float {
+        transfer_function.forward_map(src_image, &mut linear_src_image)?;
+    } else {
+        mapper
+            .forward_map(src_image, &mut linear_src_image)
+            .map_err(|err| err.to_string())?;
+    }
+    let mut linear_dst_image =
+        fr::images::Image::new(dst_image.width(), dst_image.height(), linear_pixel_type);
+    resizer
+        .resize(&linear_src_image, &mut linear_dst_image, options)
+        .map_err(|err| err.to_string())?;
+    if is_float {
+        return transfer_function.backward_map(&linear_dst_image, dst_image);
+    }
+    mapper
+        .backward_map(&linear_dst_image, dst_image)
+        .map_err(|err| err.to_string())
+}
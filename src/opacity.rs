@@ -0,0 +1,93 @@
+//! Detection of images with constant alpha channel.
+use fast_image_resize::pixels::{F32x2, F32x4, U8x2, U8x4, U16x2, U16x4};
+use fast_image_resize::{ImageView, IntoImageView, PixelTrait, PixelType};
+
+use crate::threading::all_rows;
+
+/// State of alpha channel of all pixels of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AlphaState {
+    /// All pixels are opaque.
+    Opaque,
+    /// All pixels are fully transparent.
+    Transparent,
+    /// All pixels have the same semi-transparent alpha.
+    Constant,
+    /// Pixels have different values of alpha channel.
+    Varying,
+}
+
+/// Pixel component that can be used to store alpha.
+trait AlphaComponent: Copy + PartialEq + Send + Sync {
+    const OPAQUE: Self;
+    const TRANSPARENT: Self;
+}
+
+impl AlphaComponent for u8 {
+    const OPAQUE: Self = u8::MAX;
+    const TRANSPARENT: Self = 0;
+}
+
+impl AlphaComponent for u16 {
+    const OPAQUE: Self = u16::MAX;
+    const TRANSPARENT: Self = 0;
+}
+
+impl AlphaComponent for f32 {
+    const OPAQUE: Self = 1.;
+    const TRANSPARENT: Self = 0.;
+}
+
+fn scan_alpha_typed<P>(image: &impl IntoImageView) -> AlphaState
+where
+    P: PixelTrait<Component: AlphaComponent>,
+{
+    let Some(view) = image.image_view::<P>() else {
+        return AlphaState::Varying;
+    };
+    let channels = P::count_of_components();
+    let first_pixel = view.iter_rows(0).next().and_then(|row| row.first());
+    let Some(&first_pixel) = first_pixel else {
+        // An empty image
+        return AlphaState::Opaque;
+    };
+    let alpha = P::components(&[first_pixel])[channels - 1];
+    let is_constant = all_rows(&view, |row| {
+        // Rows are checked without short-circuiting to allow
+        // the compiler to vectorize comparisons.
+        P::components(row)
+            .chunks_exact(channels)
+            .fold(true, |is_constant, pixel| {
+                is_constant & (pixel[channels - 1] == alpha)
+            })
+    });
+    match alpha {
+        _ if !is_constant => AlphaState::Varying,
+        a if a == AlphaComponent::OPAQUE => AlphaState::Opaque,
+        a if a == AlphaComponent::TRANSPARENT => AlphaState::Transparent,
+        _ => AlphaState::Constant,
+    }
+}
+
+/// Returns the state of alpha channel of the image or `None`
+/// if pixels of the image don't have alpha channel.
+pub(crate) fn scan_alpha(image: &impl IntoImageView) -> Option<AlphaState> {
+    use PixelType as PT;
+
+    let state = match image.pixel_type()? {
+        PT::U8x2 => scan_alpha_typed::<U8x2>(image),
+        PT::U8x4 => scan_alpha_typed::<U8x4>(image),
+        PT::U16x2 => scan_alpha_typed::<U16x2>(image),
+        PT::U16x4 => scan_alpha_typed::<U16x4>(image),
+        PT::F32x2 => scan_alpha_typed::<F32x2>(image),
+        PT::F32x4 => scan_alpha_typed::<F32x4>(image),
+        _ => return None,
+    };
+    Some(state)
+}
+
+/// Returns `true` if all pixels of the image are opaque
+/// (or pixels don't have alpha channel).
+pub(crate) fn is_opaque(image: &impl IntoImageView) -> bool {
+    scan_alpha(image).is_none_or(|state| state == AlphaState::Opaque)
+}
@@ -0,0 +1,107 @@
+//! Resizing of many images with the same sizes and options.
+use fast_image_resize as fr;
+use fast_image_resize::images::Image as FirImage;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::image_view::Image;
+use crate::resizer::{ResizeReport, RustResizeOptions, resize_image};
+use crate::thread_pool::ResizerThreadPool;
+use crate::utils::checked_pixel_type_from_u8;
+
+/// Plan of resizing of images with fixed sizes and types of pixels
+/// with the same options.
+///
+/// The thread pool is selected once while creating the plan. The plan
+/// has its own resizer. Blank images with sizes and types of pixels
+/// of the plan are resized while creating it, so options are checked
+/// and internal buffers of the resizer are allocated only once.
+#[pyclass]
+pub struct RustResizePlan {
+    resizer: fr::Resizer,
+    options: RustResizeOptions,
+    thread_pool: Option<ResizerThreadPool>,
+    src_size: (u32, u32),
+    src_pixel_type: fr::PixelType,
+    dst_size: (u32, u32),
+    dst_pixel_type: fr::PixelType,
+}
+
+impl RustResizePlan {
+    fn resize(&mut self, src_image: &FirImage, dst_image: &mut FirImage) -> PyResult<ResizeReport> {
+        let resizer = &mut self.resizer;
+        let options = Some(&self.options);
+        if let Some(thread_pool) = self.thread_pool.as_ref() {
+            return thread_pool.run_within(|| resize_image(resizer, src_image, dst_image, options));
+        }
+        resize_image(resizer, src_image, dst_image, options)
+    }
+}
+
+#[pymethods]
+impl RustResizePlan {
+    #[new]
+    #[pyo3(signature = (src_size, src_pixel_type, dst_size, dst_pixel_type, options=None))]
+    fn new(
+        py: Python,
+        src_size: (u32, u32),
+        src_pixel_type: u8,
+        dst_size: (u32, u32),
+        dst_pixel_type: u8,
+        options: Option<&RustResizeOptions>,
+    ) -> PyResult<Self> {
+        let src_pixel_type = checked_pixel_type_from_u8(src_pixel_type)?;
+        let dst_pixel_type = checked_pixel_type_from_u8(dst_pixel_type)?;
+        let options = options.cloned().unwrap_or_else(RustResizeOptions::new);
+        let crop_box = options.src_crop_box(src_size, dst_size);
+        let values = [crop_box.left, crop_box.top, crop_box.width, crop_box.height];
+        if values.iter().any(|v| !v.is_finite()) || crop_box.width <= 0. || crop_box.height <= 0. {
+            return Err(PyValueError::new_err(
+                "Crop box of the source image must have finite values and positive sizes",
+            ));
+        }
+        let thread_pool = options.thread_pool_for(src_size, dst_size).cloned();
+        let mut plan = Self {
+            resizer: fr::Resizer::new(),
+            options,
+            thread_pool,
+            src_size,
+            src_pixel_type,
+            dst_size,
+            dst_pixel_type,
+        };
+        // Errors of options and the region of the destination image
+        // don't depend on pixels, so they are reported here.
+        let src_image = FirImage::new(src_size.0, src_size.1, src_pixel_type);
+        let mut dst_image = FirImage::new(dst_size.0, dst_size.1, dst_pixel_type);
+        py.detach(|| plan.resize(&src_image, &mut dst_image))?;
+        Ok(plan)
+    }
+
+    /// Resize the source image into the destination image. Images
+    /// must have sizes and types of pixels for which the plan was created.
+    fn run(
+        &mut self,
+        py: Python,
+        src_image: &Image,
+        dst_image: &mut Image,
+    ) -> PyResult<ResizeReport> {
+        let src_image = src_image.src_image_view();
+        let dst_image = dst_image.dst_image_view();
+        if (src_image.width(), src_image.height()) != self.src_size
+            || src_image.pixel_type() != self.src_pixel_type
+        {
+            return Err(PyValueError::new_err(
+                "Size or type of pixels of the source image doesn't match to the plan",
+            ));
+        }
+        if (dst_image.width(), dst_image.height()) != self.dst_size
+            || dst_image.pixel_type() != self.dst_pixel_type
+        {
+            return Err(PyValueError::new_err(
+                "Size or type of pixels of the destination image doesn't match to the plan",
+            ));
+        }
+        py.detach(|| self.resize(src_image, dst_image))
+    }
+}
@@ -2,12 +2,29 @@ use std::ops::DerefMut;
 use std::sync::{Arc, Mutex};
 
 use fast_image_resize as fr;
+use fast_image_resize::{IntoImageView, IntoImageViewMut};
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::PyInt;
+use pyo3::types::{PyInt, PyList, PyTuple};
+use rayon::prelude::*;
 
+use crate::alpha_bleeding::bleed_alpha;
+use crate::channels::{
+    ChannelSource, check_order, copy_channel, copy_pixels, count_of_channels, fill_around_region,
+    has_alpha, is_alpha_preserved, pixel_type_with_channels, swizzle_pixels,
+};
+use crate::color_mapper::TransferFunction;
+use crate::compositing::{flatten_alpha, flatten_alpha_inplace, opaque_value};
+use crate::convert::{convert_pixels, is_convertible};
+use crate::dithering::{Dithering, is_dithering_applicable};
+use crate::edges::{EdgeMode, extend_image, sample_image};
+use crate::grayscale::{REC601_WEIGHTS, grayscale_pixel_type, is_grayscale, to_grayscale};
 use crate::image_view::Image;
+use crate::linear_light::{is_supported_in_linear_light, resize_in_linear_light};
+use crate::opacity::{AlphaState, scan_alpha};
 use crate::pil_image_wrapper::PilImageWrapper;
-use crate::thread_pool::ResizerThreadPool;
+use crate::thread_pool::{DEFAULT_THREADING_THRESHOLD, ResizerThreadPool};
+use crate::tiles::{RustTiles, TileLayout};
 use crate::utils::{cpu_extensions_from_u8, cpu_extensions_to_u8, result2pyresult};
 
 fn filter_type_from_u8(filter: u8) -> fr::FilterType {
@@ -34,27 +51,457 @@ fn filter_type_as_u8(filter_type: fr::FilterType) -> u8 {
     }
 }
 
-#[pyclass]
+fn resize_alg_from_u8(algorithm: u8, filter_type: u8, multiplicity: u8) -> fr::ResizeAlg {
+    match algorithm {
+        1 => fr::ResizeAlg::Nearest,
+        2 => fr::ResizeAlg::Convolution(filter_type_from_u8(filter_type)),
+        3 => fr::ResizeAlg::Interpolation(filter_type_from_u8(filter_type)),
+        4 => fr::ResizeAlg::SuperSampling(filter_type_from_u8(filter_type), multiplicity),
+        _ => fr::ResizeAlg::Nearest,
+    }
+}
+
+/// Returns the resize algorithm or an error if the algorithm
+/// or its filter is unknown.
+fn checked_resize_alg_from_u8(
+    algorithm: u8,
+    filter_type: u8,
+    multiplicity: u8,
+) -> PyResult<fr::ResizeAlg> {
+    let uses_filter = matches!(algorithm, 2..=4);
+    if !matches!(algorithm, 1..=4) || (uses_filter && !matches!(filter_type, 1..=6)) {
+        return Err(PyValueError::new_err(format!(
+            "Unknown resize algorithm: {algorithm} with filter {filter_type}"
+        )));
+    }
+    Ok(resize_alg_from_u8(algorithm, filter_type, multiplicity))
+}
+
+/// Returns mode of getting pixels outside the edges of an image
+/// that are used by the resize algorithm, 0 - `None`.
+fn border_mode_from_u8(mode: u8) -> PyResult<Option<EdgeMode>> {
+    if mode == 0 {
+        return Ok(None);
+    }
+    EdgeMode::border_from_u8(mode)
+        .map(Some)
+        .ok_or_else(|| PyValueError::new_err(format!("Unknown border mode: {mode}")))
+}
+
+/// Returns transfer function of colorspace that is used to resize
+/// images in linear colorspace, 0 - disabled.
+fn linear_light_from_u8(transfer_function: u8) -> PyResult<Option<TransferFunction>> {
+    if transfer_function == 0 {
+        return Ok(None);
+    }
+    TransferFunction::from_u8(transfer_function)
+        .map(Some)
+        .ok_or_else(|| {
+            PyValueError::new_err(format!("Unknown transfer function: {transfer_function}"))
+        })
+}
+
+/// Returns half of the kernel size (in pixels) of the filter.
+fn filter_support(filter_type: fr::FilterType) -> f64 {
+    match filter_type {
+        fr::FilterType::Box => 0.5,
+        fr::FilterType::Bilinear => 1.0,
+        fr::FilterType::CatmullRom | fr::FilterType::Mitchell => 2.0,
+        fr::FilterType::Gaussian | fr::FilterType::Lanczos3 => 3.0,
+        fr::FilterType::Custom(filter) => filter.support(),
+        _ => 3.0,
+    }
+}
+
+/// Estimates the amount of work required to resize an image.
+///
+/// One unit of work is approximately one access to a source pixel
+/// while calculating a destination pixel.
+pub(crate) fn estimate_resize_work(
+    src_size: (u32, u32),
+    dst_size: (u32, u32),
+    options: &fr::ResizeOptions,
+) -> u64 {
+    let (src_width, src_height) = match options.cropping {
+        fr::SrcCropping::Crop(crop_box) => (crop_box.width, crop_box.height),
+        _ => (src_size.0 as f64, src_size.1 as f64),
+    };
+    let (dst_width, dst_height) = (dst_size.0 as f64, dst_size.1 as f64);
+    if dst_width == 0. || dst_height == 0. {
+        return 0;
+    }
+    let convolution_work =
+        |filter_type: fr::FilterType, adaptive: bool, src_width: f64, src_height: f64| {
+            let support = filter_support(filter_type);
+            let (scale_x, scale_y) = if adaptive {
+                (
+                    (src_width / dst_width).max(1.),
+                    (src_height / dst_height).max(1.),
+                )
+            } else {
+                (1., 1.)
+            };
+            let kernel_width = (2. * support * scale_x).ceil();
+            let kernel_height = (2. * support * scale_y).ceil();
+            // The horizontal pass processes all rows of the source image,
+            // the vertical pass - all rows of the destination image.
+            src_height * dst_width * kernel_width + dst_height * dst_width * kernel_height
+        };
+    let work = match options.algorithm {
+        fr::ResizeAlg::Convolution(filter_type) => {
+            convolution_work(filter_type, true, src_width, src_height)
+        }
+        fr::ResizeAlg::Interpolation(filter_type) => {
+            convolution_work(filter_type, false, src_width, src_height)
+        }
+        fr::ResizeAlg::SuperSampling(filter_type, multiplicity) => {
+            // The source image is resized by the nearest algorithm first.
+            let multiplicity = multiplicity as f64;
+            let tmp_width = src_width.min(dst_width * multiplicity);
+            let tmp_height = src_height.min(dst_height * multiplicity);
+            tmp_width * tmp_height + convolution_work(filter_type, true, tmp_width, tmp_height)
+        }
+        _ => dst_width * dst_height,
+    };
+    work as u64
+}
+
+/// Region of the destination image: (left, top, width, height).
+type DstRegion = (u32, u32, u32, u32);
+
+/// Version of the format of `OptionsState`.
+const OPTIONS_STATE_VERSION: u8 = 1;
+
+/// Picklable state of `RustResizeOptions`. It is converted into a dict
+/// with names of fields as keys.
+#[derive(Clone, PartialEq, IntoPyObject, FromPyObject)]
+#[pyo3(from_item_all)]
+struct OptionsState {
+    version: u8,
+    algorithm: u8,
+    filter_type: u8,
+    multiplicity: u8,
+    use_alpha: bool,
+    /// Crop box of the source image: (left, top, width, height).
+    crop_box: Option<(f64, f64, f64, f64)>,
+    /// Centering of the source image fitted into the destination image.
+    fit_into_destination: Option<(f64, f64)>,
+    /// Centering of the source image contained in the destination image.
+    contain: Option<(f64, f64)>,
+    padding: Option<Vec<f64>>,
+    dst_region: Option<DstRegion>,
+    edge_mode: u8,
+    edge_color: Option<Vec<f64>>,
+    border_mode: u8,
+    thread_pool: Option<ResizerThreadPool>,
+    threading_threshold: u64,
+    linear_light: u8,
+    dithering: u8,
+    channel_order: Option<Vec<u8>>,
+    selected_channels: Option<Vec<u8>>,
+    detect_grayscale: bool,
+    bleed_alpha: Option<u32>,
+    background: Option<Vec<f64>>,
+}
+
+#[pyclass(module = "cykooz_resizer.rust_lib", skip_from_py_object)]
+#[derive(Clone)]
 pub struct RustResizeOptions {
     fir_options: fr::ResizeOptions,
     thread_pool: Option<ResizerThreadPool>,
+    threading_threshold: u64,
+    linear_light: Option<TransferFunction>,
+    dithering: Dithering,
+    channel_order: Option<Vec<usize>>,
+    selected_channels: Option<Vec<usize>>,
+    detect_grayscale: bool,
+    bleed_alpha: Option<u32>,
+    background: Option<Vec<f64>>,
+    contain: Option<(f64, f64)>,
+    padding: Option<Vec<f64>>,
+    dst_region: Option<DstRegion>,
+    edge_mode: EdgeMode,
+    edge_color: Option<Vec<f64>>,
+    border_mode: Option<EdgeMode>,
+}
+
+impl RustResizeOptions {
+    /// Returns thread pool if it is worth to use it
+    /// for resizing images with given sizes.
+    pub(crate) fn thread_pool_for(
+        &self,
+        src_size: (u32, u32),
+        dst_size: (u32, u32),
+    ) -> Option<&ResizerThreadPool> {
+        let dst_size = self
+            .dst_region
+            .map_or(dst_size, |(_, _, width, height)| (width, height));
+        self.thread_pool.as_ref().filter(|_| {
+            estimate_resize_work(src_size, dst_size, &self.fir_options) >= self.threading_threshold
+        })
+    }
+
+    /// Returns sources of channels of destination pixels
+    /// if channels have to be reordered.
+    fn channel_sources(&self) -> Option<Vec<ChannelSource>> {
+        let order = self.channel_order.as_ref()?;
+        Some(order.iter().copied().map(Some).collect())
+    }
+
+    pub(crate) fn algorithm(&self) -> fr::ResizeAlg {
+        self.fir_options.algorithm
+    }
+
+    /// Returns the crop box of the source image with the given size
+    /// that is resized into the destination image with the given size.
+    pub(crate) fn src_crop_box(&self, src_size: (u32, u32), dst_size: (u32, u32)) -> fr::CropBox {
+        match self.fir_options.cropping {
+            fr::SrcCropping::Crop(crop_box) => crop_box,
+            fr::SrcCropping::FitIntoDestination(centering) => fr::CropBox::fit_src_into_dst_size(
+                src_size.0,
+                src_size.1,
+                dst_size.0,
+                dst_size.1,
+                Some(centering),
+            ),
+            _ => fr::CropBox {
+                left: 0.,
+                top: 0.,
+                width: src_size.0 as f64,
+                height: src_size.1 as f64,
+            },
+        }
+    }
+
+    /// Returns a copy of the options with the given crop box.
+    pub(crate) fn cropped(&self, crop_box: fr::CropBox) -> Self {
+        Self {
+            fir_options: self.fir_options.crop(
+                crop_box.left,
+                crop_box.top,
+                crop_box.width,
+                crop_box.height,
+            ),
+            contain: None,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the name of the first option that requires the whole
+    /// source or destination image to resize it, or `None` if images
+    /// may be resized by bands of rows.
+    pub(crate) fn whole_image_option(&self) -> Option<&'static str> {
+        if matches!(self.fir_options.algorithm, fr::ResizeAlg::SuperSampling(..)) {
+            Some("Super-sampling")
+        } else if self.dithering != Dithering::None {
+            Some("Dithering")
+        } else if self.bleed_alpha.is_some() {
+            Some("Alpha bleeding")
+        } else if self.contain.is_some() {
+            Some("Containing in the destination image")
+        } else if self.dst_region.is_some() {
+            Some("Region of the destination image")
+        } else if self.border_mode.is_some() {
+            Some("Border mode")
+        } else {
+            None
+        }
+    }
+
+    fn state(&self) -> OptionsState {
+        let (algorithm, filter_type, multiplicity) = self.get_resize_alg();
+        let (crop_box, fit_into_destination) = match self.fir_options.cropping {
+            fr::SrcCropping::Crop(crop_box) => (
+                Some((crop_box.left, crop_box.top, crop_box.width, crop_box.height)),
+                None,
+            ),
+            fr::SrcCropping::FitIntoDestination(centering) => (None, Some(centering)),
+            _ => (None, None),
+        };
+        OptionsState {
+            version: OPTIONS_STATE_VERSION,
+            algorithm,
+            filter_type,
+            multiplicity,
+            use_alpha: self.fir_options.mul_div_alpha,
+            crop_box,
+            fit_into_destination,
+            contain: self.contain,
+            padding: self.padding.clone(),
+            dst_region: self.dst_region,
+            edge_mode: self.edge_mode.as_u8(),
+            edge_color: self.edge_color.clone(),
+            border_mode: EdgeMode::border_as_u8(self.border_mode),
+            thread_pool: self.thread_pool.clone(),
+            threading_threshold: self.threading_threshold,
+            linear_light: self.get_linear_light(),
+            dithering: self.dithering.as_u8(),
+            channel_order: self
+                .channel_order
+                .as_ref()
+                .map(|order| order.iter().map(|&c| c as u8).collect()),
+            selected_channels: self
+                .selected_channels
+                .as_ref()
+                .map(|channels| channels.iter().map(|&c| c as u8).collect()),
+            detect_grayscale: self.detect_grayscale,
+            bleed_alpha: self.bleed_alpha,
+            background: self.background.clone(),
+        }
+    }
+
+    fn set_state(&mut self, state: OptionsState) -> PyResult<()> {
+        if state.version != OPTIONS_STATE_VERSION {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported version of state of options: {}",
+                state.version
+            )));
+        }
+        let mut fir_options = fr::ResizeOptions::new()
+            .resize_alg(checked_resize_alg_from_u8(
+                state.algorithm,
+                state.filter_type,
+                state.multiplicity,
+            )?)
+            .use_alpha(state.use_alpha);
+        fir_options = match (state.crop_box, state.fit_into_destination, state.contain) {
+            (None, None, _) => fir_options,
+            (Some((left, top, width, height)), None, None) => {
+                fir_options.crop(left, top, width, height)
+            }
+            (None, Some(centering), None) => fir_options.fit_into_destination(Some(centering)),
+            _ => {
+                return Err(PyValueError::new_err(
+                    "Only one kind of cropping of the source image can be set",
+                ));
+            }
+        };
+        // Options are changed only if the whole state is valid
+        let (dithering, edge_mode) = (state.dithering, state.edge_mode);
+        *self = Self {
+            fir_options,
+            thread_pool: state.thread_pool,
+            threading_threshold: state.threading_threshold,
+            linear_light: linear_light_from_u8(state.linear_light)?,
+            dithering: Dithering::from_u8(dithering)
+                .ok_or_else(|| PyValueError::new_err(format!("Unknown dithering: {dithering}")))?,
+            channel_order: state
+                .channel_order
+                .map(|order| order.into_iter().map(|c| c as usize).collect()),
+            selected_channels: state
+                .selected_channels
+                .map(|channels| channels.into_iter().map(|c| c as usize).collect()),
+            detect_grayscale: state.detect_grayscale,
+            bleed_alpha: state.bleed_alpha,
+            background: state.background,
+            contain: state.contain,
+            padding: state.padding,
+            dst_region: state.dst_region,
+            edge_mode: EdgeMode::from_u8(edge_mode)
+                .ok_or_else(|| PyValueError::new_err(format!("Unknown edge mode: {edge_mode}")))?,
+            edge_color: state.edge_color,
+            border_mode: border_mode_from_u8(state.border_mode)?,
+        };
+        Ok(())
+    }
 }
 
 #[pymethods]
 impl RustResizeOptions {
     #[new]
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             fir_options: fr::ResizeOptions::new(),
             thread_pool: None,
+            threading_threshold: DEFAULT_THREADING_THRESHOLD,
+            linear_light: None,
+            dithering: Dithering::None,
+            channel_order: None,
+            selected_channels: None,
+            detect_grayscale: false,
+            bleed_alpha: None,
+            background: None,
+            contain: None,
+            padding: None,
+            dst_region: None,
+            edge_mode: EdgeMode::Transparent,
+            edge_color: None,
+            border_mode: None,
         }
     }
 
     fn copy(&self) -> Self {
-        Self {
-            fir_options: self.fir_options,
-            thread_pool: self.thread_pool.clone(),
-        }
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __getstate__(&self) -> OptionsState {
+        self.state()
+    }
+
+    fn __setstate__(&mut self, state: OptionsState) -> PyResult<()> {
+        self.set_state(state)
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.state() == other.state()
+    }
+
+    fn __hash__<'py>(&self, py: Python<'py>) -> PyResult<isize> {
+        let state = self.state().into_pyobject(py)?;
+        // Colors are converted into tuples, because lists are unhashable
+        let items = state
+            .iter()
+            .map(|(key, value)| match value.cast::<PyList>() {
+                Ok(list) => (key, list.to_tuple().into_any()),
+                Err(_) => (key, value),
+            })
+            .collect::<Vec<_>>();
+        PyTuple::new(py, items)?.hash()
+    }
+
+    fn __repr__(&self) -> String {
+        let thread_pool = match &self.thread_pool {
+            Some(thread_pool) => thread_pool.repr(),
+            None => "None".to_string(),
+        };
+        format!(
+            "RustResizeOptions(algorithm={:?}, cropping={:?}, use_alpha={}, \
+            thread_pool={}, threading_threshold={}, linear_light={:?}, \
+            dithering={:?}, channel_order={:?}, selected_channels={:?}, \
+            detect_grayscale={}, bleed_alpha={:?}, background={:?}, \
+            contain={:?}, padding={:?}, dst_region={:?}, edge_mode={:?}, \
+            edge_color={:?}, border_mode={:?})",
+            self.fir_options.algorithm,
+            self.fir_options.cropping,
+            if self.fir_options.mul_div_alpha {
+                "True"
+            } else {
+                "False"
+            },
+            thread_pool,
+            self.threading_threshold,
+            self.linear_light,
+            self.dithering,
+            self.get_channel_order(),
+            self.selected_channels,
+            if self.detect_grayscale {
+                "True"
+            } else {
+                "False"
+            },
+            self.bleed_alpha,
+            self.background,
+            self.contain,
+            self.padding,
+            self.dst_region,
+            self.edge_mode,
+            self.edge_color,
+            self.border_mode,
+        )
     }
 
     /// get_algorithm() -> Tuple[int, int, int]
@@ -79,16 +526,10 @@ impl RustResizeOptions {
     /// Set the resize algorithm.
     #[pyo3(signature = (algorithm, filter_type, multiplicity))]
     fn set_resize_alg(&mut self, algorithm: u8, filter_type: u8, multiplicity: u8) -> Self {
-        let resizer_alg = match algorithm {
-            1 => fr::ResizeAlg::Nearest,
-            2 => fr::ResizeAlg::Convolution(filter_type_from_u8(filter_type)),
-            3 => fr::ResizeAlg::Interpolation(filter_type_from_u8(filter_type)),
-            4 => fr::ResizeAlg::SuperSampling(filter_type_from_u8(filter_type), multiplicity),
-            _ => fr::ResizeAlg::Nearest,
-        };
+        let resizer_alg = resize_alg_from_u8(algorithm, filter_type, multiplicity);
         Self {
             fir_options: self.fir_options.resize_alg(resizer_alg),
-            thread_pool: self.thread_pool.clone(),
+            ..self.clone()
         }
     }
 
@@ -107,7 +548,8 @@ impl RustResizeOptions {
     fn set_crop_box(&self, left: f64, top: f64, width: f64, height: f64) -> Self {
         Self {
             fir_options: self.fir_options.crop(left, top, width, height),
-            thread_pool: self.thread_pool.clone(),
+            contain: None,
+            ..self.clone()
         }
     }
 
@@ -123,7 +565,161 @@ impl RustResizeOptions {
     fn set_fit_into_destination(&self, centering: Option<(f64, f64)>) -> Self {
         Self {
             fir_options: self.fir_options.fit_into_destination(centering),
-            thread_pool: self.thread_pool.clone(),
+            contain: None,
+            ..self.clone()
+        }
+    }
+
+    /// get_edge_mode() -> int
+    /// --
+    ///
+    /// Returns mode of getting pixels outside the edges of the source
+    /// image that are covered by the crop box.
+    ///
+    /// :rtype: int
+    fn get_edge_mode(&self) -> u8 {
+        self.edge_mode.as_u8()
+    }
+
+    /// Set mode of getting pixels outside the edges of the source image.
+    /// Mode: 0 - transparent, 1 - constant color, 2 - clamp, 3 - mirror, 4 - wrap.
+    #[pyo3(signature = (mode))]
+    fn set_edge_mode(&self, mode: u8) -> PyResult<Self> {
+        Ok(Self {
+            edge_mode: EdgeMode::from_u8(mode)
+                .ok_or_else(|| PyValueError::new_err(format!("Unknown edge mode: {mode}")))?,
+            ..self.clone()
+        })
+    }
+
+    /// get_edge_color() -> Optional[List[float]]
+    /// --
+    ///
+    /// Returns color of pixels outside the edges of the source image
+    /// that is used with the constant edge mode.
+    ///
+    /// :rtype: Optional[List[float]]
+    fn get_edge_color(&self) -> Option<Vec<f64>> {
+        self.edge_color.clone()
+    }
+
+    /// Set color (values of components of source pixels with or
+    /// without alpha channel) of pixels outside the edges of the source
+    /// image that is used with the constant edge mode. `None` - all
+    /// components are zero.
+    #[pyo3(signature = (color))]
+    fn set_edge_color(&self, color: Option<Vec<f64>>) -> PyResult<Self> {
+        if let Some(color) = &color
+            && (color.is_empty() || color.len() > 4 || color.iter().any(|v| !v.is_finite()))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid edge color: {color:?}"
+            )));
+        }
+        Ok(Self {
+            edge_color: color,
+            ..self.clone()
+        })
+    }
+
+    /// get_border_mode() -> int
+    /// --
+    ///
+    /// Returns mode of getting pixels outside the edges of the source
+    /// image that are used by the resize algorithm (0 - none).
+    ///
+    /// :rtype: int
+    fn get_border_mode(&self) -> u8 {
+        EdgeMode::border_as_u8(self.border_mode)
+    }
+
+    /// Set mode of getting pixels outside the edges of the source image
+    /// that are used by the resize algorithm near the edges.
+    /// Mode: 0 - none (kernels are clipped by the edges), 1 - clamp,
+    /// 2 - mirror, 3 - wrap.
+    #[pyo3(signature = (mode))]
+    fn set_border_mode(&self, mode: u8) -> PyResult<Self> {
+        Ok(Self {
+            border_mode: border_mode_from_u8(mode)?,
+            ..self.clone()
+        })
+    }
+
+    /// get_contain_in_destination_centering() -> Optional[Tuple[float, float]]
+    /// --
+    ///
+    /// Returns centering of the source image contained
+    /// in the destination image.
+    ///
+    /// :rtype: Optional[Tuple[float, float]]
+    fn get_contain_in_destination_centering(&self) -> Option<(f64, f64)> {
+        self.contain
+    }
+
+    /// Resize the whole source image into the biggest region of the destination image
+    /// with the aspect ratio of the source image. The rest of the destination image
+    /// is filled with the padding color.
+    #[pyo3(signature = (centering=None))]
+    fn set_contain_in_destination(&self, centering: Option<(f64, f64)>) -> Self {
+        Self {
+            fir_options: fr::ResizeOptions {
+                cropping: fr::SrcCropping::None,
+                ..self.fir_options
+            },
+            contain: Some(centering.unwrap_or((0.5, 0.5))),
+            ..self.clone()
+        }
+    }
+
+    /// get_padding() -> Optional[List[float]]
+    /// --
+    ///
+    /// Returns color that is used to fill the destination image
+    /// around the contained source image.
+    ///
+    /// :rtype: Optional[List[float]]
+    fn get_padding(&self) -> Option<Vec<f64>> {
+        self.padding.clone()
+    }
+
+    /// Set color (values of components of destination pixels with or
+    /// without alpha channel) that is used to fill the destination image
+    /// around the contained source image. `None` - the background color
+    /// or transparency.
+    #[pyo3(signature = (color))]
+    fn set_padding(&self, color: Option<Vec<f64>>) -> PyResult<Self> {
+        if let Some(color) = &color
+            && (color.is_empty() || color.len() > 4 || color.iter().any(|v| !v.is_finite()))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid padding color: {color:?}"
+            )));
+        }
+        Ok(Self {
+            padding: color,
+            ..self.clone()
+        })
+    }
+
+    /// get_dst_region() -> Optional[Tuple[int, int, int, int]]
+    /// --
+    ///
+    /// Returns region (left, top, width, height) of the destination
+    /// image into which the source image is resized.
+    ///
+    /// :rtype: Optional[Tuple[int, int, int, int]]
+    fn get_dst_region(&self) -> Option<DstRegion> {
+        self.dst_region
+    }
+
+    /// Set region (left, top, width, height) of the destination image
+    /// into which the source image is resized. Pixels outside the region
+    /// are not changed. `None` - the whole destination image.
+    #[pyo3(signature = (region))]
+    fn set_dst_region(&self, region: Option<DstRegion>) -> Self {
+        Self {
+            dst_region: region,
+            ..self.clone()
         }
     }
 
@@ -136,7 +732,7 @@ impl RustResizeOptions {
     fn set_use_alpha(&self, v: bool) -> Self {
         Self {
             fir_options: self.fir_options.use_alpha(v),
-            thread_pool: self.thread_pool.clone(),
+            ..self.clone()
         }
     }
 
@@ -154,10 +750,1590 @@ impl RustResizeOptions {
     #[pyo3(signature = (thread_pool))]
     fn set_thread_pool(&mut self, thread_pool: Option<ResizerThreadPool>) -> Self {
         Self {
-            fir_options: self.fir_options,
             thread_pool,
+            ..self.clone()
+        }
+    }
+
+    fn get_threading_threshold(&self) -> u64 {
+        self.threading_threshold
+    }
+
+    /// Set the minimal estimated amount of work required to use
+    /// a thread pool. One unit of work is approximately one access to
+    /// a source pixel while calculating a destination pixel.
+    #[pyo3(signature = (threshold))]
+    fn set_threading_threshold(&self, threshold: u64) -> Self {
+        Self {
+            threading_threshold: threshold,
+            ..self.clone()
         }
     }
+
+    /// get_linear_light() -> int
+    /// --
+    ///
+    /// Returns transfer function of colorspace that is used to convert
+    /// images into linear colorspace before resizing (0 - disabled).
+    ///
+    /// :rtype: int
+    fn get_linear_light(&self) -> u8 {
+        self.linear_light.map_or(0, |f| f.as_u8())
+    }
+
+    /// Enable or disable resizing in linear colorspace.
+    /// Transfer function: 0 - disabled, 1 - sRGB, 2 - gamma 2.2.
+    #[pyo3(signature = (transfer_function))]
+    fn set_linear_light(&self, transfer_function: u8) -> PyResult<Self> {
+        Ok(Self {
+            linear_light: linear_light_from_u8(transfer_function)?,
+            ..self.clone()
+        })
+    }
+
+    /// get_dithering() -> int
+    /// --
+    ///
+    /// Returns dithering that is used to reduce bit depth of pixels
+    /// if source and destination images have different types of pixels.
+    ///
+    /// :rtype: int
+    fn get_dithering(&self) -> u8 {
+        self.dithering.as_u8()
+    }
+
+    /// Set dithering that is used to reduce bit depth of pixels.
+    /// Dithering: 0 - disabled, 1 - ordered, 2 - blue-noise, 3 - Floyd–Steinberg.
+    #[pyo3(signature = (dithering))]
+    fn set_dithering(&self, dithering: u8) -> PyResult<Self> {
+        Ok(Self {
+            dithering: Dithering::from_u8(dithering)
+                .ok_or_else(|| PyValueError::new_err(format!("Unknown dithering: {dithering}")))?,
+            ..self.clone()
+        })
+    }
+
+    /// get_channel_order() -> Optional[List[int]]
+    /// --
+    ///
+    /// Returns order of channels of source pixels that is used
+    /// to store them into destination pixels.
+    ///
+    /// :rtype: Optional[List[int]]
+    fn get_channel_order(&self) -> Option<Vec<usize>> {
+        self.channel_order.clone()
+    }
+
+    /// Set order of channels of source pixels that is used to store
+    /// them into destination pixels (e.g. [2, 1, 0] to convert BGR into RGB).
+    #[pyo3(signature = (order))]
+    fn set_channel_order(&self, order: Option<Vec<usize>>) -> PyResult<Self> {
+        if let Some(order) = &order
+            && (order.is_empty() || order.len() > 4 || order.iter().any(|&c| c > 3))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid order of channels: {order:?}"
+            )));
+        }
+        Ok(Self {
+            channel_order: order,
+            ..self.clone()
+        })
+    }
+
+    /// get_selected_channels() -> Optional[List[int]]
+    /// --
+    ///
+    /// Returns channels of the source image that are resized
+    /// into the same channels of the destination image.
+    ///
+    /// :rtype: Optional[List[int]]
+    fn get_selected_channels(&self) -> Option<Vec<usize>> {
+        self.selected_channels.clone()
+    }
+
+    /// Set channels of the source image that are resized into
+    /// the same channels of the destination image. Other channels
+    /// of the destination image are not changed.
+    #[pyo3(signature = (channels))]
+    fn set_selected_channels(&self, channels: Option<Vec<usize>>) -> PyResult<Self> {
+        if let Some(channels) = &channels
+            && (channels.is_empty()
+                || channels.len() > 4
+                || channels.iter().any(|&c| c > 3)
+                || (1..channels.len()).any(|i| channels[..i].contains(&channels[i])))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid selected channels: {channels:?}"
+            )));
+        }
+        Ok(Self {
+            selected_channels: channels,
+            ..self.clone()
+        })
+    }
+
+    /// get_detect_grayscale() -> bool
+    /// --
+    ///
+    /// Returns `true` if a color source image is checked for
+    /// equality of red, green and blue channels to resize only
+    /// one channel of it.
+    ///
+    /// :rtype: bool
+    fn get_detect_grayscale(&self) -> bool {
+        self.detect_grayscale
+    }
+
+    /// Enable checking of a color source image for equality of red,
+    /// green and blue channels to resize only one channel of it.
+    #[pyo3(signature = (v))]
+    fn set_detect_grayscale(&self, v: bool) -> Self {
+        Self {
+            detect_grayscale: v,
+            ..self.clone()
+        }
+    }
+
+    /// get_bleed_alpha() -> Optional[int]
+    /// --
+    ///
+    /// Returns max count of iterations of filling color channels of
+    /// fully transparent pixels of the source image before resizing
+    /// or `None` if it is disabled.
+    ///
+    /// :rtype: Optional[int]
+    fn get_bleed_alpha(&self) -> Option<u32> {
+        self.bleed_alpha
+    }
+
+    /// Set max count of iterations of filling color channels of fully
+    /// transparent pixels of the source image with colors of the nearest
+    /// visible pixels before resizing. `None` disables it.
+    #[pyo3(signature = (iterations))]
+    fn set_bleed_alpha(&self, iterations: Option<u32>) -> Self {
+        Self {
+            bleed_alpha: iterations,
+            ..self.clone()
+        }
+    }
+
+    /// get_background() -> Optional[List[float]]
+    /// --
+    ///
+    /// Returns color that is used as background of images with
+    /// alpha channel or `None` if alpha channel is kept.
+    ///
+    /// :rtype: Optional[List[float]]
+    fn get_background(&self) -> Option<Vec<f64>> {
+        self.background.clone()
+    }
+
+    /// Set color (one or three values of components of destination
+    /// pixels) onto which the result of resizing of an image with
+    /// alpha channel is composited. `None` disables it.
+    #[pyo3(signature = (color))]
+    fn set_background(&self, color: Option<Vec<f64>>) -> PyResult<Self> {
+        if let Some(color) = &color
+            && (!matches!(color.len(), 1 | 3) || color.iter().any(|v| !v.is_finite()))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid background color: {color:?}"
+            )));
+        }
+        Ok(Self {
+            background: color,
+            ..self.clone()
+        })
+    }
+}
+
+/// Report about resizing of an image.
+#[pyclass(
+    module = "cykooz_resizer.rust_lib",
+    frozen,
+    eq,
+    get_all,
+    skip_from_py_object
+)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ResizeReport {
+    /// The source image is grayscale and only one channel
+    /// of it was resized.
+    grayscale: bool,
+    /// Alpha channel of the source image is constant, so multiplying
+    /// and dividing by alpha were skipped.
+    constant_alpha: bool,
+}
+
+#[pymethods]
+impl ResizeReport {
+    fn __repr__(&self) -> String {
+        let py_bool = |v: bool| if v { "True" } else { "False" };
+        format!(
+            "ResizeReport(grayscale={}, constant_alpha={})",
+            py_bool(self.grayscale),
+            py_bool(self.constant_alpha),
+        )
+    }
+}
+
+/// Resizes the source image into the destination image with
+/// the same type of pixels.
+fn resize_same_type(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    options: Option<&RustResizeOptions>,
+) -> Result<(), String> {
+    let fir_options = options.map(|o| &o.fir_options);
+    match options.and_then(|o| o.linear_light) {
+        Some(transfer_function) => resize_in_linear_light(
+            resizer,
+            transfer_function,
+            src_image,
+            dst_image,
+            fir_options,
+        ),
+        None => resizer
+            .resize(src_image, dst_image, fir_options)
+            .map_err(|err| err.to_string()),
+    }
+}
+
+/// Stage of processing of pixels at which an image is resized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResizeStage {
+    /// Before reordering of channels and conversion of pixels.
+    First,
+    /// After reordering of channels and before conversion of pixels.
+    Middle,
+    /// After reordering of channels and conversion of pixels.
+    Last,
+}
+
+/// Returns the stage at which the image must be resized.
+///
+/// The smaller of the source and destination images is preferred
+/// to reorder channels and convert pixels.
+fn resize_stage(
+    src_image: &impl IntoImageView,
+    dst_image: &impl IntoImageViewMut,
+    pixel_types: [fr::PixelType; 3],
+    order: Option<&[ChannelSource]>,
+    options: Option<&RustResizeOptions>,
+) -> ResizeStage {
+    let [src_pixel_type, swizzled_pixel_type, dst_pixel_type] = pixel_types;
+    let src_pixels = src_image.width() as u64 * src_image.height() as u64;
+    let dst_pixels = dst_image.width() as u64 * dst_image.height() as u64;
+    let preferred = if dst_pixels <= src_pixels {
+        [ResizeStage::First, ResizeStage::Middle, ResizeStage::Last]
+    } else {
+        [ResizeStage::Last, ResizeStage::Middle, ResizeStage::First]
+    };
+    let use_alpha = options.is_none_or(|o| o.fir_options.mul_div_alpha);
+    let linear_light = options.is_some_and(|o| o.linear_light.is_some());
+    let dithering = options.is_some_and(|o| o.dithering != Dithering::None)
+        && is_dithering_applicable(swizzled_pixel_type, dst_pixel_type);
+
+    let is_allowed = |stage: &ResizeStage| match stage {
+        // Alpha channel must have the same meaning before and after reordering
+        ResizeStage::First => {
+            let src_channels = count_of_channels(src_pixel_type);
+            !use_alpha || order.is_none_or(|o| is_alpha_preserved(src_channels, o))
+        }
+        // Without reordering of channels it is the same as the first stage
+        ResizeStage::Middle => order.is_some(),
+        ResizeStage::Last => true,
+    };
+    // Dithering must be applied to the result of resizing
+    let is_dithering_allowed = |stage: &ResizeStage| !dithering || *stage != ResizeStage::Last;
+    // Resizing in linear light requires pixels with integer components
+    let is_linear_light_supported = |stage: &ResizeStage| {
+        let pixel_type = match stage {
+            ResizeStage::First => src_pixel_type,
+            ResizeStage::Middle => swizzled_pixel_type,
+            ResizeStage::Last => dst_pixel_type,
+        };
+        !linear_light || is_supported_in_linear_light(pixel_type)
+    };
+    // If linear light is supported only by the destination type of pixels,
+    // the image is resized at the last stage without dithering.
+    preferred
+        .into_iter()
+        .filter(is_allowed)
+        .filter(is_linear_light_supported)
+        .find(is_dithering_allowed)
+        .or_else(|| {
+            preferred
+                .into_iter()
+                .filter(is_allowed)
+                .find(is_linear_light_supported)
+        })
+        .or_else(|| preferred.into_iter().find(is_allowed))
+        .unwrap_or(ResizeStage::First)
+}
+
+/// Reorders channels of pixels of the source image and converts them
+/// into pixels of the destination image.
+fn transform_pixels(
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    order: Option<&[ChannelSource]>,
+    swizzled_pixel_type: fr::PixelType,
+    dithering: Dithering,
+) -> Result<(), String> {
+    let Some(order) = order else {
+        return convert_pixels(src_image, dst_image, true, dithering);
+    };
+    if dst_image.pixel_type() == Some(swizzled_pixel_type) {
+        return swizzle_pixels(src_image, dst_image, order, None);
+    }
+    let mut tmp_image =
+        fr::images::Image::new(src_image.width(), src_image.height(), swizzled_pixel_type);
+    swizzle_pixels(src_image, &mut tmp_image, order, None)?;
+    convert_pixels(&tmp_image, dst_image, true, dithering)
+}
+
+/// Resizes selected channels of the source image into the same
+/// channels of the destination image.
+///
+/// Selected channels are resized as an independent image.
+/// Alpha channel is considered only if it is the last
+/// of 2 or 4 selected channels.
+fn resize_selected_channels(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    channels: &[usize],
+    options: Option<&RustResizeOptions>,
+) -> Result<(), String> {
+    if options.is_some_and(|o| o.channel_order.is_some()) {
+        return Err("Selected channels can't be resized with reordering of channels".to_string());
+    }
+    let pixel_type = src_image
+        .pixel_type()
+        .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string())?;
+    if dst_image.pixel_type() != Some(pixel_type) {
+        return Err(fr::ResizeError::PixelTypesAreDifferent.to_string());
+    }
+    let order: Vec<ChannelSource> = channels.iter().copied().map(Some).collect();
+    let selected_pixel_type = pixel_type_with_channels(pixel_type, channels.len())
+        .ok_or_else(|| fr::MappingError::UnsupportedCombinationOfImageTypes.to_string())?;
+    check_order(pixel_type, selected_pixel_type, &order)?;
+
+    // The last selected channel is not an alpha channel
+    // if it is not the alpha channel of source pixels.
+    let options_without_alpha;
+    let mut options = options;
+    if let Some(o) = options
+        && o.fir_options.mul_div_alpha
+        && !is_alpha_preserved(count_of_channels(pixel_type), &order)
+    {
+        options_without_alpha = RustResizeOptions {
+            fir_options: o.fir_options.use_alpha(false),
+            ..o.clone()
+        };
+        options = Some(&options_without_alpha);
+    }
+
+    let mut selected_src_image =
+        fr::images::Image::new(src_image.width(), src_image.height(), selected_pixel_type);
+    swizzle_pixels(src_image, &mut selected_src_image, &order, None)?;
+    let mut selected_dst_image =
+        fr::images::Image::new(dst_image.width(), dst_image.height(), selected_pixel_type);
+    resize_same_type(
+        resizer,
+        &selected_src_image,
+        &mut selected_dst_image,
+        options,
+    )?;
+    for (i, &channel) in channels.iter().enumerate() {
+        copy_channel(&selected_dst_image, i, dst_image, channel)?;
+    }
+    Ok(())
+}
+
+/// Resizes the source image into the destination image.
+///
+/// The source image is resized into the region of the destination
+/// image, if it is set, or into the part of it surrounded by padding,
+/// if the source image has to be contained in the destination image.
+/// The destination image is always cropped once, so the rest
+/// of the resizing is instantiated only for the cropped type
+/// of destination images.
+///
+/// If types of pixels of images are different or channels
+/// have to be reordered, the smaller of the source and destination
+/// images is processed through an intermediate image with the same size.
+pub(crate) fn resize_image(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    options: Option<&RustResizeOptions>,
+) -> PyResult<ResizeReport> {
+    let (dst_width, dst_height) = (dst_image.width(), dst_image.height());
+    let mut region = (0, 0, dst_width, dst_height);
+    if let Some((left, top, width, height)) = options.and_then(|o| o.dst_region) {
+        let right = left as u64 + width as u64;
+        let bottom = top as u64 + height as u64;
+        if right > dst_width as u64
+            || bottom > dst_height as u64
+            || left >= dst_width
+            || top >= dst_height
+        {
+            return Err(PyValueError::new_err(
+                "Region is out of boundaries of the destination image",
+            ));
+        }
+        region = (left, top, width, height);
+    }
+    if let Some(o) = options
+        && let Some(centering) = o.contain
+    {
+        let (outer_left, outer_top, outer_width, outer_height) = region;
+        let src_size = (src_image.width(), src_image.height());
+        let (left, top, width, height) =
+            contained_region(src_size, (outer_width, outer_height), centering);
+        let padding = result2pyresult(padding_color(dst_image, o))?;
+        let inner = (outer_left + left, outer_top + top, width, height);
+        result2pyresult(fill_around_region(dst_image, region, inner, &padding))?;
+        region = inner;
+    }
+    let (left, top, width, height) = region;
+    if width == 0 || height == 0 {
+        return Ok(ResizeReport::default());
+    }
+    let mut dst_region = fr::images::CroppedImageMut::new(dst_image, left, top, width, height)
+        .map_err(|err| PyValueError::new_err(err.to_string()))?;
+    resize_with_edges(resizer, src_image, &mut dst_region, options)
+}
+
+/// Returns the region (left, top, width, height) of the destination
+/// image into which the whole source image is resized without
+/// distortions.
+///
+/// The region is placed inside the destination image
+/// in the same way as the crop box of `fr::SrcCropping::FitIntoDestination`
+/// is placed inside the source image.
+fn contained_region(
+    src_size: (u32, u32),
+    dst_size: (u32, u32),
+    centering: (f64, f64),
+) -> (u32, u32, u32, u32) {
+    let (src_width, src_height) = (src_size.0 as f64, src_size.1 as f64);
+    let (dst_width, dst_height) = dst_size;
+    if src_width == 0. || src_height == 0. || dst_width == 0 || dst_height == 0 {
+        return (0, 0, dst_width, dst_height);
+    }
+    let scale = (dst_width as f64 / src_width).min(dst_height as f64 / src_height);
+    let width = ((src_width * scale).round() as u32).clamp(1, dst_width);
+    let height = ((src_height * scale).round() as u32).clamp(1, dst_height);
+    let (x, y) = (centering.0.clamp(0., 1.), centering.1.clamp(0., 1.));
+    let left = ((dst_width - width) as f64 * x).round() as u32;
+    let top = ((dst_height - height) as f64 * y).round() as u32;
+    (left, top, width, height)
+}
+
+/// Returns values of all components of the color for pixels
+/// of the image or an error with the given name of the color.
+///
+/// The color may be set without alpha channel, then it is opaque.
+/// `None` - all components are zero (transparent or black).
+fn pixel_color(
+    image: &impl IntoImageView,
+    color: Option<&Vec<f64>>,
+    name: &str,
+) -> Result<Vec<f64>, String> {
+    let pixel_type = image
+        .pixel_type()
+        .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string())?;
+    let channels = count_of_channels(pixel_type);
+    let Some(color) = color else {
+        return Ok(vec![0.; channels]);
+    };
+    let mut color = color.clone();
+    if has_alpha(channels) && color.len() == channels - 1 {
+        color.push(opaque_value(pixel_type));
+    }
+    if color.len() != channels {
+        return Err(format!(
+            "{name} color must have {channels} components for {pixel_type:?} pixels"
+        ));
+    }
+    Ok(color)
+}
+
+/// Returns values of all components of the color that is used to fill
+/// the destination image around the contained source image.
+///
+/// By default, the background color is used, if it is set,
+/// or all components are zero (transparent or black).
+fn padding_color(
+    dst_image: &impl IntoImageView,
+    options: &RustResizeOptions,
+) -> Result<Vec<f64>, String> {
+    let color = options.padding.as_ref().or(options.background.as_ref());
+    pixel_color(dst_image, color, "Padding")
+}
+
+/// Returns count of pixels around the crop box which are used
+/// by the resize algorithm to calculate pixels of the destination image
+/// with the given size.
+pub(crate) fn kernel_margin(
+    algorithm: fr::ResizeAlg,
+    crop_box: fr::CropBox,
+    dst_size: (u32, u32),
+) -> (f64, f64) {
+    let (support, adaptive) = match algorithm {
+        fr::ResizeAlg::Convolution(filter_type) | fr::ResizeAlg::SuperSampling(filter_type, _) => {
+            (filter_support(filter_type), true)
+        }
+        fr::ResizeAlg::Interpolation(filter_type) => (filter_support(filter_type), false),
+        // One pixel covers rounding of positions of pixels
+        // that are used by the nearest algorithm.
+        _ => return (1., 1.),
+    };
+    let margin = |src_size: f64, dst_size: u32| {
+        let scale = if adaptive {
+            (src_size / dst_size.max(1) as f64).max(1.)
+        } else {
+            1.
+        };
+        // One more pixel covers rounding of positions of the kernel
+        (support * scale).ceil() + 1.
+    };
+    (
+        margin(crop_box.width, dst_size.0),
+        margin(crop_box.height, dst_size.1),
+    )
+}
+
+/// Returns the crop box of the source image if it extends beyond
+/// the edges of the source image.
+fn crop_box_beyond_edges(
+    src_image: &impl IntoImageView,
+    options: &RustResizeOptions,
+) -> Option<fr::CropBox> {
+    let fr::SrcCropping::Crop(crop_box) = options.fir_options.cropping else {
+        return None;
+    };
+    let values = [crop_box.left, crop_box.top, crop_box.width, crop_box.height];
+    if values.iter().any(|v| !v.is_finite()) || crop_box.width <= 0. || crop_box.height <= 0. {
+        // Invalid crop boxes are reported by the resizer.
+        return None;
+    }
+    let beyond = crop_box.left < 0.
+        || crop_box.top < 0.
+        || crop_box.left + crop_box.width > src_image.width() as f64
+        || crop_box.top + crop_box.height > src_image.height() as f64;
+    beyond.then_some(crop_box)
+}
+
+/// Returns the crop box of the source image if the resize algorithm
+/// uses pixels outside the edges of the source image to resize it.
+fn crop_box_near_edges(
+    src_image: &impl IntoImageView,
+    dst_size: (u32, u32),
+    options: &RustResizeOptions,
+) -> Option<fr::CropBox> {
+    let (src_width, src_height) = (src_image.width(), src_image.height());
+    if src_width == 0 || src_height == 0 || dst_size.0 == 0 || dst_size.1 == 0 {
+        return None;
+    }
+    let crop_box = options.src_crop_box((src_width, src_height), dst_size);
+    let values = [crop_box.left, crop_box.top, crop_box.width, crop_box.height];
+    if values.iter().any(|v| !v.is_finite()) || crop_box.width <= 0. || crop_box.height <= 0. {
+        return None;
+    }
+    let (margin_x, margin_y) = kernel_margin(options.fir_options.algorithm, crop_box, dst_size);
+    let near = crop_box.left - margin_x < 0.
+        || crop_box.top - margin_y < 0.
+        || crop_box.left + crop_box.width + margin_x > src_width as f64
+        || crop_box.top + crop_box.height + margin_y > src_height as f64;
+    near.then_some(crop_box)
+}
+
+/// Returns a range of coordinates of pixels that are used to resize
+/// the range of the source image with the given size. The range
+/// is extended by the margin beyond the edges of the source image
+/// only if the range itself extends beyond them or if `all_edges`
+/// is `true`.
+fn extended_range(start: f64, length: f64, margin: f64, size: u32, all_edges: bool) -> (i64, i64) {
+    let end = start + length;
+    let mut extended_start = start.floor() - margin;
+    if start >= 0. && !all_edges {
+        extended_start = extended_start.max(0.);
+    }
+    let mut extended_end = end.ceil() + margin;
+    if end <= size as f64 && !all_edges {
+        extended_end = extended_end.min(size as f64);
+    }
+    (extended_start as i64, extended_end as i64)
+}
+
+/// Resizes the crop box of the source image using pixels outside
+/// its edges, which are got in accordance with the edge mode.
+///
+/// The crop box with margins of the resize algorithm is copied into
+/// a temporary image, so resizing needs memory for one more copy
+/// of the crop box. Time of copying is near the time of `memcpy`,
+/// e.g. the mirror border mode adds 5.5 ms to 12 ms of resizing
+/// of 2048x2048 U8x4 image into 256x256 by Lanczos3 (single thread,
+/// see `test_border_mode_time` in tests/test_benchmark.py).
+fn resize_beyond_edges(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    crop_box: fr::CropBox,
+    mode: EdgeMode,
+    options: &RustResizeOptions,
+) -> PyResult<ResizeReport> {
+    let pixel_type = result2pyresult(
+        src_image
+            .pixel_type()
+            .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string()),
+    )?;
+    let color = match mode {
+        EdgeMode::Constant => options.edge_color.as_ref(),
+        _ => None,
+    };
+    if options.fir_options.algorithm == fr::ResizeAlg::Nearest {
+        return resize_nearest_beyond_edges(
+            resizer, src_image, dst_image, crop_box, mode, color, options,
+        );
+    }
+    // All pixels used by the resize algorithm are copied into one
+    // extended image. Regions of the destination image can't be resized
+    // separately, because precision of coefficients of the convolution
+    // depends on all coefficients along an axis.
+    let dst_size = (dst_image.width(), dst_image.height());
+    let (margin_x, margin_y) = kernel_margin(options.fir_options.algorithm, crop_box, dst_size);
+    // The resize algorithm uses pixels outside all edges
+    // if the border mode is set.
+    let all_edges = options.border_mode.is_some();
+    let (left, right) = extended_range(
+        crop_box.left,
+        crop_box.width,
+        margin_x,
+        src_image.width(),
+        all_edges,
+    );
+    let (top, bottom) = extended_range(
+        crop_box.top,
+        crop_box.height,
+        margin_y,
+        src_image.height(),
+        all_edges,
+    );
+    let (Ok(width), Ok(height)) = (u32::try_from(right - left), u32::try_from(bottom - top)) else {
+        return Err(PyValueError::new_err("Crop box is too big"));
+    };
+    let mut extended_image = fr::images::Image::new(width, height, pixel_type);
+    let color = result2pyresult(pixel_color(&extended_image, color, "Edge"))?;
+    result2pyresult(extend_image(
+        src_image,
+        &mut extended_image,
+        left,
+        top,
+        mode,
+        &color,
+    ))?;
+    // The crop box must not extend beyond the extended image
+    // due to rounding errors.
+    let crop_left = crop_box.left - left as f64;
+    let crop_top = crop_box.top - top as f64;
+    let extended_options = RustResizeOptions {
+        fir_options: options.fir_options.crop(
+            crop_left,
+            crop_top,
+            crop_box.width.min(width as f64 - crop_left),
+            crop_box.height.min(height as f64 - crop_top),
+        ),
+        ..options.clone()
+    };
+    resize_bled_image(resizer, &extended_image, dst_image, Some(&extended_options))
+}
+
+/// Resizes the crop box of the source image by the nearest algorithm
+/// using pixels outside its edges, which are got in accordance
+/// with the edge mode.
+///
+/// Positions of the nearest source pixels are calculated without
+/// rounding errors, so they don't depend on the position of the crop
+/// box relative to the copied pixels. The sampled pixels are copied
+/// into an image with the size of the destination image, which is
+/// processed by the resizer in the same way as the source image.
+fn resize_nearest_beyond_edges(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    crop_box: fr::CropBox,
+    mode: EdgeMode,
+    color: Option<&Vec<f64>>,
+    options: &RustResizeOptions,
+) -> PyResult<ResizeReport> {
+    let pixel_type = result2pyresult(
+        src_image
+            .pixel_type()
+            .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string()),
+    )?;
+    let (dst_width, dst_height) = (dst_image.width(), dst_image.height());
+    // Coordinates of source pixels under centers of destination pixels
+    let coordinates = |start: f64, length: f64, dst_size: u32| -> Vec<i64> {
+        (0..dst_size as u64)
+            .map(|i| (start + (2 * i + 1) as f64 * length / (2 * dst_size as u64) as f64).floor())
+            .map(|c| c as i64)
+            .collect()
+    };
+    let columns = coordinates(crop_box.left, crop_box.width, dst_width);
+    let rows = coordinates(crop_box.top, crop_box.height, dst_height);
+    let mut sampled_image = fr::images::Image::new(dst_width, dst_height, pixel_type);
+    let color = result2pyresult(pixel_color(&sampled_image, color, "Edge"))?;
+    result2pyresult(sample_image(
+        src_image,
+        &mut sampled_image,
+        &columns,
+        &rows,
+        mode,
+        &color,
+    ))?;
+    let sampled_options = RustResizeOptions {
+        fir_options: options
+            .fir_options
+            .crop(0., 0., dst_width as f64, dst_height as f64),
+        ..options.clone()
+    };
+    resize_bled_image(resizer, &sampled_image, dst_image, Some(&sampled_options))
+}
+
+/// Resizes the source image into the destination image using pixels
+/// outside the edges of the source image if the crop box extends
+/// beyond them or if the border mode is set.
+fn resize_with_edges(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    options: Option<&RustResizeOptions>,
+) -> PyResult<ResizeReport> {
+    if let Some(o) = options {
+        if let Some(crop_box) = crop_box_beyond_edges(src_image, o) {
+            return resize_beyond_edges(resizer, src_image, dst_image, crop_box, o.edge_mode, o);
+        }
+        let dst_size = (dst_image.width(), dst_image.height());
+        if let Some(mode) = o.border_mode
+            && let Some(crop_box) = crop_box_near_edges(src_image, dst_size, o)
+        {
+            return resize_beyond_edges(resizer, src_image, dst_image, crop_box, mode, o);
+        }
+    }
+    resize_bled_image(resizer, src_image, dst_image, options)
+}
+
+/// Resizes the source image into the destination image, filling
+/// color channels of transparent pixels of the source image
+/// before resizing if it is required.
+///
+/// Only pixels that are used to resize the crop box (the crop box
+/// with margins of the resize algorithm) are copied and bled,
+/// so transparent pixels are filled with colors of the nearest
+/// visible pixels inside this region.
+fn resize_bled_image(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    options: Option<&RustResizeOptions>,
+) -> PyResult<ResizeReport> {
+    let (Some(o), Some(pixel_type)) = (options, src_image.pixel_type()) else {
+        return resize_with_report(resizer, src_image, dst_image, options);
+    };
+    let Some(iterations) = o.bleed_alpha else {
+        return resize_with_report(resizer, src_image, dst_image, options);
+    };
+    let dst_size = (dst_image.width(), dst_image.height());
+    let (crop_box, region) = used_src_region(src_image, dst_size, o);
+    let (left, top, width, height) = region;
+    // Invalid crop boxes are reported by the resizer.
+    // Only images with transparent and visible pixels have to be bled.
+    let src_region = fr::images::CroppedImage::new(src_image, left, top, width, height).ok();
+    let Some(src_region) = src_region.filter(|r| scan_alpha(r) == Some(AlphaState::Varying)) else {
+        return resize_with_report(resizer, src_image, dst_image, options);
+    };
+    let mut bled_image = fr::images::Image::new(width, height, pixel_type);
+    result2pyresult(copy_pixels(&src_region, &mut bled_image))?;
+    result2pyresult(bleed_alpha(&mut bled_image, iterations))?;
+    let crop_box = crop_box_in_region(crop_box, region);
+    let bled_options = RustResizeOptions {
+        fir_options: o.fir_options.crop(
+            crop_box.left,
+            crop_box.top,
+            crop_box.width,
+            crop_box.height,
+        ),
+        ..o.clone()
+    };
+    resize_with_report(resizer, &bled_image, dst_image, Some(&bled_options))
+}
+
+fn resize_with_report(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    options: Option<&RustResizeOptions>,
+) -> PyResult<ResizeReport> {
+    let mut report = ResizeReport::default();
+    let options_without_alpha;
+    let mut options = options;
+    let dst_size = (dst_image.width(), dst_image.height());
+    if is_alpha_constant(src_image, dst_size, options) {
+        options_without_alpha = RustResizeOptions {
+            fir_options: options
+                .map_or_else(fr::ResizeOptions::new, |o| o.fir_options)
+                .use_alpha(false),
+            ..options.cloned().unwrap_or_else(RustResizeOptions::new)
+        };
+        options = Some(&options_without_alpha);
+        report.constant_alpha = true;
+    }
+    if let Some(background) = options.and_then(|o| o.background.as_deref())
+        && src_image
+            .pixel_type()
+            .is_some_and(|pixel_type| has_alpha(count_of_channels(pixel_type)))
+    {
+        result2pyresult(resize_onto_background(
+            resizer, src_image, dst_image, background, options,
+        ))?;
+        return Ok(report);
+    }
+    if options.is_some_and(|o| o.detect_grayscale) {
+        report.grayscale =
+            result2pyresult(resize_grayscale(resizer, src_image, dst_image, options))?;
+        if report.grayscale {
+            return Ok(report);
+        }
+    }
+    result2pyresult(resize_and_transform(resizer, src_image, dst_image, options))?;
+    Ok(report)
+}
+
+/// Resizes the source image with alpha channel and composites
+/// the result onto the background color.
+///
+/// Pixels of the crop box of the source image (with pixels around it
+/// used by the resize algorithm) are multiplied by alpha before
+/// resizing, so the premultiplied result of resizing is composited
+/// without dividing by alpha. The result is composited in place
+/// if the destination image has the same type of pixels as the source
+/// image. Values of the background color are given in units
+/// of destination pixel components.
+///
+/// Color pixels are composited onto the background and converted into
+/// grayscale (with ITU-R BT.601 weights) if the destination image has
+/// only one channel. The background color of such destination image may
+/// have only one component.
+fn resize_onto_background(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    background: &[f64],
+    options: Option<&RustResizeOptions>,
+) -> Result<(), String> {
+    if options.is_some_and(|o| o.channel_order.is_some() || o.selected_channels.is_some()) {
+        return Err(
+            "Background can't be used with reordering or selecting of channels".to_string(),
+        );
+    }
+    let (Some(src_pixel_type), Some(dst_pixel_type)) =
+        (src_image.pixel_type(), dst_image.pixel_type())
+    else {
+        return Err(fr::ImageError::UnsupportedPixelType.to_string());
+    };
+    // Destination pixels may have alpha channel that is filled by
+    // the opaque value (e.g. RGB images of PIL have 4 channels).
+    let src_channels = count_of_channels(src_pixel_type);
+    let dst_channels = count_of_channels(dst_pixel_type);
+    let grayscale = src_channels == 4 && dst_channels == 1;
+    let flat_channels = if grayscale { 3 } else { dst_channels };
+    let flat_pixel_type = pixel_type_with_channels(src_pixel_type, flat_channels)
+        .filter(|_| flat_channels == src_channels || flat_channels == src_channels - 1)
+        .ok_or_else(|| fr::ResizeError::PixelTypesAreDifferent.to_string())?;
+    // Type of pixels that are converted into destination pixels
+    let last_pixel_type = match grayscale {
+        true => pixel_type_with_channels(src_pixel_type, 1),
+        false => Some(flat_pixel_type),
+    }
+    .filter(|&pixel_type| is_convertible(pixel_type, dst_pixel_type))
+    .ok_or_else(|| fr::ResizeError::PixelTypesAreDifferent.to_string())?;
+    let scale = opaque_value(src_pixel_type) / opaque_value(dst_pixel_type);
+    let mut background: Vec<f64> = background.iter().map(|&v| v * scale).collect();
+    if grayscale && background.len() == 1 {
+        background = background.repeat(3);
+    }
+
+    let fir_options = options.map_or_else(fr::ResizeOptions::new, |o| o.fir_options);
+    // Alpha must be multiplied with linear values of color components
+    let premultiply = fir_options.mul_div_alpha
+        && fir_options.algorithm != fr::ResizeAlg::Nearest
+        && options.is_none_or(|o| o.linear_light.is_none());
+    // Pixels are composited in place if the destination image
+    // has the same type of pixels as the source image.
+    let mut resized_image = None;
+    if dst_pixel_type != src_pixel_type {
+        let (width, height) = (dst_image.width(), dst_image.height());
+        resized_image = Some(fr::images::Image::new(width, height, src_pixel_type));
+    }
+    if premultiply {
+        let options = options.cloned().unwrap_or_else(RustResizeOptions::new);
+        let dst_size = (dst_image.width(), dst_image.height());
+        let (premultiplied_image, crop_box) =
+            premultiply_crop_box(resizer, src_image, dst_size, &options)?;
+        let options_without_alpha = RustResizeOptions {
+            fir_options: fir_options.use_alpha(false).crop(
+                crop_box.left,
+                crop_box.top,
+                crop_box.width,
+                crop_box.height,
+            ),
+            ..options
+        };
+        let options = Some(&options_without_alpha);
+        match resized_image.as_mut() {
+            Some(image) => resize_same_type(resizer, &premultiplied_image, image, options)?,
+            None => resize_same_type(resizer, &premultiplied_image, dst_image, options)?,
+        }
+    } else {
+        match resized_image.as_mut() {
+            Some(image) => resize_same_type(resizer, src_image, image, options)?,
+            None => resize_same_type(resizer, src_image, dst_image, options)?,
+        }
+    }
+
+    let Some(resized_image) = resized_image else {
+        return flatten_alpha_inplace(dst_image, &background, premultiply);
+    };
+    if flat_pixel_type == dst_pixel_type {
+        return flatten_alpha(&resized_image, dst_image, &background, premultiply);
+    }
+    let (width, height) = (dst_image.width(), dst_image.height());
+    let mut flat_image = fr::images::Image::new(width, height, flat_pixel_type);
+    flatten_alpha(&resized_image, &mut flat_image, &background, premultiply)?;
+    let dithering = options.map(|o| o.dithering).unwrap_or_default();
+    if !grayscale {
+        return convert_pixels(&flat_image, dst_image, true, dithering);
+    }
+    if last_pixel_type == dst_pixel_type {
+        return to_grayscale(&flat_image, dst_image, &REC601_WEIGHTS);
+    }
+    let mut gray_image = fr::images::Image::new(width, height, last_pixel_type);
+    to_grayscale(&flat_image, &mut gray_image, &REC601_WEIGHTS)?;
+    convert_pixels(&gray_image, dst_image, true, dithering)
+}
+
+/// Returns the crop box of the source image and the region
+/// (left, top, width, height) of the source image with pixels that
+/// are used to resize the crop box into the destination image
+/// with the given size.
+fn used_src_region(
+    src_image: &impl IntoImageView,
+    dst_size: (u32, u32),
+    options: &RustResizeOptions,
+) -> (fr::CropBox, (u32, u32, u32, u32)) {
+    let (src_width, src_height) = (src_image.width(), src_image.height());
+    let crop_box = options.src_crop_box((src_width, src_height), dst_size);
+    let (margin_x, margin_y) = kernel_margin(options.fir_options.algorithm, crop_box, dst_size);
+    let range = |start: f64, length: f64, margin: f64, size: u32| {
+        let (start, end) = extended_range(start, length, margin, size, false);
+        let start = start.clamp(0, size as i64) as u32;
+        let end = end.clamp(start as i64, size as i64) as u32;
+        (start, end - start)
+    };
+    let (left, width) = range(crop_box.left, crop_box.width, margin_x, src_width);
+    let (top, height) = range(crop_box.top, crop_box.height, margin_y, src_height);
+    (crop_box, (left, top, width, height))
+}
+
+/// Multiplies color channels by alpha only for pixels of the source
+/// image that are used to resize its crop box into the destination
+/// image with the given size.
+///
+/// Returns the premultiplied pixels and the crop box relative to them.
+fn premultiply_crop_box(
+    resizer: &fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_size: (u32, u32),
+    options: &RustResizeOptions,
+) -> Result<(fr::images::Image<'static>, fr::CropBox), String> {
+    let pixel_type = src_image
+        .pixel_type()
+        .ok_or_else(|| fr::ImageError::UnsupportedPixelType.to_string())?;
+    let (crop_box, (left, top, width, height)) = used_src_region(src_image, dst_size, options);
+    let src_region = fr::images::CroppedImage::new(src_image, left, top, width, height)
+        .map_err(|err| err.to_string())?;
+    let mut premultiplied_image = fr::images::Image::new(width, height, pixel_type);
+    let mut mul_div = fr::MulDiv::new();
+    // SAFETY: the resizer uses only extensions supported by the CPU
+    unsafe { mul_div.set_cpu_extensions(resizer.cpu_extensions()) };
+    mul_div
+        .multiply_alpha(&src_region, &mut premultiplied_image)
+        .map_err(|err| err.to_string())?;
+    let crop_box = crop_box_in_region(crop_box, (left, top, width, height));
+    Ok((premultiplied_image, crop_box))
+}
+
+/// Returns the crop box relative to the region (left, top, width, height)
+/// of the source image that contains it.
+fn crop_box_in_region(crop_box: fr::CropBox, region: (u32, u32, u32, u32)) -> fr::CropBox {
+    let (left, top, width, height) = region;
+    // The crop box must not extend beyond the region
+    // due to rounding errors.
+    let (crop_left, crop_top) = (crop_box.left - left as f64, crop_box.top - top as f64);
+    fr::CropBox {
+        left: crop_left,
+        top: crop_top,
+        width: crop_box.width.min(width as f64 - crop_left),
+        height: crop_box.height.min(height as f64 - crop_top),
+    }
+}
+
+/// Returns `true` if the source image has to be resized with considering
+/// of alpha channel, but all its pixels used to resize the crop box
+/// into the destination image with the given size have the same
+/// non-zero alpha. Multiplying and dividing by alpha don't change
+/// the result of resizing of such image, so they can be skipped.
+fn is_alpha_constant(
+    src_image: &impl IntoImageView,
+    dst_size: (u32, u32),
+    options: Option<&RustResizeOptions>,
+) -> bool {
+    let fir_options = options.map(|o| &o.fir_options);
+    if fir_options.is_some_and(|o| !o.mul_div_alpha || o.algorithm == fr::ResizeAlg::Nearest) {
+        return false;
+    }
+    let Some(src_pixel_type) = src_image.pixel_type() else {
+        return false;
+    };
+    // Alpha channel must have the same meaning after reordering of channels
+    let src_channels = count_of_channels(src_pixel_type);
+    if let Some(order) = options.and_then(RustResizeOptions::channel_sources)
+        && !is_alpha_preserved(src_channels, &order)
+    {
+        return false;
+    }
+    // Only pixels used by the resize algorithm are scanned
+    let default_options;
+    let options = match options {
+        Some(options) => options,
+        None => {
+            default_options = RustResizeOptions::new();
+            &default_options
+        }
+    };
+    let (_, (left, top, width, height)) = used_src_region(src_image, dst_size, options);
+    let Ok(src_region) = fr::images::CroppedImage::new(src_image, left, top, width, height) else {
+        return false;
+    };
+    matches!(
+        scan_alpha(&src_region),
+        Some(AlphaState::Opaque | AlphaState::Constant)
+    )
+}
+
+/// Resizes only one color channel of the source image if all its
+/// pixels are grayscale, and expands the result into the color
+/// channels of the destination image.
+///
+/// Returns `false` if the source image is not grayscale or can't
+/// be resized in this way.
+fn resize_grayscale(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    options: Option<&RustResizeOptions>,
+) -> Result<bool, String> {
+    if options.is_some_and(|o| o.channel_order.is_some() || o.selected_channels.is_some()) {
+        return Ok(false);
+    }
+    let (Some(src_pixel_type), Some(dst_pixel_type)) =
+        (src_image.pixel_type(), dst_image.pixel_type())
+    else {
+        return Ok(false);
+    };
+    let (Some(src_gray_pixel_type), Some(dst_gray_pixel_type)) = (
+        grayscale_pixel_type(src_pixel_type),
+        grayscale_pixel_type(dst_pixel_type),
+    ) else {
+        return Ok(false);
+    };
+    if count_of_channels(src_pixel_type) != count_of_channels(dst_pixel_type)
+        || !is_grayscale(src_image)
+    {
+        return Ok(false);
+    }
+
+    // Red channel and alpha channel (if it is present)
+    let (gray_order, color_order) = if has_alpha(count_of_channels(src_pixel_type)) {
+        (
+            vec![Some(0), Some(3)],
+            vec![Some(0), Some(0), Some(0), Some(1)],
+        )
+    } else {
+        (vec![Some(0)], vec![Some(0), Some(0), Some(0)])
+    };
+    let mut gray_src_image =
+        fr::images::Image::new(src_image.width(), src_image.height(), src_gray_pixel_type);
+    swizzle_pixels(src_image, &mut gray_src_image, &gray_order, None)?;
+    let mut gray_dst_image =
+        fr::images::Image::new(dst_image.width(), dst_image.height(), dst_gray_pixel_type);
+    resize_and_transform(resizer, &gray_src_image, &mut gray_dst_image, options)?;
+    swizzle_pixels(&gray_dst_image, dst_image, &color_order, None)?;
+    Ok(true)
+}
+
+fn resize_and_transform(
+    resizer: &mut fr::Resizer,
+    src_image: &impl IntoImageView,
+    dst_image: &mut impl IntoImageViewMut,
+    options: Option<&RustResizeOptions>,
+) -> Result<(), String> {
+    if let Some(channels) = options.and_then(|o| o.selected_channels.as_deref()) {
+        return resize_selected_channels(resizer, src_image, dst_image, channels, options);
+    }
+    let order = options.and_then(RustResizeOptions::channel_sources);
+    let order = order.as_deref();
+    let (Some(src_pixel_type), Some(dst_pixel_type)) =
+        (src_image.pixel_type(), dst_image.pixel_type())
+    else {
+        return resize_same_type(resizer, src_image, dst_image, options);
+    };
+    if order.is_none() && src_pixel_type == dst_pixel_type {
+        return resize_same_type(resizer, src_image, dst_image, options);
+    }
+    // Type of pixels after reordering of channels
+    let swizzled_pixel_type = match order {
+        Some(order) => {
+            check_order(src_pixel_type, dst_pixel_type, order)?;
+            pixel_type_with_channels(src_pixel_type, order.len())
+                .ok_or_else(|| fr::MappingError::UnsupportedCombinationOfImageTypes.to_string())?
+        }
+        None => src_pixel_type,
+    };
+    if !is_convertible(swizzled_pixel_type, dst_pixel_type) {
+        return Err(fr::ResizeError::PixelTypesAreDifferent.to_string());
+    }
+    let dithering = options.map(|o| o.dithering).unwrap_or_default();
+    let pixel_types = [src_pixel_type, swizzled_pixel_type, dst_pixel_type];
+
+    match resize_stage(src_image, dst_image, pixel_types, order, options) {
+        ResizeStage::First => {
+            let mut tmp_image =
+                fr::images::Image::new(dst_image.width(), dst_image.height(), src_pixel_type);
+            resize_same_type(resizer, src_image, &mut tmp_image, options)?;
+            transform_pixels(&tmp_image, dst_image, order, swizzled_pixel_type, dithering)
+        }
+        ResizeStage::Middle => {
+            let mut swizzled_image =
+                fr::images::Image::new(src_image.width(), src_image.height(), swizzled_pixel_type);
+            transform_pixels(
+                src_image,
+                &mut swizzled_image,
+                order,
+                swizzled_pixel_type,
+                Dithering::None,
+            )?;
+            if swizzled_pixel_type == dst_pixel_type {
+                return resize_same_type(resizer, &swizzled_image, dst_image, options);
+            }
+            let mut tmp_image =
+                fr::images::Image::new(dst_image.width(), dst_image.height(), swizzled_pixel_type);
+            resize_same_type(resizer, &swizzled_image, &mut tmp_image, options)?;
+            convert_pixels(&tmp_image, dst_image, true, dithering)
+        }
+        ResizeStage::Last => {
+            let mut tmp_image =
+                fr::images::Image::new(src_image.width(), src_image.height(), dst_pixel_type);
+            // Dithering is applied only to the result of resizing
+            transform_pixels(
+                src_image,
+                &mut tmp_image,
+                order,
+                swizzled_pixel_type,
+                Dithering::None,
+            )?;
+            resize_same_type(resizer, &tmp_image, dst_image, options)
+        }
+    }
+}
+
+/// Minimal ratio of sizes of an already resized image and a destination
+/// image that allows to resize the destination image from the already
+/// resized one. Lower ratios noticeably blur the result by double filtering.
+const MIN_CASCADE_SCALE: u32 = 2;
+
+/// Returns `true` if a destination image may be resized from another
+/// destination image resized with the same options without noticeable
+/// loss of quality.
+fn is_cascade_allowed(options: Option<&RustResizeOptions>) -> bool {
+    let Some(o) = options else {
+        return true;
+    };
+    // Positions of pixels of the destination images must be related
+    // to the whole source image in the same way.
+    matches!(o.fir_options.algorithm, fr::ResizeAlg::Convolution(_))
+        && matches!(o.fir_options.cropping, fr::SrcCropping::None)
+        && o.contain.is_none()
+        && o.dst_region.is_none()
+        && o.border_mode.is_none()
+        // Pixels of destination images must be the result of resizing only
+        && o.linear_light.is_none()
+        && o.dithering == Dithering::None
+        && o.channel_order.is_none()
+        && o.selected_channels.is_none()
+        && o.background.is_none()
+}
+
+/// Returns index of the smallest of already resized images from which
+/// the destination image may be resized.
+fn cascade_source(
+    src_pixel_type: fr::PixelType,
+    dst_images: &[&mut fr::images::Image<'static>],
+    resized: &[usize],
+    dst_index: usize,
+) -> Option<usize> {
+    let dst_image = &dst_images[dst_index];
+    resized
+        .iter()
+        .copied()
+        .filter(|&i| {
+            let image = &dst_images[i];
+            // Components of pixels of the image must have the same precision
+            image.pixel_type() == src_pixel_type
+                && image.width() >= dst_image.width() * MIN_CASCADE_SCALE
+                && image.height() >= dst_image.height() * MIN_CASCADE_SCALE
+        })
+        .min_by_key(|&i| dst_images[i].width() as u64 * dst_images[i].height() as u64)
+}
+
+/// Source of pixels of a destination image resized
+/// by `resize_into_many()`.
+#[derive(Debug, Clone, Copy)]
+enum ManySource {
+    /// The source image.
+    Image,
+    /// Another destination image with the given index.
+    Resized(usize),
+    /// An image with the given index which contains pixels of the source
+    /// image after the first pass of the convolution shared with other
+    /// destination images.
+    FirstPass(usize),
+}
+
+/// Returns the size of an image with pixels of the source image after
+/// the first pass of the convolution into the destination image,
+/// if this pass may be shared with other destination images.
+///
+/// The resizer makes the vertical pass first for images with U8
+/// components and the horizontal pass first for other images,
+/// so the first pass is shared by destination images with the same
+/// height or width respectively. Resizing from the result
+/// of the first pass makes only the second pass with the same
+/// coefficients, so the result is the same as resizing from
+/// the source image.
+fn first_pass_size(
+    src_image: &fr::images::Image<'static>,
+    dst_image: &fr::images::Image<'static>,
+    options: Option<&RustResizeOptions>,
+) -> Option<(u32, u32)> {
+    use fr::PixelType as PT;
+
+    let pixel_type = src_image.pixel_type();
+    if dst_image.pixel_type() != pixel_type {
+        return None;
+    }
+    // Pixels must be resized by the convolution only
+    let alpha_processed =
+        options.is_none_or(|o| o.fir_options.mul_div_alpha || o.bleed_alpha.is_some());
+    if options.is_some_and(|o| o.detect_grayscale)
+        || (has_alpha(count_of_channels(pixel_type)) && alpha_processed)
+    {
+        return None;
+    }
+    let (src_width, src_height) = (src_image.width(), src_image.height());
+    let (dst_width, dst_height) = (dst_image.width(), dst_image.height());
+    if matches!(pixel_type, PT::U8 | PT::U8x2 | PT::U8x3 | PT::U8x4) {
+        Some((src_width, dst_height))
+    } else {
+        Some((dst_width, src_height))
+    }
+}
+
+/// Maps items by the function that uses a resizer.
+///
+/// Items are mapped in parallel, each one by a separate resizer with
+/// CPU extensions of the given resizer, if the function is called
+/// inside a thread pool. Otherwise, they are mapped one by one
+/// by the given resizer.
+fn map_with_resizer<T, R, F>(resizer: &mut fr::Resizer, items: Vec<T>, func: F) -> PyResult<Vec<R>>
+where
+    T: Send,
+    R: Send,
+    F: Fn(&mut fr::Resizer, T) -> PyResult<R> + Sync,
+{
+    if rayon::current_num_threads() > 1 && items.len() > 1 {
+        let cpu_extensions = resizer.cpu_extensions();
+        let new_resizer = || {
+            let mut resizer = fr::Resizer::new();
+            // SAFETY: the given resizer uses only extensions supported by the CPU
+            unsafe { resizer.set_cpu_extensions(cpu_extensions) };
+            resizer
+        };
+        return items
+            .into_par_iter()
+            .map_init(new_resizer, |resizer, item| func(resizer, item))
+            .collect();
+    }
+    items.into_iter().map(|item| func(resizer, item)).collect()
+}
+
+/// Resizes the source image into several destination images.
+///
+/// A destination image is resized from the smallest of larger
+/// destination images which is at least `MIN_CASCADE_SCALE` times larger
+/// and has the same type of pixels as the source image, if the options
+/// allow it. Otherwise, it is resized from the source image, and the first
+/// pass of the convolution is shared by destination images with the same
+/// size along the axis of this pass.
+///
+/// Destination images which don't depend on each other are resized
+/// in parallel if the function is called inside a thread pool.
+fn resize_into_many(
+    resizer: &mut fr::Resizer,
+    src_image: &fr::images::Image<'static>,
+    dst_images: &mut [&mut fr::images::Image<'static>],
+    options: Option<&RustResizeOptions>,
+) -> PyResult<Vec<ResizeReport>> {
+    let count = dst_images.len();
+    let mut order: Vec<usize> = (0..count).collect();
+    order.sort_by_key(|&i| {
+        let image = &dst_images[i];
+        std::cmp::Reverse(image.width() as u64 * image.height() as u64)
+    });
+    let cascade_allowed = is_cascade_allowed(options);
+    let mut sources = vec![ManySource::Image; count];
+    // Count of images in the chain of cascading before
+    // the destination image.
+    let mut depths = vec![0; count];
+    let mut first_pass_sizes = vec![None; count];
+    let mut resized = Vec::with_capacity(count);
+    for dst_index in order {
+        let source = cascade_allowed
+            .then(|| cascade_source(src_image.pixel_type(), dst_images, &resized, dst_index))
+            .flatten();
+        if let Some(src_index) = source {
+            sources[dst_index] = ManySource::Resized(src_index);
+            depths[dst_index] = depths[src_index] + 1;
+        } else if cascade_allowed {
+            first_pass_sizes[dst_index] =
+                first_pass_size(src_image, dst_images[dst_index], options);
+        }
+        resized.push(dst_index);
+    }
+    // The first pass is made only if it is shared by several images
+    let mut shared_sizes: Vec<(u32, u32)> = Vec::new();
+    for dst_index in 0..count {
+        let Some(size) = first_pass_sizes[dst_index] else {
+            continue;
+        };
+        if first_pass_sizes
+            .iter()
+            .flatten()
+            .filter(|&&s| s == size)
+            .count()
+            < 2
+        {
+            continue;
+        }
+        let pass_index = match shared_sizes.iter().position(|&s| s == size) {
+            Some(pass_index) => pass_index,
+            None => {
+                shared_sizes.push(size);
+                shared_sizes.len() - 1
+            }
+        };
+        sources[dst_index] = ManySource::FirstPass(pass_index);
+    }
+
+    let first_passes: Vec<fr::images::Image<'static>> =
+        map_with_resizer(resizer, shared_sizes, |resizer, (width, height)| {
+            let mut image = fr::images::Image::new(width, height, src_image.pixel_type());
+            resize_image(resizer, src_image, &mut image, options)?;
+            Ok(image)
+        })?;
+
+    let mut reports = vec![ResizeReport::default(); count];
+    let max_depth = depths.iter().copied().max().unwrap_or_default();
+    for depth in 0..=max_depth {
+        // Images of smaller depths are already resized
+        let mut targets = Vec::new();
+        let mut resized_images = vec![None; count];
+        for (index, image) in dst_images.iter_mut().enumerate() {
+            if depths[index] == depth {
+                targets.push((index, &mut **image));
+            } else {
+                resized_images[index] = Some(&**image);
+            }
+        }
+        let depth_reports = map_with_resizer(resizer, targets, |resizer, (index, dst_image)| {
+            let src_image = match sources[index] {
+                ManySource::Image => src_image,
+                ManySource::Resized(src_index) => resized_images[src_index]
+                    .ok_or_else(|| PyValueError::new_err("Source image is not resized"))?,
+                ManySource::FirstPass(pass_index) => &first_passes[pass_index],
+            };
+            let report = resize_image(resizer, src_image, dst_image, options)?;
+            Ok((index, report))
+        })?;
+        for (index, report) in depth_reports {
+            reports[index] = report;
+        }
+    }
+    Ok(reports)
+}
+
+/// Returns size of the next level of an image pyramid: a half of
+/// the previous level rounded down, but not less than one pixel.
+fn next_level_size(size: (u32, u32)) -> (u32, u32) {
+    ((size.0 / 2).max(1), (size.1 / 2).max(1))
+}
+
+/// Options to build levels of an image pyramid.
+pub(crate) struct PyramidOptions {
+    /// Options to resize every level from the previous one
+    /// by the convolution with the filter.
+    pub(crate) resize: RustResizeOptions,
+    /// Options to transform pixels of every resized level once
+    /// (reorder or select channels, composite onto the background),
+    /// `None` - pixels are not transformed.
+    pub(crate) transform: Option<RustResizeOptions>,
+}
+
+impl PyramidOptions {
+    /// Splits options of resizing into options to resize levels
+    /// of an image pyramid and options to transform their pixels.
+    /// Cropping, padding and the region of the destination image
+    /// are not used.
+    ///
+    /// Levels are resized from untransformed previous levels,
+    /// so transformations are not applied repeatedly.
+    pub(crate) fn new(options: Option<&RustResizeOptions>, filter_type: fr::FilterType) -> Self {
+        let options = options.cloned().unwrap_or_else(RustResizeOptions::new);
+        let resize = RustResizeOptions {
+            fir_options: fr::ResizeOptions::new()
+                .resize_alg(fr::ResizeAlg::Convolution(filter_type))
+                .use_alpha(options.fir_options.mul_div_alpha),
+            dithering: Dithering::None,
+            channel_order: None,
+            selected_channels: None,
+            background: None,
+            contain: None,
+            padding: None,
+            dst_region: None,
+            ..options.clone()
+        };
+        let has_transform = options.channel_order.is_some()
+            || options.selected_channels.is_some()
+            || options.background.is_some();
+        // Transformed levels have the same size as resized levels,
+        // so pixels are only copied by the nearest algorithm.
+        let transform = has_transform.then(|| RustResizeOptions {
+            fir_options: fr::ResizeOptions::new()
+                .resize_alg(fr::ResizeAlg::Nearest)
+                .use_alpha(options.fir_options.mul_div_alpha),
+            linear_light: None,
+            detect_grayscale: false,
+            bleed_alpha: None,
+            contain: None,
+            padding: None,
+            dst_region: None,
+            border_mode: None,
+            ..options
+        });
+        Self { resize, transform }
+    }
+
+    /// Returns the level with transformed pixels or `None`
+    /// if pixels are not transformed.
+    pub(crate) fn transform_level(
+        &self,
+        resizer: &mut fr::Resizer,
+        level: &fr::images::Image,
+    ) -> PyResult<Option<fr::images::Image<'static>>> {
+        let Some(options) = &self.transform else {
+            return Ok(None);
+        };
+        let pixel_type = match &options.channel_order {
+            Some(order) => {
+                pixel_type_with_channels(level.pixel_type(), order.len()).ok_or_else(|| {
+                    PyValueError::new_err(
+                        fr::MappingError::UnsupportedCombinationOfImageTypes.to_string(),
+                    )
+                })?
+            }
+            None => level.pixel_type(),
+        };
+        let mut transformed = fr::images::Image::new(level.width(), level.height(), pixel_type);
+        resize_image(resizer, level, &mut transformed, Some(options))?;
+        Ok(Some(transformed))
+    }
+
+    /// Returns thread pool if it is worth to use it for transforming
+    /// pixels of levels with the given size.
+    pub(crate) fn transform_thread_pool_for(&self, size: (u32, u32)) -> Option<&ResizerThreadPool> {
+        self.transform.as_ref()?.thread_pool_for(size, size)
+    }
+
+    /// Returns values of all components of the color that is used
+    /// to fill pixels outside levels: the background color, if it is set,
+    /// or all components are zero (transparent or black).
+    pub(crate) fn background_color(&self, image: &impl IntoImageView) -> Result<Vec<f64>, String> {
+        let background = self.transform.as_ref().and_then(|o| o.background.as_ref());
+        pixel_color(image, background, "Background")
+    }
+}
+
+/// Builds levels of an image pyramid. Each level is resized from
+/// the previous one until the count of levels is reached or
+/// the larger dimension of the last level is not greater than `min_size`.
+fn build_pyramid_levels(
+    resizer: &mut fr::Resizer,
+    src_image: &fr::images::Image<'static>,
+    levels: Option<u32>,
+    min_size: u32,
+    options: &PyramidOptions,
+) -> PyResult<Vec<fr::images::Image<'static>>> {
+    let mut pyramid: Vec<fr::images::Image<'static>> = Vec::new();
+    // The last resized level if pixels of levels are transformed
+    let mut resized_level: Option<fr::images::Image<'static>> = None;
+    while levels.is_none_or(|levels| pyramid.len() < levels as usize) {
+        let prev_level = match options.transform {
+            Some(_) => resized_level.as_ref(),
+            None => pyramid.last(),
+        }
+        .unwrap_or(src_image);
+        if prev_level.width().max(prev_level.height()) <= min_size {
+            break;
+        }
+        let (width, height) = next_level_size((prev_level.width(), prev_level.height()));
+        let mut level = fr::images::Image::new(width, height, prev_level.pixel_type());
+        resize_image(resizer, prev_level, &mut level, Some(&options.resize))?;
+        match options.transform_level(resizer, &level)? {
+            Some(transformed) => {
+                pyramid.push(transformed);
+                resized_level = Some(level);
+            }
+            None => pyramid.push(level),
+        }
+    }
+    Ok(pyramid)
 }
 
 #[pyclass]
@@ -203,29 +2379,133 @@ impl RustResizer {
         src_image: &Image,
         dst_image: &mut Image,
         options: Option<&RustResizeOptions>,
-    ) -> PyResult<()> {
+    ) -> PyResult<ResizeReport> {
         let resizer_mutex = self.resizer.clone();
         py.detach(move || {
-            let fir_options = options.map(|o| &o.fir_options);
             let src_image_view = src_image.src_image_view();
             let dst_image_view = dst_image.dst_image_view();
             let mut resizer_guard = result2pyresult(resizer_mutex.lock())?;
             let resizer = resizer_guard.deref_mut();
-            if let Some(thread_pool) = options.and_then(|o| o.thread_pool.as_ref()) {
-                if fir_options
-                    // Don't process in thread-pool if resize alg is nearest
-                    .map(|o| o.algorithm != fr::ResizeAlg::Nearest)
-                    .unwrap_or(true)
-                {
-                    return thread_pool.run_within(|| {
-                        result2pyresult(resizer.resize(src_image_view, dst_image_view, fir_options))
-                    });
-                }
+            let src_size = (src_image_view.width(), src_image_view.height());
+            let dst_size = (dst_image_view.width(), dst_image_view.height());
+            if let Some(thread_pool) = options.and_then(|o| o.thread_pool_for(src_size, dst_size)) {
+                return thread_pool
+                    .run_within(|| resize_image(resizer, src_image_view, dst_image_view, options));
+            }
+            resize_image(resizer, src_image_view, dst_image_view, options)
+        })
+    }
+
+    /// Resize the source image into several destination images.
+    ///
+    /// Smaller destination images may be resized from larger ones
+    /// if it doesn't noticeably decrease quality of the result.
+    /// Independent destination images are resized in parallel
+    /// by the thread pool.
+    #[pyo3(signature = (src_image, dst_images, options=None))]
+    fn resize_multi(
+        &self,
+        py: Python,
+        src_image: &Image,
+        mut dst_images: Vec<PyRefMut<Image>>,
+        options: Option<&RustResizeOptions>,
+    ) -> PyResult<Vec<ResizeReport>> {
+        let resizer_mutex = self.resizer.clone();
+        let src_image_view = src_image.src_image_view();
+        let mut dst_image_views: Vec<&mut fr::images::Image<'static>> = dst_images
+            .iter_mut()
+            .map(|image| image.dst_image_view())
+            .collect();
+        py.detach(move || {
+            let mut resizer_guard = result2pyresult(resizer_mutex.lock())?;
+            let resizer = resizer_guard.deref_mut();
+            let src_size = (src_image_view.width(), src_image_view.height());
+            let thread_pool = options.and_then(|o| {
+                dst_image_views
+                    .iter()
+                    .find_map(|image| o.thread_pool_for(src_size, (image.width(), image.height())))
+            });
+            if let Some(thread_pool) = thread_pool {
+                return thread_pool.run_within(|| {
+                    resize_into_many(resizer, src_image_view, &mut dst_image_views, options)
+                });
             }
-            result2pyresult(resizer.resize(src_image_view, dst_image_view, fir_options))
+            resize_into_many(resizer, src_image_view, &mut dst_image_views, options)
         })
     }
 
+    /// Build levels of an image pyramid (mipmaps) from the source image.
+    ///
+    /// Each level is a half of the previous one (rounded down, but not
+    /// less than one pixel) and resized from it by the convolution
+    /// with the filter. The source image is not included.
+    #[pyo3(signature = (src_image, levels=None, filter_type=1, min_size=1, options=None))]
+    fn build_pyramid(
+        &self,
+        py: Python,
+        src_image: &Image,
+        levels: Option<u32>,
+        filter_type: u8,
+        min_size: u32,
+        options: Option<&RustResizeOptions>,
+    ) -> PyResult<Vec<Image>> {
+        if min_size == 0 {
+            return Err(PyValueError::new_err(
+                "Minimal size of levels must be greater than zero",
+            ));
+        }
+        let options = PyramidOptions::new(options, filter_type_from_u8(filter_type));
+        let resizer_mutex = self.resizer.clone();
+        let src_image_view = src_image.src_image_view();
+        let pyramid = py.detach(|| {
+            let mut resizer_guard = result2pyresult(resizer_mutex.lock())?;
+            let resizer = resizer_guard.deref_mut();
+            let src_size = (src_image_view.width(), src_image_view.height());
+            let thread_pool = options
+                .resize
+                .thread_pool_for(src_size, next_level_size(src_size));
+            if let Some(thread_pool) = thread_pool {
+                return thread_pool.run_within(|| {
+                    build_pyramid_levels(resizer, src_image_view, levels, min_size, &options)
+                });
+            }
+            build_pyramid_levels(resizer, src_image_view, levels, min_size, &options)
+        })?;
+        Ok(pyramid.into_iter().map(Image::from).collect())
+    }
+
+    /// Cut an image pyramid built from the source image into tiles
+    /// with the given size and overlap.
+    ///
+    /// Returns an iterator over tuples `(level, col, row, image)`.
+    /// Levels are resized one by one from the previous level
+    /// by the convolution with the filter, each one is a half of
+    /// the previous level rounded up. Pixels of every level
+    /// are transformed by options once.
+    /// Layout of tiles: 0 - Deep Zoom (DZI), 1 - XYZ.
+    #[pyo3(signature = (src_image, tile_size=256, overlap=0, layout=0, filter_type=1, options=None))]
+    fn tiles(
+        &self,
+        src_image: Bound<Image>,
+        tile_size: u32,
+        overlap: u32,
+        layout: u8,
+        filter_type: u8,
+        options: Option<&RustResizeOptions>,
+    ) -> PyResult<RustTiles> {
+        let layout = TileLayout::from_u8(layout)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown layout of tiles: {layout}")))?;
+        let options = PyramidOptions::new(options, filter_type_from_u8(filter_type));
+        RustTiles::new(
+            self.resizer.clone(),
+            src_image,
+            tile_size,
+            overlap,
+            layout,
+            options,
+        )
+    }
+
     /// Resize the source image into a destination image.
     #[pyo3(signature = (src_image, dst_image, options=None))]
     fn resize_pil(
@@ -234,24 +2514,18 @@ impl RustResizer {
         src_image: &PilImageWrapper,
         dst_image: &mut PilImageWrapper,
         options: Option<&RustResizeOptions>,
-    ) -> PyResult<()> {
+    ) -> PyResult<ResizeReport> {
         let resizer_mutex = self.resizer.clone();
         py.detach(move || {
-            let fir_options = options.map(|o| &o.fir_options);
             let mut resizer_guard = result2pyresult(resizer_mutex.lock())?;
             let resizer = resizer_guard.deref_mut();
-            if let Some(thread_pool) = options.and_then(|o| o.thread_pool.as_ref()) {
-                if fir_options
-                    // Don't process in thread-pool if resize alg is nearest
-                    .map(|o| o.algorithm != fr::ResizeAlg::Nearest)
-                    .unwrap_or(true)
-                {
-                    return thread_pool.run_within(|| {
-                        result2pyresult(resizer.resize(src_image, dst_image, fir_options))
-                    });
-                }
+            let src_size = (src_image.width(), src_image.height());
+            let dst_size = (dst_image.width(), dst_image.height());
+            if let Some(thread_pool) = options.and_then(|o| o.thread_pool_for(src_size, dst_size)) {
+                return thread_pool
+                    .run_within(|| resize_image(resizer, src_image, dst_image, options));
             }
-            result2pyresult(resizer.resize(src_image, dst_image, fir_options))
+            resize_image(resizer, src_image, dst_image, options)
         })
     }
 }
@@ -0,0 +1,218 @@
+//! Resizing of images that are received by bands of rows.
+use std::ops::Range;
+
+use fast_image_resize as fr;
+use fast_image_resize::images::Image as FirImage;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::image_view::Image;
+use crate::resizer::{RustResizeOptions, kernel_margin, resize_image};
+use crate::utils::{pixel_type_from_u8, result2pyresult};
+
+/// Resizer of images that are received by bands of rows.
+///
+/// Rows of the destination image are calculated as soon as all rows
+/// of the source image used by the kernel of the resize algorithm
+/// are received. Only these rows of the source image are kept in memory.
+#[pyclass]
+pub struct RustStreamingResizer {
+    resizer: fr::Resizer,
+    options: RustResizeOptions,
+    src_size: (u32, u32),
+    src_pixel_type: fr::PixelType,
+    dst_size: (u32, u32),
+    dst_pixel_type: fr::PixelType,
+    crop_box: fr::CropBox,
+    /// Count of rows around the crop box which are used
+    /// by the resize algorithm.
+    margin: f64,
+    /// Pushed rows of the source image starting from `window_top`.
+    window: Vec<u8>,
+    window_top: u32,
+    /// Count of pushed rows of the source image.
+    src_rows: u32,
+    /// Count of calculated rows of the destination image.
+    dst_rows: u32,
+}
+
+impl RustStreamingResizer {
+    fn row_size(&self) -> usize {
+        self.src_size.0 as usize * self.src_pixel_type.size()
+    }
+
+    /// Returns the range of rows of the source image that are used
+    /// to calculate the given rows of the destination image.
+    fn src_rows_range(&self, dst_rows: Range<u32>) -> Range<u32> {
+        let scale = self.crop_box.height / self.dst_size.1.max(1) as f64;
+        let top = self.crop_box.top + dst_rows.start as f64 * scale - self.margin;
+        let bottom = self.crop_box.top + dst_rows.end as f64 * scale + self.margin;
+        let height = self.src_size.1 as f64;
+        (top.floor().clamp(0., height) as u32)..(bottom.ceil().clamp(0., height) as u32)
+    }
+
+    /// Drops rows of the source image above the given one.
+    /// Rows that are not pushed yet will be skipped.
+    fn drop_rows(&mut self, window_top: u32) {
+        let window_top = window_top.max(self.window_top);
+        let count = window_top
+            .min(self.src_rows)
+            .saturating_sub(self.window_top);
+        let row_size = self.row_size();
+        self.window.drain(..count as usize * row_size);
+        self.window_top = window_top;
+    }
+
+    /// Resizes rows of the destination image that can be calculated
+    /// using the pushed rows of the source image.
+    fn resize_ready_rows(&mut self, py: Python) -> PyResult<Option<Image>> {
+        let (dst_width, dst_height) = self.dst_size;
+        let first_row = self.dst_rows;
+        let mut last_row = first_row;
+        while last_row < dst_height
+            && self.src_rows_range(last_row..last_row + 1).end <= self.src_rows
+        {
+            last_row += 1;
+        }
+        if last_row == first_row {
+            return Ok(None);
+        }
+
+        let band_height = last_row - first_row;
+        let window_height = self.src_rows - self.window_top;
+        let scale = self.crop_box.height / dst_height as f64;
+        let top = self.crop_box.top + first_row as f64 * scale - self.window_top as f64;
+        // The crop box must not extend beyond the window
+        // due to rounding errors.
+        let crop_box = fr::CropBox {
+            top,
+            height: (band_height as f64 * scale).min(window_height as f64 - top),
+            ..self.crop_box
+        };
+        let options = self.options.cropped(crop_box);
+        let src_image = result2pyresult(FirImage::from_slice_u8(
+            self.src_size.0,
+            window_height,
+            &mut self.window,
+            self.src_pixel_type,
+        ))?;
+        let mut dst_image = FirImage::new(dst_width, band_height, self.dst_pixel_type);
+        let resizer = &mut self.resizer;
+        py.detach(|| {
+            let src_size = (src_image.width(), src_image.height());
+            let dst_size = (dst_width, band_height);
+            if let Some(thread_pool) = options.thread_pool_for(src_size, dst_size) {
+                return thread_pool.run_within(|| {
+                    resize_image(resizer, &src_image, &mut dst_image, Some(&options))
+                });
+            }
+            resize_image(resizer, &src_image, &mut dst_image, Some(&options))
+        })?;
+
+        self.dst_rows = last_row;
+        let window_top = if last_row < dst_height {
+            self.src_rows_range(last_row..last_row + 1).start
+        } else {
+            self.src_size.1
+        };
+        self.drop_rows(window_top);
+        Ok(Some(dst_image.into()))
+    }
+}
+
+#[pymethods]
+impl RustStreamingResizer {
+    #[new]
+    #[pyo3(signature = (src_size, src_pixel_type, dst_size, dst_pixel_type, options=None))]
+    fn new(
+        src_size: (u32, u32),
+        src_pixel_type: u8,
+        dst_size: (u32, u32),
+        dst_pixel_type: u8,
+        options: Option<&RustResizeOptions>,
+    ) -> PyResult<Self> {
+        let options = options.cloned().unwrap_or_else(RustResizeOptions::new);
+        if let Some(name) = options.whole_image_option() {
+            return Err(PyValueError::new_err(format!(
+                "{name} can't be used by the streaming resizer"
+            )));
+        }
+        let crop_box = options.src_crop_box(src_size, dst_size);
+        let values = [crop_box.left, crop_box.top, crop_box.width, crop_box.height];
+        if values.iter().any(|v| !v.is_finite() || *v < 0.)
+            || crop_box.left + crop_box.width > src_size.0 as f64
+            || crop_box.top + crop_box.height > src_size.1 as f64
+        {
+            return Err(PyValueError::new_err(
+                "Crop box of the streaming resizer must be inside the source image",
+            ));
+        }
+        let margin = kernel_margin(options.algorithm(), crop_box, dst_size).1;
+        let mut resizer = Self {
+            resizer: fr::Resizer::new(),
+            options,
+            src_size,
+            src_pixel_type: pixel_type_from_u8(src_pixel_type),
+            dst_size,
+            dst_pixel_type: pixel_type_from_u8(dst_pixel_type),
+            crop_box,
+            margin,
+            window: Vec::new(),
+            window_top: 0,
+            src_rows: 0,
+            dst_rows: 0,
+        };
+        resizer.window_top = if dst_size.1 > 0 {
+            resizer.src_rows_range(0..1).start
+        } else {
+            src_size.1
+        };
+        Ok(resizer)
+    }
+
+    /// Returns count of pushed rows of the source image.
+    fn src_rows(&self) -> u32 {
+        self.src_rows
+    }
+
+    /// Returns count of calculated rows of the destination image.
+    fn dst_rows(&self) -> u32 {
+        self.dst_rows
+    }
+
+    /// Pushes the next rows of the source image and returns an image
+    /// with the next rows of the destination image that were
+    /// calculated using them, or `None`.
+    fn push(&mut self, py: Python, rows: &Image) -> PyResult<Option<Image>> {
+        let rows = rows.src_image_view();
+        if rows.width() != self.src_size.0 || rows.pixel_type() != self.src_pixel_type {
+            return Err(PyValueError::new_err(
+                "Rows must have the same width and type of pixels as the source image",
+            ));
+        }
+        let height = rows.height();
+        if height > self.src_size.1 - self.src_rows {
+            return Err(PyValueError::new_err(
+                "Count of pushed rows is greater than height of the source image",
+            ));
+        }
+        // Rows above the window are not used by the resize algorithm
+        let skipped = self.window_top.saturating_sub(self.src_rows).min(height);
+        let row_size = self.row_size();
+        let buffer = &rows.buffer()[skipped as usize * row_size..height as usize * row_size];
+        self.window.extend_from_slice(buffer);
+        self.src_rows += height;
+        self.resize_ready_rows(py)
+    }
+
+    /// Returns an image with the rest of rows of the destination image
+    /// or `None`. All rows of the source image must be pushed.
+    fn finish(&mut self, py: Python) -> PyResult<Option<Image>> {
+        if self.src_rows < self.src_size.1 {
+            return Err(PyValueError::new_err(
+                "Not all rows of the source image were pushed",
+            ));
+        }
+        self.resize_ready_rows(py)
+    }
+}
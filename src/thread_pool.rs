@@ -1,43 +1,570 @@
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, Once, RwLock};
+use std::thread::JoinHandle;
 
+use fast_image_resize::IntoImageView;
 use pyo3::prelude::*;
+use pyo3::types::{PyAny, PyType};
 
+use crate::cpu_affinity::{AffinityStrategy, current_thread_cpus, pin_current_thread};
 use crate::utils::result2pyresult;
 
-#[pyclass(from_py_object)]
+/// Number of `fork()` calls that were made by the current process
+/// and its ancestors. Incremented by the at-fork handler in a child process.
+static FORK_GENERATION: AtomicUsize = AtomicUsize::new(0);
+static REGISTER_AT_FORK: Once = Once::new();
+
+/// Minimal time (in nanoseconds) of processing of an image in the current
+/// thread which is worth to split between worker threads.
+///
+/// Dispatching of jobs into a thread pool with 2-4 threads and waiting
+/// for them takes 4-6 µs (`test_thread_pool_dispatch_time` in
+/// tests/test_benchmark.py), so processing has to take about 50 times
+/// longer to make this overhead negligible.
+const MIN_THREADED_TIME_NS: u64 = 250_000;
+
+/// Time (in picoseconds) of one unit of resize work: 0.3 ns for Lanczos3
+/// and 0.65 ns for bilinear filter and U8x4 pixels
+/// (`test_resize_work_unit_time` in tests/test_benchmark.py).
+const RESIZE_WORK_UNIT_TIME_PS: u64 = 500;
+
+/// Time (in picoseconds) of processing of one pixel by the cheapest
+/// pixel by pixel operations: 0.6 ns to multiply U8x4 pixels by alpha
+/// (`test_pixel_time` in tests/test_benchmark.py).
+const PIXEL_TIME_PS: u64 = 600;
+
+/// Default minimal estimated amount of work required to resize an image
+/// in a thread pool. One unit of work is one access to a source pixel
+/// by the kernel of the resize algorithm (see `estimate_resize_work()`).
+///
+/// Dispatching of work into a thread pool has its own overhead,
+/// so images with less amount of work are resized in the current thread.
+pub(crate) const DEFAULT_THREADING_THRESHOLD: u64 =
+    MIN_THREADED_TIME_NS * 1000 / RESIZE_WORK_UNIT_TIME_PS;
+
+/// Default minimal count of pixels of an image required to process it
+/// pixel by pixel (e.g. to multiply by alpha or to convert pixels)
+/// in a thread pool.
+///
+/// It is not comparable with `DEFAULT_THREADING_THRESHOLD`,
+/// which is measured in accesses to pixels by the resize algorithm.
+pub(crate) const DEFAULT_PIXELS_THREADING_THRESHOLD: u64 =
+    MIN_THREADED_TIME_NS * 1000 / PIXEL_TIME_PS;
+
+/// Returns thread pool if it is worth to use it for processing
+/// the image pixel by pixel.
+///
+/// `threading_threshold` is the minimal count of pixels of the image,
+/// `DEFAULT_PIXELS_THREADING_THRESHOLD` by default.
+pub(crate) fn thread_pool_for_image(
+    thread_pool: Option<ResizerThreadPool>,
+    threading_threshold: Option<u64>,
+    image: &impl IntoImageView,
+) -> Option<ResizerThreadPool> {
+    let threshold = threading_threshold.unwrap_or(DEFAULT_PIXELS_THREADING_THRESHOLD);
+    let work = image.width() as u64 * image.height() as u64;
+    thread_pool.filter(|_| work >= threshold)
+}
+
+/// Registers a handler which marks all existed thread pools as outdated
+/// in a child process created by `fork()`.
+///
+/// Worker threads are not copied into a child process, so rayon's
+/// thread pool inherited from the parent can't execute any job.
+/// Outdated pools are rebuilt lazily by `ResizerThreadPool::run_within()`
+/// because spawning threads inside an at-fork handler isn't safe.
+pub(crate) fn register_at_fork_handler() {
+    REGISTER_AT_FORK.call_once(|| {
+        #[cfg(unix)]
+        unsafe {
+            libc::pthread_atfork(None, None, Some(after_fork_in_child));
+        }
+    });
+}
+
+#[cfg(unix)]
+extern "C" fn after_fork_in_child() {
+    FORK_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ThreadPoolConfig {
+    num_threads: Option<usize>,
+    thread_name_prefix: Option<String>,
+    stack_size: Option<usize>,
+    use_current_thread: bool,
+    cpus: Option<Vec<usize>>,
+    affinity: Option<AffinityStrategy>,
+}
+
+impl ThreadPoolConfig {
+    /// Returns CPUs that worker threads have to be pinned to.
+    fn cpus_to_pin(&self) -> Result<Option<Vec<usize>>, String> {
+        if self.cpus.is_none() && self.affinity.is_none() {
+            return Ok(None);
+        }
+        let allowed_cpus = current_thread_cpus().map_err(|err| err.to_string())?;
+        match &self.cpus {
+            Some(cpus) => {
+                if cpus.is_empty() {
+                    return Err("List of CPUs must not be empty".to_string());
+                }
+                if let Some(cpu) = cpus.iter().find(|cpu| !allowed_cpus.contains(cpu)) {
+                    return Err(format!("CPU {cpu} is not available for the process"));
+                }
+                Ok(Some(cpus.clone()))
+            }
+            None => Ok(Some(allowed_cpus)),
+        }
+    }
+
+    fn build(&self) -> Result<GenerationPool, String> {
+        let cpus_to_pin = self.cpus_to_pin()?;
+        let strategy = self.affinity.unwrap_or(AffinityStrategy::Compact);
+        let affinity: Arc<ThreadsAffinity> = Default::default();
+        let started_affinity = affinity.clone();
+
+        let handles: Arc<Mutex<Vec<JoinHandle<()>>>> = Default::default();
+        let spawned_handles = handles.clone();
+        let mut builder = rayon::ThreadPoolBuilder::new().spawn_handler(move |thread| {
+            let mut thread_builder = std::thread::Builder::new();
+            if let Some(name) = thread.name() {
+                thread_builder = thread_builder.name(name.to_owned());
+            }
+            if let Some(stack_size) = thread.stack_size() {
+                thread_builder = thread_builder.stack_size(stack_size);
+            }
+            let handle = thread_builder.spawn(|| thread.run())?;
+            if let Ok(mut handles) = spawned_handles.lock() {
+                handles.push(handle);
+            }
+            Ok(())
+        });
+        if let Some(num) = self.num_threads {
+            builder = builder.num_threads(num);
+        }
+        if let Some(prefix) = self.thread_name_prefix.clone() {
+            builder = builder.thread_name(move |index| format!("{prefix}-{index}"));
+        }
+        if let Some(stack_size) = self.stack_size {
+            builder = builder.stack_size(stack_size);
+        }
+        // The current thread can't be added into the pool if it
+        // is already a worker of another pool (e.g. the thread that
+        // imported the module is the worker of rayon's global pool).
+        // It is also true for a thread inherited from a pool
+        // of the parent process. A new thread is spawned instead.
+        if self.use_current_thread && rayon::current_thread_index().is_none() {
+            builder = builder.use_current_thread();
+        }
+        builder = builder.start_handler(move |index| {
+            if let Some(cpus) = &cpus_to_pin {
+                let cpu = strategy.select_cpu(cpus, index, rayon::current_num_threads());
+                if let Err(err) = pin_current_thread(cpu) {
+                    started_affinity.add_error(format!(
+                        "Failed to pin worker thread {index} to CPU {cpu}: {err}"
+                    ));
+                }
+            }
+            started_affinity.set(index, current_thread_cpus().ok());
+        });
+        let pool = builder.build().map_err(|err| err.to_string())?;
+        let handles = handles
+            .lock()
+            .map(|mut handles| std::mem::take(&mut *handles))
+            .unwrap_or_default();
+        affinity.wait_for_threads(handles.len());
+        // Worker threads exit after the pool is dropped.
+        if let Some(err) = affinity.first_error() {
+            return Err(err);
+        }
+        Ok(GenerationPool {
+            fork_generation: FORK_GENERATION.load(Ordering::SeqCst),
+            pool: Some(Arc::new(pool)),
+            handles,
+            affinity,
+        })
+    }
+}
+
+/// Effective CPU affinity of worker threads.
+#[derive(Default)]
+struct ThreadsAffinity {
+    cpus: Mutex<BTreeMap<usize, Option<Vec<usize>>>>,
+    started: Condvar,
+    /// Errors of pinning of worker threads to CPUs.
+    errors: Mutex<Vec<String>>,
+}
+
+impl ThreadsAffinity {
+    fn add_error(&self, error: String) {
+        if let Ok(mut errors) = self.errors.lock() {
+            errors.push(error);
+        }
+    }
+
+    fn first_error(&self) -> Option<String> {
+        self.errors
+            .lock()
+            .ok()
+            .and_then(|errors| errors.first().cloned())
+    }
+
+    fn set(&self, thread_index: usize, cpus: Option<Vec<usize>>) {
+        if let Ok(mut threads_cpus) = self.cpus.lock() {
+            threads_cpus.insert(thread_index, cpus);
+            self.started.notify_all();
+        }
+    }
+
+    /// Waits until the given number of worker threads are started.
+    fn wait_for_threads(&self, num_threads: usize) {
+        if let Ok(threads_cpus) = self.cpus.lock() {
+            let _started = self.started.wait_while(threads_cpus, |threads_cpus| {
+                threads_cpus.len() < num_threads
+            });
+        }
+    }
+
+    fn get(&self, thread_index: usize) -> Option<Vec<usize>> {
+        self.cpus
+            .lock()
+            .ok()
+            .and_then(|threads_cpus| threads_cpus.get(&thread_index).cloned().flatten())
+    }
+}
+
+struct GenerationPool {
+    fork_generation: usize,
+    /// `None` if the pool was shut down.
+    pool: Option<Arc<rayon::ThreadPool>>,
+    handles: Vec<JoinHandle<()>>,
+    affinity: Arc<ThreadsAffinity>,
+}
+
+struct ThreadPoolInner {
+    config: ThreadPoolConfig,
+    current: RwLock<GenerationPool>,
+}
+
+impl ThreadPoolInner {
+    /// Returns the pool that can be used in the current process,
+    /// rebuilds it if the process was forked after the pool creation.
+    fn pool(&self) -> Result<Arc<rayon::ThreadPool>, String> {
+        let fork_generation = FORK_GENERATION.load(Ordering::SeqCst);
+        {
+            let current = self.current.read().map_err(|err| err.to_string())?;
+            if current.fork_generation == fork_generation {
+                return current.pool.clone().ok_or_else(shutdown_error);
+            }
+        }
+        let mut current = self.current.write().map_err(|err| err.to_string())?;
+        if current.fork_generation != fork_generation {
+            let is_shutdown = current.pool.is_none();
+            let new_pool = if is_shutdown {
+                GenerationPool {
+                    fork_generation,
+                    pool: None,
+                    handles: Vec::new(),
+                    affinity: Default::default(),
+                }
+            } else {
+                self.config.build()?
+            };
+            let old_pool = std::mem::replace(&mut *current, new_pool);
+            // Worker threads of the old pool don't exist in this process,
+            // so it must not be dropped: its destructor tries to
+            // terminate and notify threads that are gone.
+            std::mem::forget(old_pool);
+        }
+        current.pool.clone().ok_or_else(shutdown_error)
+    }
+
+    /// Terminates the pool and waits until all its worker threads exit.
+    fn shutdown(&self) -> Result<(), String> {
+        let (pool, handles) = {
+            let mut current = self.current.write().map_err(|err| err.to_string())?;
+            if current.fork_generation != FORK_GENERATION.load(Ordering::SeqCst) {
+                // Threads of the pool were created in the parent process.
+                let old_pool = current.pool.take();
+                std::mem::forget(old_pool);
+                std::mem::forget(std::mem::take(&mut current.handles));
+                return Ok(());
+            }
+            (current.pool.take(), std::mem::take(&mut current.handles))
+        };
+        // Worker threads exit after the last reference to the pool
+        // is dropped, i.e. after all running jobs have been finished.
+        drop(pool);
+        for handle in handles {
+            handle
+                .join()
+                .map_err(|_| "Worker thread of the thread pool panicked".to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Returns effective CPU affinity of every worker thread.
+    fn cpu_affinity(&self) -> Result<Vec<Option<Vec<usize>>>, String> {
+        let pool = self.pool()?;
+        let current = self.current.read().map_err(|err| err.to_string())?;
+        Ok((0..pool.current_num_threads())
+            .map(|thread_index| current.affinity.get(thread_index))
+            .collect())
+    }
+
+    fn is_shutdown(&self) -> bool {
+        self.current
+            .read()
+            .map(|current| current.pool.is_none())
+            .unwrap_or(true)
+    }
+}
+
+fn shutdown_error() -> String {
+    "Thread pool is shut down".to_string()
+}
+
+#[pyclass(module = "cykooz_resizer.rust_lib", from_py_object)]
 #[derive(Clone)]
 pub struct ResizerThreadPool {
-    pool: Arc<rayon::ThreadPool>,
+    inner: Arc<ThreadPoolInner>,
 }
 
 #[pymethods]
 impl ResizerThreadPool {
+    /// Creates a new thread pool.
+    ///
+    /// If `use_current_thread` is `true`, the thread that creates the pool
+    /// becomes a worker of the pool. This thread takes part in processing
+    /// only of jobs which it runs itself, and it stays bound to the pool
+    /// even after shutting down, so it can't be used to create another pool
+    /// with `use_current_thread=True`. Any rayon's parallel code called
+    /// by this thread outside of the pool is also processed by workers
+    /// of the pool. Using of another thread pool from this thread blocks it
+    /// without processing jobs of its own pool, so it may hang if these
+    /// jobs are waited for. A dedicated thread should be used to create
+    /// such a pool and to run jobs in it. The thread that imported
+    /// the module is a worker of rayon's global pool, so a new worker
+    /// thread is spawned instead of it.
+    ///
+    /// `cpus` and `affinity` are used to pin worker threads to CPUs
+    /// (only on Linux). Worker threads are distributed among CPUs from
+    /// the `cpus` list or, if it is not specified, among all CPUs available
+    /// for the process. `affinity` is a strategy of distribution:
+    /// - "compact" - worker threads occupy neighbouring CPUs (default);
+    /// - "spread" - worker threads are evenly distributed among CPUs.
+    ///
+    /// CPUs are selected by their indexes in the list, topology of CPUs
+    /// (NUMA nodes, shared caches) isn't taken into account.
+    /// An error is raised if a worker thread can't be pinned to its CPU.
+    /// The current thread used as a worker isn't pinned.
     #[new]
-    #[pyo3(signature = (num_threads = None))]
-    fn new(num_threads: Option<usize>) -> PyResult<Self> {
-        let mut builder = rayon::ThreadPoolBuilder::new();
-        if let Some(num) = num_threads {
-            builder = builder.num_threads(num);
-        }
-        let pool = result2pyresult(builder.build())?;
+    #[pyo3(signature = (
+        num_threads = None,
+        thread_name_prefix = None,
+        stack_size = None,
+        use_current_thread = false,
+        cpus = None,
+        affinity = None,
+    ))]
+    fn new(
+        num_threads: Option<usize>,
+        thread_name_prefix: Option<String>,
+        stack_size: Option<usize>,
+        use_current_thread: bool,
+        cpus: Option<Vec<usize>>,
+        affinity: Option<&str>,
+    ) -> PyResult<Self> {
+        let affinity = result2pyresult(affinity.map(AffinityStrategy::from_name).transpose())?;
+        let config = ThreadPoolConfig {
+            num_threads,
+            thread_name_prefix,
+            stack_size,
+            use_current_thread,
+            cpus,
+            affinity,
+        };
+        let pool = result2pyresult(config.build())?;
         Ok(Self {
-            pool: Arc::new(pool),
+            inner: Arc::new(ThreadPoolInner {
+                config,
+                current: RwLock::new(pool),
+            }),
         })
     }
 
     #[getter]
-    fn current_num_threads(&self) -> usize {
-        self.pool.current_num_threads()
+    fn current_num_threads(&self) -> PyResult<usize> {
+        let pool = result2pyresult(self.inner.pool())?;
+        Ok(pool.current_num_threads())
+    }
+
+    /// The number of threads requested while creating the pool.
+    #[getter]
+    fn num_threads(&self) -> Option<usize> {
+        self.inner.config.num_threads
+    }
+
+    #[getter]
+    fn thread_name_prefix(&self) -> Option<String> {
+        self.inner.config.thread_name_prefix.clone()
+    }
+
+    #[getter]
+    fn stack_size(&self) -> Option<usize> {
+        self.inner.config.stack_size
+    }
+
+    #[getter]
+    fn use_current_thread(&self) -> bool {
+        self.inner.config.use_current_thread
+    }
+
+    #[getter]
+    fn cpus(&self) -> Option<Vec<usize>> {
+        self.inner.config.cpus.clone()
+    }
+
+    #[getter]
+    fn affinity(&self) -> Option<&'static str> {
+        self.inner.config.affinity.map(|strategy| strategy.name())
+    }
+
+    /// Effective CPU affinity of worker threads.
+    ///
+    /// Returns a list with CPUs allowed for every worker thread
+    /// or `None` if affinity of a thread is unknown.
+    #[getter]
+    fn cpu_affinity(&self) -> PyResult<Vec<Option<Vec<usize>>>> {
+        result2pyresult(self.inner.cpu_affinity())
+    }
+
+    #[getter]
+    fn is_shutdown(&self) -> bool {
+        self.inner.is_shutdown()
+    }
+
+    /// shutdown()
+    /// --
+    ///
+    /// Terminates the thread pool and waits until all worker threads exit.
+    /// Jobs that are running at the moment will be finished before.
+    /// The thread pool can't be used after shutting down.
+    fn shutdown(&self, py: Python) -> PyResult<()> {
+        let inner = self.inner.clone();
+        py.detach(move || result2pyresult(inner.shutdown()))
+    }
+
+    /// Thread pool is pickled as its configuration and recreated
+    /// while unpickling.
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> (
+        Bound<'py, PyType>,
+        (
+            Option<usize>,
+            Option<String>,
+            Option<usize>,
+            bool,
+            Option<Vec<usize>>,
+            Option<&'static str>,
+        ),
+    ) {
+        let inner = slf.borrow().inner.clone();
+        let config = &inner.config;
+        (
+            slf.get_type(),
+            (
+                config.num_threads,
+                config.thread_name_prefix.clone(),
+                config.stack_size,
+                config.use_current_thread,
+                config.cpus.clone(),
+                config.affinity.map(|strategy| strategy.name()),
+            ),
+        )
+    }
+
+    /// Copies share the same thread pool.
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    #[pyo3(signature = (_memo))]
+    fn __deepcopy__(&self, _memo: Bound<PyAny>) -> Self {
+        self.clone()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn __hash__(&self) -> u64 {
+        Arc::as_ptr(&self.inner) as u64
+    }
+
+    fn __repr__(&self) -> String {
+        self.repr()
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (_exc_type, _exc_value, _traceback))]
+    fn __exit__(
+        &self,
+        py: Python,
+        _exc_type: Option<Bound<PyAny>>,
+        _exc_value: Option<Bound<PyAny>>,
+        _traceback: Option<Bound<PyAny>>,
+    ) -> PyResult<bool> {
+        self.shutdown(py)?;
+        Ok(false)
+    }
+}
+
+impl PartialEq for ResizerThreadPool {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
     }
 }
 
 impl ResizerThreadPool {
+    pub(crate) fn repr(&self) -> String {
+        let config = &self.inner.config;
+        let mut args = Vec::new();
+        if let Some(num_threads) = config.num_threads {
+            args.push(format!("num_threads={num_threads}"));
+        }
+        if let Some(prefix) = &config.thread_name_prefix {
+            args.push(format!("thread_name_prefix={prefix:?}"));
+        }
+        if let Some(stack_size) = config.stack_size {
+            args.push(format!("stack_size={stack_size}"));
+        }
+        if config.use_current_thread {
+            args.push("use_current_thread=True".to_string());
+        }
+        if let Some(cpus) = &config.cpus {
+            args.push(format!("cpus={cpus:?}"));
+        }
+        if let Some(affinity) = config.affinity {
+            args.push(format!("affinity={:?}", affinity.name()));
+        }
+        format!("ResizerThreadPool({})", args.join(", "))
+    }
+
     #[inline]
-    pub fn run_within<OP, R>(&self, op: OP) -> R
+    pub fn run_within<OP, R>(&self, op: OP) -> PyResult<R>
     where
-        OP: FnOnce() -> R + Send,
+        OP: FnOnce() -> PyResult<R> + Send,
         R: Send,
     {
-        self.pool.install(op)
+        let pool = result2pyresult(self.inner.pool())?;
+        pool.install(op)
     }
 }
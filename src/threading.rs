@@ -0,0 +1,252 @@
+//! Processing of image rows in the current thread pool.
+use std::num::NonZeroU32;
+
+use fast_image_resize::pixels::InnerPixel;
+use fast_image_resize::{ImageView, ImageViewMut};
+use rayon::prelude::*;
+
+/// Returns height of an image and number of parts to split the image
+/// for processing in the current thread pool.
+fn split_parts(height: u32) -> Option<(NonZeroU32, NonZeroU32)> {
+    let num_parts = (rayon::current_num_threads() as u32).min(height);
+    if num_parts > 1 {
+        NonZeroU32::new(height).zip(NonZeroU32::new(num_parts))
+    } else {
+        None
+    }
+}
+
+fn process_row_pairs<S, D, E, F>(
+    src_view: &impl ImageView<Pixel = S>,
+    dst_view: &mut impl ImageViewMut<Pixel = D>,
+    first_row: u32,
+    func: &F,
+) -> Result<(), E>
+where
+    S: InnerPixel,
+    D: InnerPixel,
+    F: Fn(u32, &[S], &mut [D]) -> Result<(), E>,
+{
+    let width = src_view.width() as usize;
+    let rows = src_view.iter_rows(0).zip(dst_view.iter_rows_mut(0));
+    for (y, (src_row, dst_row)) in (first_row..).zip(rows) {
+        func(y, &src_row[..width], &mut dst_row[..width])?;
+    }
+    Ok(())
+}
+
+fn process_rows<P, E, F>(
+    view: &mut impl ImageViewMut<Pixel = P>,
+    first_row: u32,
+    func: &F,
+) -> Result<(), E>
+where
+    P: InnerPixel,
+    F: Fn(u32, &mut [P]) -> Result<(), E>,
+{
+    let width = view.width() as usize;
+    for (y, row) in (first_row..).zip(view.iter_rows_mut(0)) {
+        func(y, &mut row[..width])?;
+    }
+    Ok(())
+}
+
+/// Calls the function for each pair of rows of source and destination
+/// images with the same dimensions.
+///
+/// Rows are processed in parallel if the function is called
+/// inside a thread pool.
+pub(crate) fn try_for_each_row_pair<S, D, E, F>(
+    src_view: &impl ImageView<Pixel = S>,
+    dst_view: &mut impl ImageViewMut<Pixel = D>,
+    func: F,
+) -> Result<(), E>
+where
+    S: InnerPixel,
+    D: InnerPixel,
+    E: Send,
+    F: Fn(&[S], &mut [D]) -> Result<(), E> + Sync,
+{
+    try_for_each_indexed_row_pair(src_view, dst_view, |_, src_row, dst_row| {
+        func(src_row, dst_row)
+    })
+}
+
+/// Calls the function for each pair of rows of source and destination
+/// images with the same dimensions. The function also receives
+/// an index of the row.
+///
+/// Rows are processed in parallel if the function is called
+/// inside a thread pool.
+pub(crate) fn try_for_each_indexed_row_pair<S, D, E, F>(
+    src_view: &impl ImageView<Pixel = S>,
+    dst_view: &mut impl ImageViewMut<Pixel = D>,
+    func: F,
+) -> Result<(), E>
+where
+    S: InnerPixel,
+    D: InnerPixel,
+    E: Send,
+    F: Fn(u32, &[S], &mut [D]) -> Result<(), E> + Sync,
+{
+    if let Some((height, num_parts)) = split_parts(src_view.height())
+        && let Some(src_parts) = src_view.split_by_height(0, height, num_parts)
+        && let Some(dst_parts) = dst_view.split_by_height_mut(0, height, num_parts)
+    {
+        let first_rows: Vec<u32> = src_parts
+            .iter()
+            .scan(0, |first_row, part| {
+                let row = *first_row;
+                *first_row += part.height();
+                Some(row)
+            })
+            .collect();
+        return src_parts
+            .into_par_iter()
+            .zip(dst_parts)
+            .zip(first_rows)
+            .try_for_each(|((src_part, mut dst_part), first_row)| {
+                process_row_pairs(&src_part, &mut dst_part, first_row, &func)
+            });
+    }
+    process_row_pairs(src_view, dst_view, 0, &func)
+}
+
+fn process_split_rows<S, D, E, F>(
+    src_view: &impl ImageView<Pixel = S>,
+    dst_views: &mut [impl ImageViewMut<Pixel = D>],
+    func: &F,
+) -> Result<(), E>
+where
+    S: InnerPixel,
+    D: InnerPixel,
+    F: Fn(&[S], Vec<&mut [D]>) -> Result<(), E>,
+{
+    let width = src_view.width() as usize;
+    let mut dst_rows: Vec<_> = dst_views
+        .iter_mut()
+        .map(|view| view.iter_rows_mut(0))
+        .collect();
+    for src_row in src_view.iter_rows(0) {
+        let rows = dst_rows
+            .iter_mut()
+            .filter_map(Iterator::next)
+            .map(|row| &mut row[..width])
+            .collect();
+        func(&src_row[..width], rows)?;
+    }
+    Ok(())
+}
+
+/// Calls the function for each row of the source image and rows
+/// with the same index of all destination images. All images must
+/// have the same dimensions.
+///
+/// Rows are processed in parallel if the function is called
+/// inside a thread pool.
+pub(crate) fn try_for_each_split_row<S, D, E, F>(
+    src_view: &impl ImageView<Pixel = S>,
+    dst_views: &mut [impl ImageViewMut<Pixel = D>],
+    func: F,
+) -> Result<(), E>
+where
+    S: InnerPixel,
+    D: InnerPixel,
+    E: Send,
+    F: Fn(&[S], Vec<&mut [D]>) -> Result<(), E> + Sync,
+{
+    if let Some((height, num_parts)) = split_parts(src_view.height())
+        && let Some(src_parts) = src_view.split_by_height(0, height, num_parts)
+        && let Some(dst_parts) = dst_views
+            .iter_mut()
+            .map(|view| view.split_by_height_mut(0, height, num_parts))
+            .collect::<Option<Vec<_>>>()
+    {
+        // Parts of all destination images for every part of the source image
+        let mut dst_parts: Vec<_> = dst_parts.into_iter().map(Vec::into_iter).collect();
+        let parts: Vec<_> = src_parts
+            .into_iter()
+            .map(|src_part| {
+                let dst_parts: Vec<_> = dst_parts.iter_mut().filter_map(Iterator::next).collect();
+                (src_part, dst_parts)
+            })
+            .collect();
+        return parts
+            .into_par_iter()
+            .try_for_each(|(src_part, mut dst_parts)| {
+                process_split_rows(&src_part, &mut dst_parts, &func)
+            });
+    }
+    process_split_rows(src_view, dst_views, &func)
+}
+
+/// Calls the function for each row of the image.
+///
+/// Rows are processed in parallel if the function is called
+/// inside a thread pool.
+pub(crate) fn try_for_each_row<P, E, F>(
+    view: &mut impl ImageViewMut<Pixel = P>,
+    func: F,
+) -> Result<(), E>
+where
+    P: InnerPixel,
+    E: Send,
+    F: Fn(&mut [P]) -> Result<(), E> + Sync,
+{
+    try_for_each_indexed_row(view, |_, row| func(row))
+}
+
+/// Calls the function for each row of the image. The function
+/// also receives an index of the row.
+///
+/// Rows are processed in parallel if the function is called
+/// inside a thread pool.
+pub(crate) fn try_for_each_indexed_row<P, E, F>(
+    view: &mut impl ImageViewMut<Pixel = P>,
+    func: F,
+) -> Result<(), E>
+where
+    P: InnerPixel,
+    E: Send,
+    F: Fn(u32, &mut [P]) -> Result<(), E> + Sync,
+{
+    if let Some((height, num_parts)) = split_parts(view.height())
+        && let Some(parts) = view.split_by_height_mut(0, height, num_parts)
+    {
+        let first_rows: Vec<u32> = parts
+            .iter()
+            .scan(0, |first_row, part| {
+                let row = *first_row;
+                *first_row += part.height();
+                Some(row)
+            })
+            .collect();
+        return parts
+            .into_par_iter()
+            .zip(first_rows)
+            .try_for_each(|(mut part, first_row)| process_rows(&mut part, first_row, &func));
+    }
+    process_rows(view, 0, &func)
+}
+
+/// Returns `true` if the predicate returns `true` for all rows
+/// of the image.
+///
+/// Rows are processed in parallel if the function is called
+/// inside a thread pool. Processing stops at the first row
+/// for which the predicate returns `false`.
+pub(crate) fn all_rows<P, F>(view: &impl ImageView<Pixel = P>, predicate: F) -> bool
+where
+    P: InnerPixel,
+    F: Fn(&[P]) -> bool + Sync,
+{
+    let width = view.width() as usize;
+    if let Some((height, num_parts)) = split_parts(view.height())
+        && let Some(parts) = view.split_by_height(0, height, num_parts)
+    {
+        return parts
+            .into_par_iter()
+            .all(|part| part.iter_rows(0).all(|row| predicate(&row[..width])));
+    }
+    view.iter_rows(0).all(|row| predicate(&row[..width]))
+}
@@ -0,0 +1,290 @@
+//! Cutting of image pyramids into tiles.
+use std::ops::DerefMut;
+use std::sync::{Arc, Mutex};
+
+use fast_image_resize as fr;
+use fast_image_resize::images::Image as FirImage;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::channels::fill_pixels;
+use crate::image_view::Image;
+use crate::resizer::{PyramidOptions, resize_image};
+use crate::utils::result2pyresult;
+
+/// Layout of tiles of an image pyramid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TileLayout {
+    /// Deep Zoom (DZI): the level 0 has size 1x1 pixel, tiles on
+    /// the right and bottom edges are smaller than others, neighbouring
+    /// tiles may overlap.
+    DeepZoom,
+    /// XYZ (slippy map): the level 0 fits into one tile, all tiles
+    /// have the same size, pixels outside the level are filled with
+    /// the background color, if it is set, or all their components
+    /// are zero (transparent or black).
+    Xyz,
+}
+
+impl TileLayout {
+    pub(crate) fn from_u8(layout: u8) -> Option<Self> {
+        match layout {
+            0 => Some(Self::DeepZoom),
+            1 => Some(Self::Xyz),
+            _ => None,
+        }
+    }
+}
+
+/// Returns size of the next (lower) level of tiles: a half of
+/// the previous level rounded up.
+fn next_level_size(size: (u32, u32)) -> (u32, u32) {
+    (size.0.div_ceil(2), size.1.div_ceil(2))
+}
+
+/// Returns the number of the level with the full size of the image.
+fn max_level(size: (u32, u32), layout: TileLayout, tile_size: u32) -> u32 {
+    let min_size = match layout {
+        TileLayout::DeepZoom => 1,
+        TileLayout::Xyz => tile_size,
+    };
+    let mut size = size.0.max(size.1);
+    let mut level = 0;
+    while size > min_size {
+        size = size.div_ceil(2);
+        level += 1;
+    }
+    level
+}
+
+/// Copies pixels of the region of the source image with the top-left
+/// corner at (left, top) into the top-left corner of the destination image
+/// with the same type of pixels.
+fn copy_region(
+    src_image: &FirImage,
+    left: u32,
+    top: u32,
+    size: (u32, u32),
+    dst_image: &mut FirImage,
+) {
+    let pixel_size = src_image.pixel_type().size();
+    let src_row_size = src_image.width() as usize * pixel_size;
+    let dst_row_size = dst_image.width() as usize * pixel_size;
+    let region_row_size = size.0 as usize * pixel_size;
+    let src_rows = src_image.buffer()[top as usize * src_row_size..].chunks_exact(src_row_size);
+    let dst_rows = dst_image.buffer_mut().chunks_exact_mut(dst_row_size);
+    for (src_row, dst_row) in src_rows.zip(dst_rows).take(size.1 as usize) {
+        let start = left as usize * pixel_size;
+        dst_row[..region_row_size].copy_from_slice(&src_row[start..start + region_row_size]);
+    }
+}
+
+/// Iterator over tiles of all levels of an image pyramid.
+///
+/// Tiles are yielded as tuples `(level, col, row, image)` from
+/// the level with the full size of the source image to the level 0.
+/// Tiles of the first level are cut from the source image, every next
+/// level is resized from the previous one. Pixels of every level
+/// (including the first one) are transformed once before cutting
+/// of tiles, if options require it. Only the current level
+/// is kept in memory.
+#[pyclass]
+pub struct RustTiles {
+    resizer: Arc<Mutex<fr::Resizer>>,
+    options: PyramidOptions,
+    layout: TileLayout,
+    tile_size: u32,
+    overlap: u32,
+    /// The source image that is used as the first level.
+    src_image: Option<Py<Image>>,
+    /// The current level if it is resized from the previous one.
+    level_image: Option<FirImage<'static>>,
+    /// The current level with transformed pixels.
+    transformed_image: Option<FirImage<'static>>,
+    level_size: (u32, u32),
+    level: u32,
+    /// Position of the next tile in the current level.
+    col: u32,
+    row: u32,
+    finished: bool,
+}
+
+impl RustTiles {
+    pub(crate) fn new(
+        resizer: Arc<Mutex<fr::Resizer>>,
+        src_image: Bound<Image>,
+        tile_size: u32,
+        overlap: u32,
+        layout: TileLayout,
+        options: PyramidOptions,
+    ) -> PyResult<Self> {
+        if tile_size == 0 {
+            return Err(PyValueError::new_err(
+                "Size of tiles must be greater than zero",
+            ));
+        }
+        if layout == TileLayout::Xyz && overlap > 0 {
+            return Err(PyValueError::new_err(
+                "Overlap of tiles can't be used with XYZ layout",
+            ));
+        }
+        let src_size = {
+            let src_image = src_image.borrow();
+            let view = src_image.src_image_view();
+            (view.width(), view.height())
+        };
+        Ok(Self {
+            resizer,
+            options,
+            layout,
+            tile_size,
+            overlap,
+            src_image: Some(src_image.unbind()),
+            level_image: None,
+            transformed_image: None,
+            level_size: src_size,
+            level: max_level(src_size, layout, tile_size),
+            col: 0,
+            row: 0,
+            finished: src_size.0 == 0 || src_size.1 == 0,
+        })
+    }
+
+    /// Returns count of columns and rows of tiles in the current level.
+    fn grid_size(&self) -> (u32, u32) {
+        (
+            self.level_size.0.div_ceil(self.tile_size),
+            self.level_size.1.div_ceil(self.tile_size),
+        )
+    }
+
+    /// Returns the range of pixels of the current level along one axis
+    /// that is covered by the tile with the given index.
+    fn tile_range(&self, index: u32, level_size: u32) -> (u32, u32) {
+        let start = index * self.tile_size;
+        let end = (start + self.tile_size).min(level_size);
+        if self.overlap == 0 {
+            return (start, end);
+        }
+        (
+            start.saturating_sub(self.overlap),
+            (end + self.overlap).min(level_size),
+        )
+    }
+
+    fn cut_tile(&self, level_image: &FirImage) -> PyResult<Image> {
+        let (left, right) = self.tile_range(self.col, self.level_size.0);
+        let (top, bottom) = self.tile_range(self.row, self.level_size.1);
+        let region_size = (right - left, bottom - top);
+        let tile_size = match self.layout {
+            TileLayout::DeepZoom => region_size,
+            TileLayout::Xyz => (self.tile_size, self.tile_size),
+        };
+        let mut tile = FirImage::new(tile_size.0, tile_size.1, level_image.pixel_type());
+        if tile_size != region_size {
+            let color = result2pyresult(self.options.background_color(&tile))?;
+            result2pyresult(fill_pixels(&mut tile, &color))?;
+        }
+        copy_region(level_image, left, top, region_size, &mut tile);
+        Ok(tile.into())
+    }
+
+    /// Transforms pixels of the current level, if options require it.
+    fn transform_level(&mut self, py: Python) -> PyResult<()> {
+        let src_image = self.src_image.as_ref().map(|image| image.bind(py).borrow());
+        let level = match (&src_image, &self.level_image) {
+            (Some(src_image), _) => src_image.src_image_view(),
+            (None, Some(level_image)) => level_image,
+            (None, None) => return Ok(()),
+        };
+        let resizer_mutex = self.resizer.clone();
+        let options = &self.options;
+        let transformed_image = py.detach(|| {
+            let mut resizer_guard = result2pyresult(resizer_mutex.lock())?;
+            let resizer = resizer_guard.deref_mut();
+            let size = (level.width(), level.height());
+            if let Some(thread_pool) = options.transform_thread_pool_for(size) {
+                return thread_pool.run_within(|| options.transform_level(resizer, level));
+            }
+            options.transform_level(resizer, level)
+        })?;
+        drop(src_image);
+        self.transformed_image = transformed_image;
+        Ok(())
+    }
+
+    /// Resizes the next (lower) level from the current one.
+    fn resize_next_level(&mut self, py: Python) -> PyResult<()> {
+        let (width, height) = next_level_size(self.level_size);
+        let src_image = self.src_image.take();
+        let src_image = src_image.as_ref().map(|image| image.bind(py).borrow());
+        let prev_level = match (&src_image, &self.level_image) {
+            (Some(src_image), _) => src_image.src_image_view(),
+            (None, Some(level_image)) => level_image,
+            (None, None) => return Ok(()),
+        };
+        let mut level = FirImage::new(width, height, prev_level.pixel_type());
+        let resizer_mutex = self.resizer.clone();
+        let options = &self.options.resize;
+        py.detach(|| {
+            let mut resizer_guard = result2pyresult(resizer_mutex.lock())?;
+            let resizer = resizer_guard.deref_mut();
+            let src_size = (prev_level.width(), prev_level.height());
+            if let Some(thread_pool) = options.thread_pool_for(src_size, (width, height)) {
+                return thread_pool
+                    .run_within(|| resize_image(resizer, prev_level, &mut level, Some(options)));
+            }
+            resize_image(resizer, prev_level, &mut level, Some(options))
+        })?;
+        self.level_image = Some(level);
+        self.transformed_image = None;
+        self.level_size = (width, height);
+        self.level -= 1;
+        self.col = 0;
+        self.row = 0;
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl RustTiles {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<(u32, u32, u32, Image)>> {
+        while !self.finished {
+            let (cols, rows) = self.grid_size();
+            if self.row >= rows {
+                if self.level == 0 {
+                    self.finished = true;
+                    self.src_image = None;
+                    self.level_image = None;
+                    self.transformed_image = None;
+                } else {
+                    self.resize_next_level(py)?;
+                }
+                continue;
+            }
+            if self.options.transform.is_some() && self.transformed_image.is_none() {
+                self.transform_level(py)?;
+            }
+            let tile = match (&self.transformed_image, &self.src_image, &self.level_image) {
+                (Some(transformed_image), _, _) => self.cut_tile(transformed_image)?,
+                (None, Some(src_image), _) => {
+                    self.cut_tile(src_image.bind(py).borrow().src_image_view())?
+                }
+                (None, None, Some(level_image)) => self.cut_tile(level_image)?,
+                (None, None, None) => break,
+            };
+            let position = (self.level, self.col, self.row);
+            self.col += 1;
+            if self.col >= cols {
+                self.col = 0;
+                self.row += 1;
+            }
+            return Ok(Some((position.0, position.1, position.2, tile)));
+        }
+        Ok(None)
+    }
+}
@@ -29,6 +29,36 @@ pub(crate) fn pixel_type_from_u8(pixel_type: u8) -> PixelType {
     }
 }
 
+/// Returns the type of pixels with the given number or an error
+/// if the number is unknown.
+pub(crate) fn checked_pixel_type_from_u8(pixel_type: u8) -> PyResult<PixelType> {
+    if !(1..=13).contains(&pixel_type) {
+        return Err(exceptions::PyValueError::new_err(format!(
+            "Unknown type of pixels: {pixel_type}"
+        )));
+    }
+    Ok(pixel_type_from_u8(pixel_type))
+}
+
+pub(crate) fn pixel_type_to_u8(pixel_type: PixelType) -> u8 {
+    match pixel_type {
+        PixelType::U8 => 1,
+        PixelType::U8x2 => 2,
+        PixelType::U8x3 => 3,
+        PixelType::U8x4 => 4,
+        PixelType::U16 => 5,
+        PixelType::U16x2 => 6,
+        PixelType::U16x3 => 7,
+        PixelType::U16x4 => 8,
+        PixelType::I32 => 9,
+        PixelType::F32 => 10,
+        PixelType::F32x2 => 11,
+        PixelType::F32x3 => 12,
+        PixelType::F32x4 => 13,
+        _ => 0,
+    }
+}
+
 pub(crate) fn cpu_extensions_from_u8(extensions: u8) -> fr::CpuExtensions {
     match extensions {
         1 => fr::CpuExtensions::None,
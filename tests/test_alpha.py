@@ -7,7 +7,7 @@ from pathlib import Path
 import pytest
 from PIL import Image
 
-from cykooz_resizer import AlphaMulDiv, CpuExtensions
+from cykooz_resizer import AlphaMulDiv, CpuExtensions, ImageData, PixelType
 
 from utils import Checksum, get_image_checksum, save_result
 
@@ -133,3 +133,33 @@ def test_divide_alpha_pil(
         Path('alpha_mul') / 'pil' / dir_name,
         f'nasa-multiply-{cpu_extensions.name}.png',
     )
+
+
+@pytest.mark.parametrize('pixel_type', [PixelType.U8x4, PixelType.U16x2, PixelType.F32x4])
+def test_mul_div_alpha_of_opaque_image(thread_pool, pixel_type: PixelType):
+    colors = ImageData(64, 64, PixelType.U8, bytes(range(256)) * 16)
+    alpha = ImageData(64, 64, PixelType.U8, bytes([255]) * 64 * 64)
+    channels = [colors] * (3 if pixel_type is not PixelType.U16x2 else 1)
+    image = ImageData.merge(channels + [alpha], pixel_type)
+    assert image.is_opaque()
+    pixels = image.get_buffer()
+    mul_div = AlphaMulDiv()
+
+    # Pixels of opaque image are copied without changes
+    for method in (mul_div.multiply_alpha, mul_div.divide_alpha):
+        dst_image = ImageData(64, 64, pixel_type)
+        assert method(image, dst_image, thread_pool, threading_threshold=0)
+        assert dst_image.get_buffer() == pixels
+    assert mul_div.multiply_alpha_inplace(image, thread_pool)
+    assert mul_div.divide_alpha_inplace(image, thread_pool)
+    assert image.get_buffer() == pixels
+
+    # Not opaque image
+    alpha = ImageData(64, 64, PixelType.U8, bytes([255]) * (64 * 64 - 1) + bytes([128]))
+    image = ImageData.merge(channels + [alpha], pixel_type)
+    assert not image.is_opaque()
+    dst_image = ImageData(64, 64, pixel_type)
+    assert not mul_div.multiply_alpha(image, dst_image, thread_pool)
+    assert dst_image.get_buffer() != image.get_buffer()
+    assert not mul_div.multiply_alpha_inplace(image, thread_pool)
+    assert image.get_buffer() == dst_image.get_buffer()
@@ -2,7 +2,10 @@
 :Authors: cykooz
 :Date: 12.08.2021
 """
+import math
+import time
 from pathlib import Path
+from typing import Optional
 
 import pytest
 from PIL import Image
@@ -11,6 +14,8 @@ from pytest_benchmark.stats import Metadata
 
 from cykooz_resizer import (
     Algorithm,
+    AlphaMulDiv,
+    BorderMode,
     CpuExtensions,
     FilterType,
     ImageData,
@@ -18,6 +23,7 @@ from cykooz_resizer import (
     ResizeAlg,
     ResizeOptions,
     Resizer,
+    ResizerThreadPool,
 )
 from cykooz_resizer.alpha import set_image_mode
 from utils import BenchResults
@@ -290,3 +296,102 @@ def test_resize_pil_u8(
         resize_options,
         benchmark.stats,
     )
+
+
+# Costs that are used to derive default thresholds of threading
+# (see src/thread_pool.rs)
+
+
+THRESHOLD_IMAGE_SIZE = 2048
+
+
+def _resize_work(src_size, dst_size, support: float) -> int:
+    """Returns amount of work of resizing by the convolution
+    (see `estimate_resize_work()` in src/resizer.rs)."""
+    scale_x = max(src_size[0] / dst_size[0], 1)
+    scale_y = max(src_size[1] / dst_size[1], 1)
+    kernel_width = math.ceil(2 * support * scale_x)
+    kernel_height = math.ceil(2 * support * scale_y)
+    return (
+        src_size[1] * dst_size[0] * kernel_width
+        + dst_size[1] * dst_size[0] * kernel_height
+    )
+
+
+@pytest.mark.skip('Only manual running')
+@pytest.mark.parametrize(
+    ('filter_type', 'support'),
+    [(FilterType.bilinear, 1), (FilterType.lanczos3, 3)],
+)
+def test_resize_work_unit_time(benchmark, filter_type, support):
+    size = THRESHOLD_IMAGE_SIZE
+    src_image = ImageData(size, size, PixelType.U8x4, bytes(range(256)) * (size * size // 64))
+    dst_image = ImageData(size // 2, size // 2, PixelType.U8x4)
+    options = ResizeOptions(ResizeAlg.convolution(filter_type))
+    benchmark.pedantic(
+        Resizer().resize,
+        args=(src_image, dst_image, options),
+        rounds=20,
+        warmup_rounds=3,
+    )
+    work = _resize_work((size, size), (size // 2, size // 2), support)
+    print(f'Time of one unit of resize work: {benchmark.stats.stats.min / work * 1e9:.3f} ns')
+
+
+@pytest.mark.skip('Only manual running')
+def test_pixel_time(benchmark):
+    size = THRESHOLD_IMAGE_SIZE
+    src_image = ImageData(size, size, PixelType.U8x4, bytes(range(256)) * (size * size // 64))
+    dst_image = ImageData(size, size, PixelType.U8x4)
+    benchmark.pedantic(
+        AlphaMulDiv().multiply_alpha,
+        args=(src_image, dst_image),
+        rounds=20,
+        warmup_rounds=3,
+    )
+    print(f'Time of multiplying of one pixel by alpha: '
+          f'{benchmark.stats.stats.min / (size * size) * 1e9:.3f} ns')
+
+
+@pytest.mark.skip('Only manual running')
+@pytest.mark.parametrize('num_threads', [2, 4])
+def test_thread_pool_dispatch_time(num_threads):
+    src_image = ImageData(64, 64, PixelType.U8x4, bytes(range(256)) * 64)
+    dst_image = ImageData(64, 64, PixelType.U8x4)
+    alpha_mul_div = AlphaMulDiv()
+
+    def run(thread_pool: Optional[ResizerThreadPool]) -> float:
+        times = []
+        for _ in range(5):
+            start = time.perf_counter()
+            for _ in range(1000):
+                alpha_mul_div.multiply_alpha(src_image, dst_image, thread_pool, 0)
+            times.append((time.perf_counter() - start) / 1000)
+        return min(times)
+
+    with ResizerThreadPool(num_threads=num_threads) as thread_pool:
+        dispatch_time = run(thread_pool) - run(None)
+    print(f'Time of dispatching of jobs into thread pool: {dispatch_time * 1e6:.2f} us')
+
+
+# Overhead of border modes: all pixels used by the resize algorithm
+# are copied into an extended image (see `resize_beyond_edges()`
+# in src/resizer.rs).
+
+
+@pytest.mark.skip('Only manual running')
+@pytest.mark.parametrize('border_mode', [BorderMode.normalize, BorderMode.mirror])
+@pytest.mark.parametrize('dst_size', [1024, 256])
+def test_border_mode_time(benchmark, border_mode, dst_size):
+    size = THRESHOLD_IMAGE_SIZE
+    src_image = ImageData(size, size, PixelType.U8x4, bytes(range(256)) * (size * size // 64))
+    dst_image = ImageData(dst_size, dst_size, PixelType.U8x4)
+    options = ResizeOptions(ResizeAlg.convolution(FilterType.lanczos3), border_mode=border_mode)
+    benchmark.pedantic(
+        Resizer().resize,
+        args=(src_image, dst_image, options),
+        rounds=20,
+        warmup_rounds=3,
+    )
+    print(f'Time of resizing with {border_mode.name} border mode '
+          f'into {dst_size}x{dst_size}: {benchmark.stats.stats.min * 1e3:.2f} ms')
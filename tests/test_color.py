@@ -0,0 +1,152 @@
+"""
+:Authors: cykooz
+:Date: 18.10.2026
+"""
+import struct
+
+import pytest
+from PIL import Image
+
+from cykooz_resizer import (
+    ColorMapper,
+    ImageData,
+    PixelType,
+    ResizerThreadPool,
+    TransferFunction,
+)
+
+
+WIDTH = 256
+HEIGHT = 64
+# All possible values of color components and different values of alpha.
+SRC_PIXELS = b''.join(bytes((v, 255 - v, v // 2, v)) for v in range(256)) * HEIGHT
+
+
+def _srgb_to_linear(value: float) -> float:
+    if value < 0.04045:
+        return value / 12.92
+    return ((value + 0.055) / 1.055) ** 2.4
+
+
+def _floats(image: ImageData):
+    buffer = image.get_buffer()
+    return struct.unpack(f'{len(buffer) // 4}f', buffer)
+
+
+def test_color_mapper_u8_into_f32():
+    mapper = ColorMapper()
+    assert mapper.transfer_function is TransferFunction.srgb
+    src_image = ImageData(WIDTH, HEIGHT, PixelType.U8x4, SRC_PIXELS)
+    dst_image = ImageData(WIDTH, HEIGHT, PixelType.F32x4)
+    mapper.forward_map(src_image, dst_image)
+
+    values = _floats(dst_image)
+    for v in range(256):
+        r, g, b, a = values[v * 4:v * 4 + 4]
+        assert r == pytest.approx(_srgb_to_linear(v / 255), abs=1e-6)
+        assert g == pytest.approx(_srgb_to_linear((255 - v) / 255), abs=1e-6)
+        assert b == pytest.approx(_srgb_to_linear((v // 2) / 255), abs=1e-6)
+        # Alpha channel is not mapped
+        assert a == pytest.approx(v / 255, abs=1e-6)
+
+    # Back into the source colorspace
+    restored_image = ImageData(WIDTH, HEIGHT, PixelType.U8x4)
+    mapper.backward_map(dst_image, restored_image)
+    assert restored_image.get_buffer() == SRC_PIXELS
+
+
+@pytest.mark.parametrize(
+    ('transfer_function', 'linear_pixel_type'),
+    [
+        (TransferFunction.srgb, PixelType.U16x4),
+        (TransferFunction.srgb, PixelType.F32x4),
+        # Dark values of gamma 2.2 don't fit into U16 in linear colorspace
+        (TransferFunction.gamma_22, PixelType.F32x4),
+    ],
+)
+def test_color_mapper_round_trip(transfer_function, linear_pixel_type):
+    mapper = ColorMapper(transfer_function)
+    src_image = ImageData(WIDTH, HEIGHT, PixelType.U8x4, SRC_PIXELS)
+    linear_image = ImageData(WIDTH, HEIGHT, linear_pixel_type)
+    mapper.forward_map(src_image, linear_image)
+    dst_image = ImageData(WIDTH, HEIGHT, PixelType.U8x4)
+    mapper.backward_map(linear_image, dst_image)
+    assert dst_image.get_buffer() == SRC_PIXELS
+
+
+def test_color_mapper_inplace():
+    mapper = ColorMapper(TransferFunction.gamma_22)
+    image = ImageData(WIDTH, HEIGHT, PixelType.U8x4, SRC_PIXELS)
+    expected_image = ImageData(WIDTH, HEIGHT, PixelType.U8x4)
+    mapper.forward_map(image, expected_image)
+    mapper.forward_map_inplace(image)
+    assert image.get_buffer() == expected_image.get_buffer()
+    # Alpha channel is not changed
+    assert image.get_buffer()[3::4] == SRC_PIXELS[3::4]
+
+    linear_pixels = SRC_PIXELS[0::4]
+    image = ImageData(WIDTH, HEIGHT, PixelType.F32, struct.pack(
+        f'{len(linear_pixels)}f', *(v / 255 for v in linear_pixels),
+    ))
+    mapper.backward_map_inplace(image)
+    for v, value in zip(linear_pixels, _floats(image)):
+        assert value == pytest.approx((v / 255) ** (1 / 2.2), abs=1e-6)
+
+
+def test_color_mapper_thread_pool():
+    mapper = ColorMapper()
+    src_image = ImageData(WIDTH, HEIGHT, PixelType.U8x4, SRC_PIXELS)
+    expected_image = ImageData(WIDTH, HEIGHT, PixelType.U16x4)
+    mapper.forward_map(src_image, expected_image)
+
+    with ResizerThreadPool(num_threads=3) as thread_pool:
+        dst_image = ImageData(WIDTH, HEIGHT, PixelType.U16x4)
+        mapper.forward_map(src_image, dst_image, thread_pool, threading_threshold=0)
+        assert dst_image.get_buffer() == expected_image.get_buffer()
+
+        image = ImageData(WIDTH, HEIGHT, PixelType.U8x4, SRC_PIXELS)
+        mapper.forward_map_inplace(image, thread_pool, threading_threshold=0)
+        mapper.backward_map_inplace(image, thread_pool, threading_threshold=0)
+        assert image.get_buffer() != SRC_PIXELS  # Precision of U8 is lost
+
+    # The shut-down pool isn't used for small images
+    mapper.forward_map(src_image, dst_image, thread_pool)
+    with pytest.raises(RuntimeError, match='Thread pool is shut down'):
+        mapper.forward_map(src_image, dst_image, thread_pool, threading_threshold=0)
+
+
+def test_color_mapper_errors():
+    mapper = ColorMapper()
+    src_image = ImageData(WIDTH, HEIGHT, PixelType.U8x4, SRC_PIXELS)
+    with pytest.raises(RuntimeError, match='dimensions'):
+        mapper.forward_map(src_image, ImageData(WIDTH, HEIGHT + 1, PixelType.U8x4))
+    with pytest.raises(RuntimeError, match='Unsupported combination'):
+        mapper.forward_map(src_image, ImageData(WIDTH, HEIGHT, PixelType.U16x3))
+    with pytest.raises(RuntimeError, match='Unsupported combination'):
+        mapper.forward_map_inplace(ImageData(WIDTH, HEIGHT, PixelType.I32))
+
+
+def test_color_mapper_pil():
+    mapper = ColorMapper()
+    src_image = Image.frombytes('RGBA', (WIDTH, HEIGHT), SRC_PIXELS)
+    dst_image = Image.new('RGBA', (WIDTH, HEIGHT))
+    mapper.forward_map_pil(src_image, dst_image)
+
+    expected_image = ImageData(WIDTH, HEIGHT, PixelType.U8x4)
+    mapper.forward_map(
+        ImageData(WIDTH, HEIGHT, PixelType.U8x4, SRC_PIXELS),
+        expected_image,
+    )
+    assert dst_image.tobytes() == expected_image.get_buffer()
+
+    mapper.backward_map_pil_inplace(dst_image)
+    mapper.forward_map_pil_inplace(dst_image)
+    assert dst_image.mode == 'RGBA'
+
+    gray_image = src_image.getchannel(0)
+    float_image = Image.new('F', gray_image.size)
+    mapper.forward_map_pil(gray_image, float_image)
+    assert float_image.getpixel((128, 0)) == pytest.approx(_srgb_to_linear(128 / 255), abs=1e-6)
+
+    with pytest.raises(ValueError, match='unsupported mode'):
+        mapper.forward_map_pil_inplace(Image.new('CMYK', (16, 16)))
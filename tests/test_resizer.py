@@ -2,22 +2,34 @@
 :Authors: cykooz
 :Date: 21.03.2021
 """
+from fractions import Fraction
 from pathlib import Path
-from typing import Optional, Tuple
+from typing import List, Optional, Tuple
 
 import pytest
 from PIL import Image
 
 from cykooz_resizer import (
+    Algorithm,
+    BorderMode,
     CpuExtensions,
+    CropBox,
+    Dithering,
+    EdgeMode,
     FilterType,
     ImageData,
     PixelType,
     ResizeAlg,
     ResizeOptions,
+    ResizePlan,
     Resizer,
     ResizerThreadPool,
+    StreamingResizer,
+    TileLayout,
+    TransferFunction,
 )
+from cykooz_resizer.rust_lib import RustResizePlan
+from cykooz_resizer.structs import PIXEL_SIZE
 from utils import Checksum, get_image_checksum, save_result
 
 
@@ -197,3 +209,1583 @@ def test_image_modes(source_image: Image.Image, thread_pool, src_mode, dst_mode)
         Path('resize') / 'modes',
         f'nasa-{src_mode}_into_{dst_mode}.png',
     )
+
+
+def _stripes(width: int, height: int, pixel: bytes) -> bytes:
+    """Returns pixels of an image with vertical stripes of "pixel" and zeros."""
+    zero = bytes(len(pixel))
+    return (pixel + zero) * (width // 2) * height
+
+
+@pytest.mark.parametrize(
+    ('linear_light', 'expected'),
+    [
+        (False, 128),
+        (TransferFunction.srgb, 188),
+        (TransferFunction.gamma_22, 186),
+    ],
+)
+def test_resize_in_linear_light(thread_pool, linear_light, expected):
+    src_image = ImageData(64, 64, PixelType.U8, _stripes(64, 64, b'\xff'))
+    dst_image = ImageData(32, 32, PixelType.U8)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.box),
+        thread_pool=thread_pool,
+        linear_light=linear_light,
+    )
+    assert options.linear_light == (linear_light or None)
+    Resizer().resize(src_image, dst_image, options)
+    assert set(dst_image.get_buffer()) == {expected}
+
+    # U16 image
+    src_image = ImageData(64, 64, PixelType.U16, _stripes(64, 64, b'\xff\xff'))
+    dst_image = ImageData(32, 32, PixelType.U16)
+    Resizer().resize(src_image, dst_image, options)
+    buffer = dst_image.get_buffer()
+    values = {int.from_bytes(buffer[i:i + 2], 'little') for i in range(0, len(buffer), 2)}
+    assert len(values) == 1
+    assert abs(values.pop() - (expected * 257)) < 257
+
+
+def test_resize_in_linear_light_keeps_dark_tones(thread_pool):
+    # U16 images are resized through an intermediate image with F32 pixels,
+    # so dark tones are not quantized in linear colorspace.
+    pixels = b''.join(v.to_bytes(2, 'little') for v in range(256))
+    src_image = ImageData(256, 4, PixelType.U16, pixels * 4)
+    dst_image = ImageData(256, 2, PixelType.U16)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.box),
+        thread_pool=thread_pool,
+        linear_light=True,
+    )
+    Resizer().resize(src_image, dst_image, options)
+    assert dst_image.get_buffer() == pixels * 2
+
+
+def test_resize_in_linear_light_with_alpha(thread_pool):
+    # Transparent pixels must not affect colors of the result
+    src_image = ImageData(
+        64, 64, PixelType.U8x4, _stripes(64, 64, b'\xff\x80\x10\xff'),
+    )
+    dst_image = ImageData(32, 32, PixelType.U8x4)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.box),
+        thread_pool=thread_pool,
+        linear_light=True,
+    )
+    assert options.linear_light is TransferFunction.srgb
+    Resizer().resize(src_image, dst_image, options)
+    assert set(zip(*[iter(dst_image.get_buffer())] * 4)) == {(255, 128, 16, 128)}
+
+    # Without alpha transparent pixels are black
+    options.use_alpha = False
+    Resizer().resize(src_image, dst_image, options)
+    assert set(zip(*[iter(dst_image.get_buffer())] * 4)) == {(188, 92, 9, 128)}
+
+
+def test_resize_in_linear_light_errors():
+    options = ResizeOptions(linear_light=True)
+    src_image = ImageData(64, 64, PixelType.F32)
+    dst_image = ImageData(32, 32, PixelType.F32)
+    with pytest.raises(RuntimeError, match='not supported for images with F32 pixels'):
+        Resizer().resize(src_image, dst_image, options)
+
+    src_image = ImageData(64, 64, PixelType.U8)
+    dst_image = ImageData(32, 32, PixelType.U8x2)
+    with pytest.raises(RuntimeError, match='Pixel type of source image does not match'):
+        Resizer().resize(src_image, dst_image, options)
+
+    options.linear_light = None
+    assert options.linear_light is None
+    assert options != ResizeOptions(linear_light=True)
+    with pytest.raises(ValueError, match='Unknown transfer function: 10'):
+        options.rust_options.set_linear_light(10)
+
+
+@pytest.mark.parametrize(
+    ('u8_pixel_type', 'src_pixel_type', 'dst_pixel_type'),
+    [
+        (PixelType.U8x4, PixelType.U16x4, PixelType.U8x4),
+        (PixelType.U8x4, PixelType.U8x4, PixelType.U16x4),
+        (PixelType.U8x3, PixelType.U8x3, PixelType.F32x3),
+        (PixelType.U8x2, PixelType.F32x2, PixelType.U16x2),
+        (PixelType.U8, PixelType.I32, PixelType.U8),
+    ],
+)
+@pytest.mark.parametrize('dst_size', [(16, 24), (100, 80)])
+def test_resize_into_other_pixel_type(
+        thread_pool,
+        u8_pixel_type,
+        src_pixel_type,
+        dst_pixel_type,
+        dst_size,
+):
+    pixel_size = PIXEL_SIZE[u8_pixel_type]
+    src_image = ImageData(
+        64, 64, u8_pixel_type, bytes(range(256)) * (16 * pixel_size),
+    ).convert(src_pixel_type)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.bilinear),
+        use_alpha=False,
+        thread_pool=thread_pool,
+        threading_threshold=0,
+    )
+    resizer = Resizer()
+    dst_image = ImageData(*dst_size, dst_pixel_type)
+    resizer.resize(src_image, dst_image, options)
+
+    # Result must be near the same as a result of resizing
+    # of converted source image.
+    expected_image = ImageData(*dst_size, dst_pixel_type)
+    resizer.resize(src_image.convert(dst_pixel_type), expected_image, options)
+    result = dst_image.convert(u8_pixel_type).get_buffer()
+    expected = expected_image.convert(u8_pixel_type).get_buffer()
+    assert len(result) == dst_size[0] * dst_size[1] * pixel_size
+    assert max(abs(a - b) for a, b in zip(result, expected)) <= 1
+    assert len(set(result)) > 1
+
+
+@pytest.mark.parametrize(
+    ('dst_size', 'expected'),
+    [
+        ((32, 32), {(255, 128, 16, 128)}),
+        ((128, 128), {(255, 128, 16, 255), (0, 0, 0, 0)}),
+    ],
+)
+@pytest.mark.parametrize('linear_light', [False, True])
+def test_resize_into_other_pixel_type_with_alpha(dst_size, expected, linear_light):
+    # Transparent pixels must not affect colors of the result
+    src_image = ImageData(
+        64, 64, PixelType.U8x4, _stripes(64, 64, b'\xff\x80\x10\xff'),
+    ).convert(PixelType.U16x4)
+    dst_image = ImageData(*dst_size, PixelType.U8x4)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.box),
+        linear_light=linear_light,
+    )
+    Resizer().resize(src_image, dst_image, options)
+    assert set(zip(*[iter(dst_image.get_buffer())] * 4)) == expected
+
+
+@pytest.mark.parametrize('dst_size', [(32, 32), (128, 128)])
+def test_resize_into_other_pixel_type_with_dithering(thread_pool, dst_size):
+    # Value between two levels of U8 components
+    src_image = ImageData(64, 64, PixelType.U16, (128 * 257 + 128).to_bytes(2, 'little') * 64 * 64)
+    dst_image = ImageData(*dst_size, PixelType.U8)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        thread_pool=thread_pool,
+    )
+    assert options.dithering is Dithering.none
+    with pytest.raises(ValueError, match='Unknown dithering: 10'):
+        options.rust_options.set_dithering(10)
+    Resizer().resize(src_image, dst_image, options)
+    assert set(dst_image.get_buffer()) == {128}
+
+    options.dithering = Dithering.ordered
+    Resizer().resize(src_image, dst_image, options)
+    buffer = dst_image.get_buffer()
+    assert set(buffer) == {128, 129}
+    assert sum(buffer) / len(buffer) == pytest.approx(128.5, abs=0.02)
+
+
+@pytest.mark.parametrize(
+    ('u8_pixel_type', 'src_pixel_type', 'dst_pixel_type', 'order'),
+    [
+        # BGR -> RGB
+        (PixelType.U8x3, PixelType.U8x3, PixelType.U8x3, (2, 1, 0)),
+        # BGRA -> RGBA
+        (PixelType.U8x4, PixelType.U8x4, PixelType.U8x4, (2, 1, 0, 3)),
+        # ARGB -> RGBA
+        (PixelType.U8x4, PixelType.U8x4, PixelType.U8x4, (1, 2, 3, 0)),
+        # 16-bit BGRA -> 8-bit RGB
+        (PixelType.U8x4, PixelType.U16x4, PixelType.U8x3, (2, 1, 0)),
+        # Gray -> RGB
+        (PixelType.U8, PixelType.F32, PixelType.F32x3, (0, 0, 0)),
+    ],
+)
+@pytest.mark.parametrize('dst_size', [(16, 24), (100, 80)])
+def test_resize_with_channel_order(
+        thread_pool,
+        u8_pixel_type,
+        src_pixel_type,
+        dst_pixel_type,
+        order,
+        dst_size,
+):
+    src_image = ImageData(
+        64, 64, u8_pixel_type, bytes(range(256)) * (16 * PIXEL_SIZE[u8_pixel_type]),
+    ).convert(src_pixel_type)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.bilinear),
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        channel_order=order,
+    )
+    assert options.channel_order == order
+    resizer = Resizer()
+    dst_image = ImageData(*dst_size, dst_pixel_type)
+    resizer.resize(src_image, dst_image, options)
+
+    # Result must be near the same as a result of resizing
+    # of the source image with reordered channels.
+    swizzled_image = src_image.swizzle(order).convert(dst_pixel_type)
+    expected_image = ImageData(*dst_size, dst_pixel_type)
+    options.channel_order = None
+    resizer.resize(swizzled_image, expected_image, options)
+    u8_dst_pixel_type = (PixelType.U8, PixelType.U8x2, PixelType.U8x3, PixelType.U8x4)[
+        len(order) - 1
+    ]
+    result = dst_image.convert(u8_dst_pixel_type).get_buffer()
+    expected = expected_image.convert(u8_dst_pixel_type).get_buffer()
+    assert max(abs(a - b) for a, b in zip(result, expected)) <= 1
+
+
+def test_resize_with_channel_order_errors():
+    src_image = ImageData(64, 64, PixelType.U8x3)
+    options = ResizeOptions(channel_order=(2, 1, 0))
+    with pytest.raises(RuntimeError, match='Order of channels must have 4 items for U8x4 pixels'):
+        Resizer().resize(src_image, ImageData(32, 32, PixelType.U8x4), options)
+    options.channel_order = (3, 1, 0)
+    with pytest.raises(RuntimeError, match='Index of channel 3 is out of range of U8x3 pixels'):
+        Resizer().resize(src_image, ImageData(32, 32, PixelType.U8x3), options)
+    with pytest.raises(ValueError, match='Invalid order of channels'):
+        options.channel_order = (0, 1, 2, 3, 4)
+    options.channel_order = None
+    assert options.channel_order is None
+    assert options == ResizeOptions()
+
+
+@pytest.mark.parametrize('selected_channels', [(3,), (0, 2), (0, 1, 3)])
+@pytest.mark.parametrize('dst_size', [(16, 24), (100, 80)])
+def test_resize_selected_channels(thread_pool, selected_channels, dst_size):
+    src_image = ImageData(64, 64, PixelType.U8x4, bytes(range(256)) * 64)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.bilinear),
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        selected_channels=selected_channels,
+    )
+    assert options.selected_channels == selected_channels
+    dst_image = ImageData(*dst_size, PixelType.U8x4, bytes([7]) * (dst_size[0] * dst_size[1] * 4))
+    Resizer().resize(src_image, dst_image, options)
+
+    # Selected channels are resized independently of each other
+    # because they don't include the alpha channel as the last one,
+    # other channels are not changed.
+    options.selected_channels = None
+    dst_channels = dst_image.split()
+    src_channels = src_image.split()
+    for channel in range(4):
+        if channel in selected_channels:
+            expected_image = ImageData(*dst_size, PixelType.U8)
+            Resizer().resize(src_channels[channel], expected_image, options)
+            assert dst_channels[channel].get_buffer() == expected_image.get_buffer()
+        else:
+            assert set(dst_channels[channel].get_buffer()) == {7}
+
+
+def test_resize_selected_channels_errors():
+    src_image = ImageData(64, 64, PixelType.U8x3)
+    options = ResizeOptions(selected_channels=(3,))
+    with pytest.raises(RuntimeError, match='Index of channel 3 is out of range of U8x3 pixels'):
+        Resizer().resize(src_image, ImageData(32, 32, PixelType.U8x3), options)
+    options.selected_channels = (0,)
+    with pytest.raises(RuntimeError, match='Pixel type of source image does not match'):
+        Resizer().resize(src_image, ImageData(32, 32, PixelType.U16x3), options)
+    options.channel_order = (2, 1, 0)
+    with pytest.raises(RuntimeError, match="can't be resized with reordering of channels"):
+        Resizer().resize(src_image, ImageData(32, 32, PixelType.U8x3), options)
+    with pytest.raises(ValueError, match='Invalid selected channels'):
+        options.selected_channels = (0, 0)
+
+
+@pytest.mark.parametrize(
+    ('src_pixel_type', 'dst_pixel_type'),
+    [
+        (PixelType.U8x3, PixelType.U8x3),
+        (PixelType.U8x4, PixelType.U8x4),
+        (PixelType.U16x4, PixelType.U8x4),
+        (PixelType.F32x3, PixelType.F32x3),
+    ],
+)
+@pytest.mark.parametrize('dst_size', [(16, 24), (100, 80)])
+def test_resize_with_detect_grayscale(thread_pool, src_pixel_type, dst_pixel_type, dst_size):
+    gray_image = ImageData(64, 64, PixelType.U8, bytes(range(256)) * 16)
+    channels = [gray_image] * 3
+    if src_pixel_type.name.endswith('x4'):
+        channels.append(ImageData(64, 64, PixelType.U8, bytes(range(0, 256, 4)) * 64))
+    src_image = ImageData.merge(channels).convert(src_pixel_type)
+
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        thread_pool=thread_pool,
+        threading_threshold=0,
+    )
+    expected_image = ImageData(*dst_size, dst_pixel_type)
+    report = Resizer().resize(src_image, expected_image, options)
+    assert not report.grayscale
+
+    options.detect_grayscale = True
+    assert options.detect_grayscale
+    dst_image = ImageData(*dst_size, dst_pixel_type)
+    report = Resizer().resize(src_image, dst_image, options)
+    assert report.grayscale
+    assert dst_image.get_buffer() == expected_image.get_buffer()
+
+    # Not grayscale image is resized as usual
+    channels[0] = ImageData(64, 64, PixelType.U8, bytes(range(255, -1, -1)) * 16)
+    src_image = ImageData.merge(channels).convert(src_pixel_type)
+    report = Resizer().resize(src_image, dst_image, options)
+    assert not report.grayscale
+    assert not dst_image.is_grayscale()
+
+
+@pytest.mark.parametrize(
+    ('alpha', 'constant_alpha'),
+    [
+        (bytes([255]) * 64 * 64, True),
+        (bytes([128]) * 64 * 64, True),
+        (bytes(64 * 64), False),
+        (bytes(range(256)) * 16, False),
+    ],
+    ids=['opaque', 'semi-transparent', 'transparent', 'varying'],
+)
+@pytest.mark.parametrize('pixel_type', [PixelType.U8x4, PixelType.U16x2])
+def test_resize_with_constant_alpha(thread_pool, pixel_type, alpha, constant_alpha):
+    channels = [ImageData(64, 64, PixelType.U8, bytes(range(256)) * 16)]
+    if pixel_type is PixelType.U8x4:
+        channels *= 3
+    channels.append(ImageData(64, 64, PixelType.U8, alpha))
+    src_image = ImageData.merge(channels, pixel_type)
+
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        thread_pool=thread_pool,
+        threading_threshold=0,
+    )
+    dst_image = ImageData(32, 24, pixel_type)
+    report = Resizer().resize(src_image, dst_image, options)
+    assert report.constant_alpha is constant_alpha
+    if constant_alpha:
+        # Result is the same as resizing without considering of alpha channel
+        expected_image = ImageData(32, 24, pixel_type)
+        options.use_alpha = False
+        report = Resizer().resize(src_image, expected_image, options)
+        assert not report.constant_alpha
+        assert dst_image.get_buffer() == expected_image.get_buffer()
+
+    report = Resizer().resize(src_image, dst_image, ResizeOptions(ResizeAlg.nearest()))
+    assert not report.constant_alpha
+    # Default options
+    report = Resizer().resize(src_image, dst_image)
+    assert report.constant_alpha is constant_alpha
+
+
+def test_resize_crop_box_with_constant_alpha(thread_pool):
+    # Only the left half of the image is opaque
+    row = bytes([10, 20, 30, 255]) * 32 + bytes(range(128))
+    src_image = ImageData(64, 64, PixelType.U8x4, row * 64)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        crop_box=CropBox(0, 0, 16, 64),
+        thread_pool=thread_pool,
+        threading_threshold=0,
+    )
+    dst_image = ImageData(8, 32, PixelType.U8x4)
+    # Pixels outside the crop box and the margin of the kernel are not used
+    report = Resizer().resize(src_image, dst_image, options)
+    assert report.constant_alpha
+    assert set(dst_image.get_buffer()) == {10, 20, 30, 255}
+
+    # Pixels near the crop box are used by the resize algorithm
+    options.crop_box = CropBox(0, 0, 28, 64)
+    report = Resizer().resize(src_image, dst_image, options)
+    assert not report.constant_alpha
+
+
+@pytest.mark.parametrize('pixel_type', [PixelType.U8x4, PixelType.U16x4, PixelType.F32x4])
+def test_resize_with_bleed_alpha(thread_pool, pixel_type):
+    # The left half of the image is opaque and white, the right half
+    # is transparent and black.
+    row = bytes([255, 255, 255, 255]) * 32 + bytes([0, 0, 0, 0]) * 32
+    src_image = ImageData(64, 64, PixelType.U8x4, row * 64).convert(pixel_type)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        use_alpha=False,
+        thread_pool=thread_pool,
+        threading_threshold=0,
+    )
+    dst_image = ImageData(24, 24, pixel_type)
+    Resizer().resize(src_image, dst_image, options)
+    colors = set(dst_image.convert(PixelType.U8x4).drop_alpha().get_buffer())
+    assert min(colors) == 0
+
+    options.bleed_alpha = True
+    assert options.bleed_alpha is True
+    Resizer().resize(src_image, dst_image, options)
+    colors = set(dst_image.convert(PixelType.U8x4).drop_alpha().get_buffer())
+    assert colors == {255}
+
+    # The filled area is too narrow
+    options.bleed_alpha = 2
+    assert options.bleed_alpha == 2
+    Resizer().resize(src_image, dst_image, options)
+    colors = set(dst_image.convert(PixelType.U8x4).drop_alpha().get_buffer())
+    assert min(colors) == 0
+
+    options.bleed_alpha = False
+    assert options.bleed_alpha is False
+    with pytest.raises(ValueError, match='must be greater than zero'):
+        options.bleed_alpha = 0
+
+
+def test_resize_with_bleed_alpha_in_crop_box(thread_pool):
+    # Columns 150-155 are opaque and white, other pixels
+    # are transparent and black.
+    def make_row(far_pixel: bytes) -> bytes:
+        return (
+            far_pixel * 8
+            + bytes([0, 0, 0, 0]) * 142
+            + bytes([255, 255, 255, 255]) * 6
+            + bytes([0, 0, 0, 0]) * 100
+        )
+
+    src_image = ImageData(256, 64, PixelType.U8x4, make_row(bytes([0, 0, 0, 0])) * 64)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        crop_box=CropBox(154, 0, 64, 64),
+        use_alpha=False,
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        bleed_alpha=True,
+    )
+    dst_image = ImageData(32, 32, PixelType.U8x4)
+    Resizer().resize(src_image, dst_image, options)
+    assert set(dst_image.drop_alpha().get_buffer()) == {255}
+
+    # Only pixels used to resize the crop box are bled
+    expected_image = ImageData(32, 32, PixelType.U8x4)
+    options.bleed_alpha = False
+    Resizer().resize(src_image.bleed_alpha(), expected_image, options)
+    assert dst_image.get_buffer() == expected_image.get_buffer()
+
+    # Pixels far from the crop box don't affect the result
+    options.bleed_alpha = True
+    src_image = ImageData(256, 64, PixelType.U8x4, make_row(bytes([255, 0, 0, 255])) * 64)
+    other_image = ImageData(32, 32, PixelType.U8x4)
+    Resizer().resize(src_image, other_image, options)
+    assert other_image.get_buffer() == dst_image.get_buffer()
+
+
+@pytest.mark.parametrize(
+    ('src_pixel_type', 'dst_pixel_type', 'background'),
+    [
+        (PixelType.U8x4, PixelType.U8x3, (255, 255, 255)),
+        (PixelType.U8x4, PixelType.U8x4, (255, 255, 255)),
+        (PixelType.U16x4, PixelType.U8x3, (255, 255, 255)),
+        (PixelType.U8x4, PixelType.U16x3, (65535, 65535, 65535)),
+        (PixelType.F32x4, PixelType.F32x3, (1.0, 1.0, 1.0)),
+        (PixelType.U8x2, PixelType.U8, 255),
+        (PixelType.U16x2, PixelType.U16, 65535),
+        (PixelType.U8x4, PixelType.U8, 255),
+        (PixelType.U8x4, PixelType.U8, (255, 255, 255)),
+        (PixelType.U16x4, PixelType.U8, 255),
+    ],
+)
+@pytest.mark.parametrize('use_alpha', [True, False])
+def test_resize_onto_background(
+        thread_pool,
+        src_pixel_type,
+        dst_pixel_type,
+        background,
+        use_alpha,
+):
+    # The left half of the image is opaque and black, the right half
+    # is transparent and has garbage colors.
+    row = bytes([0, 0, 0, 255]) * 32 + bytes([255, 0, 255, 0]) * 32
+    src_image = ImageData(64, 64, PixelType.U8x4, row * 64)
+    if src_pixel_type.name.endswith('x2'):
+        src_image = src_image.swizzle((0, 3))
+    src_image = src_image.convert(src_pixel_type)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        use_alpha=use_alpha,
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        background=background,
+    )
+    assert options.background == (background if isinstance(background, tuple) else (background,))
+    dst_image = ImageData(16, 16, dst_pixel_type)
+    Resizer().resize(src_image, dst_image, options)
+
+    def get_u8_pixels(image: ImageData) -> bytes:
+        if dst_pixel_type is PixelType.U8x4:
+            assert image.is_opaque()
+            image = image.drop_alpha()
+        gray = dst_pixel_type in (PixelType.U8, PixelType.U16)
+        u8_pixel_type = PixelType.U8 if gray else PixelType.U8x3
+        return image.convert(u8_pixel_type).get_buffer()
+
+    pixels = get_u8_pixels(dst_image)
+    row_size = len(pixels) // 16
+    pixel_size = row_size // 16
+    for i in range(0, len(pixels), row_size):
+        row = pixels[i:i + row_size]
+        assert set(row[:4 * pixel_size]) == {0}
+        assert set(row[-4 * pixel_size:]) == {255}
+
+    # Semi-transparent image with constant alpha
+    src_image = ImageData(64, 64, PixelType.U8x4, bytes([0, 0, 0, 128]) * 64 * 64)
+    if src_pixel_type.name.endswith('x2'):
+        src_image = src_image.swizzle((0, 3))
+    src_image = src_image.convert(src_pixel_type)
+    Resizer().resize(src_image, dst_image, options)
+    assert set(get_u8_pixels(dst_image)) == {127}
+
+
+def test_resize_onto_background_into_grayscale(thread_pool):
+    # Colors are composited onto the background before conversion
+    # into grayscale with weights of ITU-R BT.601.
+    row = bytes([255, 0, 0, 255]) * 32 + bytes([0, 255, 0, 0]) * 32
+    src_image = ImageData(64, 64, PixelType.U8x4, row * 64)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        thread_pool=thread_pool,
+        background=(0, 0, 255),
+    )
+    dst_image = ImageData(16, 16, PixelType.U8)
+    Resizer().resize(src_image, dst_image, options)
+    pixels = dst_image.get_buffer()
+    for i in range(0, len(pixels), 16):
+        assert set(pixels[i:i + 4]) == {76}
+        assert set(pixels[i + 12:i + 16]) == {29}
+
+    expected_image = ImageData(16, 16, PixelType.U8x3)
+    Resizer().resize(src_image, expected_image, options)
+    expected_image = expected_image.to_grayscale()
+    assert dst_image.get_buffer() == expected_image.get_buffer()
+
+
+@pytest.mark.parametrize('dst_pixel_type', [PixelType.U8x3, PixelType.U8x4])
+def test_resize_onto_background_with_crop_box(thread_pool, dst_pixel_type):
+    # The left half of the image is opaque and black, the right half
+    # is transparent and has garbage colors.
+    src_row = bytes([0, 0, 0, 255]) * 32 + bytes([255, 0, 255, 0]) * 32
+    src_image = ImageData(64, 64, PixelType.U8x4, src_row * 64)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        crop_box=CropBox(16, 8, 32, 48),
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        background=(255, 255, 255),
+    )
+    dst_image = ImageData(16, 24, dst_pixel_type)
+    Resizer().resize(src_image, dst_image, options)
+    pixels = dst_image.get_buffer()
+    if dst_pixel_type is PixelType.U8x4:
+        assert dst_image.is_opaque()
+        pixels = dst_image.drop_alpha().get_buffer()
+    for i in range(0, len(pixels), 16 * 3):
+        row = pixels[i:i + 16 * 3]
+        assert set(row[:5 * 3]) == {0}
+        assert set(row[-5 * 3:]) == {255}
+
+    # Pixels far from the crop box don't affect the result
+    far_row = bytes([255, 255, 255, 255]) * 4 + src_row[4 * 4:]
+    src_image = ImageData(64, 64, PixelType.U8x4, far_row * 64)
+    other_image = ImageData(16, 24, dst_pixel_type)
+    Resizer().resize(src_image, other_image, options)
+    assert other_image.get_buffer() == dst_image.get_buffer()
+
+
+def test_resize_onto_background_errors():
+    with pytest.raises(ValueError, match='Invalid background color'):
+        ResizeOptions(background=(255, 255))
+
+    resizer = Resizer()
+    src_image = ImageData(64, 64, PixelType.U8x4)
+    options = ResizeOptions(background=(255, 255, 255))
+    with pytest.raises(RuntimeError, match='Pixel type of source image does not match'):
+        resizer.resize(src_image, ImageData(16, 16, PixelType.U8x2), options)
+    with pytest.raises(RuntimeError, match='Background color must have 3 components'):
+        resizer.resize(src_image, ImageData(16, 16, PixelType.U8x3), ResizeOptions(background=0))
+    options.channel_order = (0, 1, 2)
+    with pytest.raises(RuntimeError, match="can't be used with reordering"):
+        resizer.resize(src_image, ImageData(16, 16, PixelType.U8x3), options)
+
+    # Background is not used for images without alpha channel
+    options = ResizeOptions(background=255)
+    resizer.resize(ImageData(64, 64, PixelType.U8), ImageData(16, 16, PixelType.U8), options)
+
+
+@pytest.mark.parametrize('src_mode', ('RGBA', 'RGBa'))
+@pytest.mark.parametrize('dst_mode', ('RGB', 'L'))
+def test_resize_pil_onto_background(src_mode, dst_mode):
+    src_image = Image.new('RGBA', (64, 64), (0, 0, 0, 0))
+    src_image.paste((255, 0, 0, 255), (0, 0, 32, 64))
+    if src_mode == 'RGBa':
+        src_image = src_image.convert('RGBa')
+    dst_image = Image.new(dst_mode, (16, 16))
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        background=(255, 255, 255),
+    )
+    Resizer().resize_pil(src_image, dst_image, options)
+    assert dst_image.mode == dst_mode
+    red, white = (255, 0, 0), (255, 255, 255)
+    if dst_mode == 'L':
+        red, white = Image.new('RGB', (1, 1), red).convert('L').getpixel((0, 0)), 255
+    assert dst_image.getpixel((0, 8)) == red
+    assert dst_image.getpixel((15, 8)) == white
+
+
+@pytest.mark.parametrize(
+    ('src_size', 'centering', 'region'),
+    [
+        ((64, 32), None, (0, 8, 32, 16)),
+        ((64, 32), (0.0, 0.0), (0, 0, 32, 16)),
+        ((64, 32), (1.0, 0.25), (0, 4, 32, 16)),
+        ((30, 60), None, (8, 0, 16, 32)),
+        ((30, 60), (1.0, 1.0), (16, 0, 16, 32)),
+        ((64, 64), None, (0, 0, 32, 32)),
+    ],
+)
+@pytest.mark.parametrize(
+    ('padding', 'padding_pixel'),
+    [
+        (None, bytes([0, 0, 0, 0])),
+        ((0, 0, 255), bytes([0, 0, 255, 255])),
+        ((10, 20, 30, 40), bytes([10, 20, 30, 40])),
+    ],
+)
+def test_resize_contain_in_destination(
+        thread_pool,
+        src_size,
+        centering,
+        region,
+        padding,
+        padding_pixel,
+):
+    src_width, src_height = src_size
+    pixels = bytes((x * 4 + y * 3) % 256 for y in range(src_height) for x in range(src_width * 4))
+    src_image = ImageData(src_width, src_height, PixelType.U8x4, pixels)
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        contain_in_destination=centering or True,
+        padding=padding,
+    )
+    assert options.get_contain_in_destination_centering() == (centering or (0.5, 0.5))
+    dst_image = ImageData(32, 32, PixelType.U8x4, bytes([1]) * 32 * 32 * 4)
+    Resizer().resize(src_image, dst_image, options)
+
+    left, top, width, height = region
+    expected_image = ImageData(width, height, PixelType.U8x4)
+    Resizer().resize(src_image, expected_image, ResizeOptions(options.resize_alg))
+    expected_pixels = expected_image.get_buffer()
+    dst_pixels = dst_image.get_buffer()
+    for y in range(32):
+        row = dst_pixels[y * 32 * 4:(y + 1) * 32 * 4]
+        if top <= y < top + height:
+            assert row[:left * 4] == padding_pixel * left
+            expected_row = expected_pixels[(y - top) * width * 4:(y - top + 1) * width * 4]
+            assert row[left * 4:(left + width) * 4] == expected_row
+            assert row[(left + width) * 4:] == padding_pixel * (32 - left - width)
+        else:
+            assert row == padding_pixel * 32
+
+
+def test_resize_contain_in_destination_options():
+    options = ResizeOptions(contain_in_destination=True)
+    assert options.get_contain_in_destination_centering() == (0.5, 0.5)
+    options.fit_into_destination()
+    assert options.get_contain_in_destination_centering() is None
+    assert options.get_fit_into_destination_centering() == (0.5, 0.5)
+    options.contain_in_destination((0.0, 1.0))
+    assert options.get_contain_in_destination_centering() == (0.0, 1.0)
+    assert options.get_fit_into_destination_centering() is None
+
+    with pytest.raises(ValueError, match='Invalid padding color'):
+        options.padding = (1, 2, 3, 4, 5)
+    options.padding = (255, 255)
+    with pytest.raises(RuntimeError, match='Padding color must have 4 components'):
+        Resizer().resize(ImageData(8, 4, PixelType.U8x4), ImageData(4, 4, PixelType.U8x4), options)
+
+    # The background color is used as padding by default
+    src_image = ImageData(8, 4, PixelType.U8x4, bytes([0, 0, 0, 255]) * 8 * 4)
+    dst_image = ImageData(4, 4, PixelType.U8x3)
+    options = ResizeOptions(contain_in_destination=(0.5, 0.0), background=(255, 255, 255))
+    Resizer().resize(src_image, dst_image, options)
+    assert dst_image.get_buffer() == bytes([0]) * 4 * 2 * 3 + bytes([255]) * 4 * 2 * 3
+
+
+@pytest.mark.parametrize('dst_mode', ('RGB', 'RGBA', 'L'))
+def test_resize_pil_contain_in_destination(dst_mode):
+    src_image = Image.new('RGB', (64, 32), (255, 0, 0))
+    dst_image = Image.new(dst_mode, (32, 32))
+    padding = (255,) if dst_mode == 'L' else (0, 0, 255)
+    options = ResizeOptions(contain_in_destination=True, padding=padding)
+    Resizer().resize_pil(src_image, dst_image, options)
+    assert dst_image.mode == dst_mode
+    red = Image.new('RGB', (1, 1), (255, 0, 0)).convert(dst_mode).getpixel((0, 0))
+    blue = Image.new('RGB', (1, 1), padding).convert(dst_mode).getpixel((0, 0))
+    assert dst_image.getpixel((16, 16)) == red
+    assert dst_image.getpixel((16, 2)) == blue
+    assert dst_image.getpixel((16, 29)) == blue
+
+
+def _get_region_pixels(image: ImageData, region: Tuple[int, int, int, int]) -> bytes:
+    left, top, width, height = region
+    pixel_size = PIXEL_SIZE[image.pixel_type]
+    row_size = image.width * pixel_size
+    buffer = image.get_buffer()
+    return b''.join(
+        buffer[y * row_size + left * pixel_size:y * row_size + (left + width) * pixel_size]
+        for y in range(top, top + height)
+    )
+
+
+@pytest.mark.parametrize('dst_pixel_type', [PixelType.U8x4, PixelType.U8x3])
+def test_resize_into_dst_region(thread_pool, dst_pixel_type):
+    src_image = ImageData(64, 48, PixelType.U8x4, bytes(range(256)) * 48)
+    canvas = ImageData(64, 48, dst_pixel_type, bytes([7]) * 64 * 48 * PIXEL_SIZE[dst_pixel_type])
+    resizer = Resizer()
+    options = ResizeOptions(
+        ResizeAlg.convolution(FilterType.lanczos3),
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        channel_order=(0, 1, 2) if dst_pixel_type is PixelType.U8x3 else None,
+    )
+    regions = [(0, 0, 32, 24), (32, 24, 32, 24), (40, 2, 10, 20)]
+    for region in regions:
+        options.dst_region = region
+        assert options.dst_region == region
+        resizer.resize(src_image, canvas, options)
+
+    options.dst_region = None
+    for region in regions:
+        expected_image = ImageData(region[2], region[3], dst_pixel_type)
+        resizer.resize(src_image, expected_image, options)
+        assert _get_region_pixels(canvas, region) == expected_image.get_buffer()
+    # Pixels outside regions are not changed
+    for region in [(0, 24, 32, 24), (32, 0, 8, 24), (50, 0, 14, 24)]:
+        assert set(_get_region_pixels(canvas, region)) == {7}
+
+    # The source image is contained in the region
+    options.dst_region = (0, 0, 32, 48)
+    options.contain_in_destination()
+    options.padding = (255, 255, 255)
+    resizer.resize(src_image, canvas, options)
+    assert set(_get_region_pixels(canvas, (0, 0, 32, 12))) == {255}
+    assert set(_get_region_pixels(canvas, (0, 36, 32, 12))) == {255}
+    assert set(_get_region_pixels(canvas, (32, 0, 8, 24))) == {7}
+
+
+def test_resize_into_dst_region_errors():
+    options = ResizeOptions()
+    with pytest.raises(ValueError, match='must be greater or equal to zero'):
+        options.dst_region = (-1, 0, 10, 10)
+    with pytest.raises(ValueError, match='must have four values'):
+        options.dst_region = (0, 0, 10)
+    src_image = ImageData(64, 64, PixelType.U8x4)
+    for region in [(60, 0, 10, 10), (0, 0, 65, 64), (64, 0, 0, 10)]:
+        options.dst_region = region
+        with pytest.raises(ValueError, match='out of'):
+            Resizer().resize(src_image, ImageData(64, 64, PixelType.U8x4), options)
+
+
+@pytest.mark.parametrize('src_mode', ('RGB', 'RGBA', 'L'))
+def test_resize_pil_into_dst_region(src_mode):
+    src_image = Image.new(src_mode, (64, 64), 'red')
+    canvas = Image.new('RGB', (64, 32), 'blue')
+    options = ResizeOptions(dst_region=(32, 0, 32, 32))
+    Resizer().resize_pil(src_image, canvas, options)
+    assert canvas.mode == 'RGB'
+    red = src_image.convert('RGB').getpixel((0, 0))
+    assert canvas.getpixel((31, 16)) == (0, 0, 255)
+    assert canvas.getpixel((32, 16)) == red
+    assert canvas.getpixel((63, 31)) == red
+
+
+@pytest.mark.parametrize(
+    ('src_mode', 'dst_mode', 'expected'),
+    [
+        ('RGBA', 'RGBa', (128, 0, 0, 128)),
+        ('RGBa', 'RGBA', (255, 0, 0, 128)),
+    ],
+)
+def test_resize_pil_into_dst_region_with_other_alpha(src_mode, dst_mode, expected):
+    # Pixels outside the region are not multiplied or divided by alpha
+    # after resizing (the nearest algorithm doesn't multiply source pixels).
+    src_image = Image.new('RGBA', (64, 64), (255, 0, 0, 128)).convert(src_mode)
+    canvas = Image.new('RGBA', (64, 32), (0, 0, 100, 128)).convert(dst_mode)
+    outside = canvas.getpixel((0, 0))
+    options = ResizeOptions(ResizeAlg.nearest(), dst_region=(32, 0, 32, 32))
+    Resizer().resize_pil(src_image, canvas, options)
+    assert canvas.mode == dst_mode
+    assert canvas.getpixel((31, 16)) == outside
+    assert canvas.getpixel((32, 16)) == expected
+    assert canvas.getpixel((63, 31)) == expected
+
+    options.dst_region = (40, 0, 32, 32)
+    with pytest.raises(ValueError, match='out of'):
+        Resizer().resize_pil(src_image, canvas, options)
+    assert canvas.mode == dst_mode
+    assert canvas.getpixel((0, 0)) == outside
+
+
+def _extend_image(
+        image: ImageData,
+        crop_box: CropBox,
+        mode: EdgeMode,
+        color: Optional[bytes],
+        all_edges: bool = False,
+) -> Tuple[ImageData, CropBox]:
+    """Extends U8x4 image by 40 pixels on each side where the crop box
+    extends beyond the edges of the image or on all sides."""
+    width, height = image.width, image.height
+    left = -40 if crop_box.left < 0 or all_edges else 0
+    top = -40 if crop_box.top < 0 or all_edges else 0
+    right = width + 40 if crop_box.left + crop_box.width > width or all_edges else width
+    bottom = height + 40 if crop_box.top + crop_box.height > height or all_edges else height
+
+    def source_coordinate(c: int, size: int) -> Optional[int]:
+        if 0 <= c < size:
+            return c
+        if mode is EdgeMode.clamp:
+            return min(max(c, 0), size - 1)
+        if mode is EdgeMode.mirror:
+            c %= 2 * size
+            return c if c < size else 2 * size - 1 - c
+        if mode is EdgeMode.wrap:
+            return c % size
+        return None
+
+    buffer = image.get_buffer()
+    pixels = bytearray()
+    for y in range(top, bottom):
+        src_y = source_coordinate(y, height)
+        for x in range(left, right):
+            src_x = source_coordinate(x, width)
+            if src_x is None or src_y is None:
+                pixels += color
+            else:
+                offset = (src_y * width + src_x) * 4
+                pixels += buffer[offset:offset + 4]
+    extended_image = ImageData(right - left, bottom - top, PixelType.U8x4, bytes(pixels))
+    extended_crop_box = CropBox(
+        crop_box.left - left,
+        crop_box.top - top,
+        crop_box.width,
+        crop_box.height,
+    )
+    return extended_image, extended_crop_box
+
+
+def _resize_nearest(image: ImageData, crop_box: CropBox, width: int, height: int) -> bytes:
+    """Resizes U8x4 image by the nearest algorithm without rounding
+    errors of positions of source pixels."""
+
+    def coordinates(start: float, length: float, size: int) -> List[int]:
+        start, length = Fraction(start), Fraction(length)
+        return [int(start + (2 * i + 1) * length / (2 * size)) for i in range(size)]
+
+    buffer = image.get_buffer()
+    columns = coordinates(crop_box.left, crop_box.width, width)
+    rows = coordinates(crop_box.top, crop_box.height, height)
+    return b''.join(
+        buffer[(y * image.width + x) * 4:(y * image.width + x + 1) * 4]
+        for y in rows
+        for x in columns
+    )
+
+
+@pytest.mark.parametrize(
+    ('edge_mode', 'edge_color', 'color'),
+    [
+        (EdgeMode.transparent, None, bytes([0, 0, 0, 0])),
+        (EdgeMode.transparent, (10, 20, 30), bytes([0, 0, 0, 0])),
+        (EdgeMode.constant, None, bytes([0, 0, 0, 0])),
+        (EdgeMode.constant, (10, 20, 30), bytes([10, 20, 30, 255])),
+        (EdgeMode.constant, (10, 20, 30, 40), bytes([10, 20, 30, 40])),
+        (EdgeMode.clamp, None, None),
+        (EdgeMode.mirror, None, None),
+        (EdgeMode.wrap, None, None),
+    ],
+)
+@pytest.mark.parametrize(
+    'crop_box',
+    [
+        CropBox(-4, -2, 24, 16),
+        CropBox(2, -3.5, 20, 10.5),
+        CropBox(-20.5, 13, 10, 6),
+    ],
+)
+@pytest.mark.parametrize(
+    'resize_alg',
+    [
+        ResizeAlg.convolution(FilterType.lanczos3),
+        ResizeAlg.convolution(FilterType.bilinear),
+        ResizeAlg.nearest(),
+    ],
+)
+def test_resize_crop_box_beyond_edges(
+        thread_pool,
+        edge_mode,
+        edge_color,
+        color,
+        crop_box,
+        resize_alg,
+):
+    pixels = bytes((x * 5 + y * 7) % 256 for y in range(12) for x in range(16 * 4))
+    src_image = ImageData(16, 12, PixelType.U8x4, pixels)
+    # Multiplying by alpha is disabled, because it is skipped
+    # for extended images with constant alpha.
+    options = ResizeOptions(
+        resize_alg,
+        use_alpha=False,
+        crop_box=crop_box,
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        edge_mode=edge_mode,
+        edge_color=edge_color,
+    )
+    assert options.crop_box == crop_box
+    assert options.edge_mode is edge_mode
+    assert options.edge_color == edge_color
+    dst_image = ImageData(12, 8, PixelType.U8x4)
+    Resizer().resize(src_image, dst_image, options)
+
+    extended_image, extended_crop_box = _extend_image(src_image, crop_box, edge_mode, color)
+    if resize_alg.algorithm is Algorithm.nearest:
+        expected = _resize_nearest(extended_image, extended_crop_box, 12, 8)
+        assert dst_image.get_buffer() == expected
+        return
+    expected_image = ImageData(12, 8, PixelType.U8x4)
+    expected_options = ResizeOptions(resize_alg, use_alpha=False, crop_box=extended_crop_box)
+    Resizer().resize(extended_image, expected_image, expected_options)
+    assert dst_image.get_buffer() == expected_image.get_buffer()
+
+
+@pytest.mark.parametrize(
+    'edge_mode',
+    [EdgeMode.constant, EdgeMode.clamp, EdgeMode.mirror, EdgeMode.wrap],
+)
+def test_resize_crop_box_beyond_edges_by_nearest(thread_pool, edge_mode):
+    # Centers of some destination pixels are exactly
+    # on the boundaries of source pixels.
+    pixels = bytes((x * 5 + y * 7) % 256 for y in range(20) for x in range(24 * 4))
+    src_image = ImageData(24, 20, PixelType.U8x4, pixels)
+    crop_box = CropBox(-12, -10, 24, 20)
+    options = ResizeOptions(
+        ResizeAlg.nearest(),
+        crop_box=crop_box,
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        edge_mode=edge_mode,
+        edge_color=(10, 20, 30, 40),
+    )
+    dst_image = ImageData(50, 41, PixelType.U8x4)
+    Resizer().resize(src_image, dst_image, options)
+
+    extended_image, extended_crop_box = _extend_image(
+        src_image,
+        crop_box,
+        edge_mode,
+        bytes([10, 20, 30, 40]),
+    )
+    expected = _resize_nearest(extended_image, extended_crop_box, 50, 41)
+    assert dst_image.get_buffer() == expected
+
+
+def test_resize_crop_box_beyond_edges_errors():
+    with pytest.raises(ValueError, match='must be greater or equal to zero'):
+        CropBox(-1, -1, -2, 4)
+    options = ResizeOptions(crop_box=CropBox(-4, 0, 8, 4), edge_mode=EdgeMode.constant)
+    with pytest.raises(ValueError, match='Unknown edge mode: 10'):
+        options.rust_options.set_edge_mode(10)
+    with pytest.raises(ValueError, match='Invalid edge color'):
+        options.edge_color = (1, 2, 3, 4, 5)
+    options.edge_color = (255, 255)
+    with pytest.raises(RuntimeError, match='Edge color must have 4 components'):
+        Resizer().resize(ImageData(8, 4, PixelType.U8x4), ImageData(4, 4, PixelType.U8x4), options)
+
+
+@pytest.mark.parametrize('border_mode', [BorderMode.clamp, BorderMode.mirror, BorderMode.wrap])
+@pytest.mark.parametrize('crop_box', [None, CropBox(2, 1.5, 10, 8), CropBox(-2, 3, 10, 12)])
+@pytest.mark.parametrize('dst_size', [(8, 6), (20, 15), (50, 41)])
+@pytest.mark.parametrize(
+    'resize_alg',
+    [
+        ResizeAlg.convolution(FilterType.lanczos3),
+        ResizeAlg.convolution(FilterType.catmull_rom),
+        ResizeAlg.nearest(),
+    ],
+)
+def test_resize_with_border_mode(thread_pool, border_mode, crop_box, dst_size, resize_alg):
+    pixels = bytes((x * 5 + y * 7) % 256 for y in range(12) for x in range(16 * 4))
+    src_image = ImageData(16, 12, PixelType.U8x4, pixels)
+    options = ResizeOptions(
+        resize_alg,
+        use_alpha=False,
+        crop_box=crop_box,
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        edge_mode=EdgeMode.clamp,
+        border_mode=border_mode,
+    )
+    assert options.border_mode is border_mode
+    dst_image = ImageData(*dst_size, PixelType.U8x4)
+    Resizer().resize(src_image, dst_image, options)
+
+    # Pixels outside the crop box that extends beyond the edges
+    # are got in accordance with the edge mode.
+    if crop_box is None or crop_box.left >= 0:
+        edge_mode = EdgeMode[border_mode.name]
+    else:
+        edge_mode = EdgeMode.clamp
+    extended_image, extended_crop_box = _extend_image(
+        src_image,
+        crop_box or CropBox(0, 0, 16, 12),
+        edge_mode,
+        None,
+        all_edges=True,
+    )
+    if resize_alg.algorithm is Algorithm.nearest:
+        # The nearest algorithm doesn't use pixels outside the crop box
+        expected = _resize_nearest(extended_image, extended_crop_box, *dst_size)
+        assert dst_image.get_buffer() == expected
+        return
+    expected_image = ImageData(*dst_size, PixelType.U8x4)
+    expected_options = ResizeOptions(resize_alg, use_alpha=False, crop_box=extended_crop_box)
+    Resizer().resize(extended_image, expected_image, expected_options)
+    assert dst_image.get_buffer() == expected_image.get_buffer()
+
+    if crop_box is None:
+        # Kernels are clipped by the edges of the whole image
+        options.border_mode = BorderMode.normalize
+        normalized_image = ImageData(*dst_size, PixelType.U8x4)
+        Resizer().resize(src_image, normalized_image, options)
+        assert normalized_image.get_buffer() != dst_image.get_buffer()
+
+
+@pytest.mark.parametrize('mode', ('RGB', 'RGBA', 'L'))
+def test_resize_pil_crop_box_beyond_edges(mode):
+    src_image = Image.new(mode, (32, 32), 'red')
+    dst_image = Image.new(mode, (64, 32))
+    edge_color = (255,) if mode == 'L' else (0, 0, 255)
+    options = ResizeOptions(
+        crop_box=CropBox(-32, 0, 64, 32),
+        edge_mode=EdgeMode.constant,
+        edge_color=edge_color,
+    )
+    Resizer().resize_pil(src_image, dst_image, options)
+    assert dst_image.mode == mode
+    color = Image.new(mode, (1, 1), edge_color[0] if mode == 'L' else edge_color).getpixel((0, 0))
+    assert dst_image.getpixel((0, 16)) == color
+    assert dst_image.getpixel((16, 16)) == color
+    assert dst_image.getpixel((48, 16)) == src_image.getpixel((0, 0))
+    assert dst_image.getpixel((63, 16)) == src_image.getpixel((0, 0))
+
+
+def test_resize_multi(thread_pool):
+    pixels = bytes((x * 5 + y * 7 + x * y) % 256 for y in range(300) for x in range(400 * 4))
+    src_image = ImageData(400, 300, PixelType.U8x4, pixels)
+    options = ResizeOptions(thread_pool=thread_pool, threading_threshold=0)
+    sizes = [(60, 45), (200, 150), (100, 75), (400, 300), (50, 150)]
+    dst_images = [ImageData(w, h, PixelType.U8x4) for w, h in sizes]
+    reports = Resizer().resize_multi(src_image, dst_images, options)
+    assert len(reports) == len(dst_images)
+
+    def resize(src: ImageData, size: Tuple[int, int]) -> bytes:
+        dst = ImageData(*size, PixelType.U8x4)
+        Resizer().resize(src, dst, options)
+        return dst.get_buffer()
+
+    full_image, half_image = dst_images[3], dst_images[1]
+    assert full_image.get_buffer() == resize(src_image, (400, 300))
+    # Resized from the smallest image that is at least two times larger
+    assert half_image.get_buffer() == resize(full_image, (200, 150))
+    assert dst_images[2].get_buffer() == resize(half_image, (100, 75))
+    assert dst_images[0].get_buffer() == resize(half_image, (60, 45))
+    assert dst_images[4].get_buffer() == resize(src_image, (50, 150))
+
+
+@pytest.mark.parametrize(
+    ('pixel_type', 'use_alpha'),
+    [
+        (PixelType.U8x3, True),
+        (PixelType.U8x4, False),
+        (PixelType.U16x3, True),
+        (PixelType.F32, True),
+    ],
+)
+def test_resize_multi_with_shared_pass(thread_pool, pixel_type, use_alpha):
+    u8_pixel_type = {
+        PixelType.U8x3: PixelType.U8x3,
+        PixelType.U8x4: PixelType.U8x4,
+        PixelType.U16x3: PixelType.U8x3,
+        PixelType.F32: PixelType.U8,
+    }[pixel_type]
+    pixel_size = PIXEL_SIZE[u8_pixel_type]
+    pixels = bytes((x * 5 + y * 7 + x * y) % 256 for y in range(300) for x in range(400 * pixel_size))
+    src_image = ImageData(400, 300, u8_pixel_type, pixels).convert(pixel_type)
+    options = ResizeOptions(use_alpha=use_alpha, thread_pool=thread_pool, threading_threshold=0)
+    # Images have the same widths or heights, but aren't two times
+    # smaller than each other.
+    sizes = [(300, 170), (300, 250), (250, 170), (310, 250), (160, 45)]
+    dst_images = [ImageData(w, h, pixel_type) for w, h in sizes]
+    Resizer().resize_multi(src_image, dst_images, options)
+    for dst_image in dst_images:
+        expected_image = ImageData(dst_image.width, dst_image.height, pixel_type)
+        Resizer().resize(src_image, expected_image, options)
+        assert dst_image.get_buffer() == expected_image.get_buffer()
+
+
+@pytest.mark.parametrize(
+    ('src_pixel_type', 'options'),
+    [
+        (PixelType.U8x4, ResizeOptions(ResizeAlg.nearest())),
+        (PixelType.U8x4, ResizeOptions(crop_box=CropBox(10, 10, 300, 200))),
+        (PixelType.U8x4, ResizeOptions(dithering=Dithering.ordered)),
+        (PixelType.U16x4, ResizeOptions()),
+    ],
+)
+def test_resize_multi_without_cascade(src_pixel_type, options):
+    pixel_size = PIXEL_SIZE[src_pixel_type]
+    pixels = bytes((x * 5 + y * 7) % 256 for y in range(300) for x in range(400 * pixel_size))
+    src_image = ImageData(400, 300, src_pixel_type, pixels)
+    sizes = [(200, 150), (100, 75), (40, 30)]
+    dst_images = [ImageData(w, h, PixelType.U8x4) for w, h in sizes]
+    Resizer().resize_multi(src_image, dst_images, options)
+    for dst_image in dst_images:
+        expected_image = ImageData(dst_image.width, dst_image.height, PixelType.U8x4)
+        Resizer().resize(src_image, expected_image, options)
+        assert dst_image.get_buffer() == expected_image.get_buffer()
+
+    assert Resizer().resize_multi(src_image, [], options) == []
+
+
+@pytest.mark.parametrize(
+    ('levels', 'min_size', 'sizes'),
+    [
+        (None, 1, [(50, 18), (25, 9), (12, 4), (6, 2), (3, 1), (1, 1)]),
+        (3, 1, [(50, 18), (25, 9), (12, 4)]),
+        (None, 12, [(50, 18), (25, 9), (12, 4)]),
+        (0, 1, []),
+    ],
+)
+@pytest.mark.parametrize('filter_type', [FilterType.box, FilterType.lanczos3])
+@pytest.mark.parametrize('linear_light', [False, True])
+def test_build_pyramid(thread_pool, levels, min_size, sizes, filter_type, linear_light):
+    pixels = bytes((x * 5 + y * 7 + x * y) % 256 for y in range(37) for x in range(100 * 4))
+    src_image = ImageData(100, 37, PixelType.U8x4, pixels)
+    options = ResizeOptions(
+        thread_pool=thread_pool,
+        threading_threshold=0,
+        linear_light=linear_light,
+    )
+    pyramid = Resizer().build_pyramid(src_image, levels, filter_type, min_size, options)
+    assert [(level.width, level.height) for level in pyramid] == sizes
+
+    # Each level is resized from the previous one
+    level_options = options.copy()
+    level_options.resize_alg = ResizeAlg.convolution(filter_type)
+    prev_level = src_image
+    for level in pyramid:
+        assert level.pixel_type is PixelType.U8x4
+        expected_image = ImageData(level.width, level.height, PixelType.U8x4)
+        Resizer().resize(prev_level, expected_image, level_options)
+        assert level.get_buffer() == expected_image.get_buffer()
+        prev_level = level
+
+
+def test_build_pyramid_channel_order():
+    pixels = bytes((x * 5 + y * 7 + x * y) % 256 for y in range(40) for x in range(64 * 3))
+    src_image = ImageData(64, 40, PixelType.U8x3, pixels)
+    options = ResizeOptions(channel_order=[2, 1, 0])
+    pyramid = Resizer().build_pyramid(src_image, levels=4, options=options)
+    assert len(pyramid) == 4
+
+    # Channels of every level are reordered once
+    plain_pyramid = Resizer().build_pyramid(src_image, levels=4)
+    for level, plain_level in zip(pyramid, plain_pyramid):
+        assert level.pixel_type is PixelType.U8x3
+        plain_pixels = plain_level.get_buffer()
+        expected_pixels = bytearray(len(plain_pixels))
+        expected_pixels[0::3] = plain_pixels[2::3]
+        expected_pixels[1::3] = plain_pixels[1::3]
+        expected_pixels[2::3] = plain_pixels[0::3]
+        assert level.get_buffer() == expected_pixels
+
+
+def test_build_pyramid_background():
+    pixels = bytes([10, 20, 30, 0, 200, 100, 50, 255] * 32 * 16)
+    src_image = ImageData(32, 32, PixelType.U8x4, pixels)
+    options = ResizeOptions(background=[255, 255, 255])
+    pyramid = Resizer().build_pyramid(src_image, levels=3, options=options)
+    # Half of pixels are transparent in every level
+    for level in pyramid:
+        assert level.get_buffer()[:8] == bytes([227, 177, 152, 255] * 2)
+
+
+def test_build_pyramid_errors():
+    src_image = ImageData(16, 16, PixelType.U8x4)
+    with pytest.raises(ValueError, match='Minimal size of levels must be greater than zero'):
+        Resizer().build_pyramid(src_image, min_size=0)
+    assert Resizer().build_pyramid(ImageData(1, 1, PixelType.U8)) == []
+    assert Resizer().build_pyramid(ImageData(0, 0, PixelType.U8)) == []
+
+
+def _crop_image(image: ImageData, left: int, top: int, width: int, height: int) -> ImageData:
+    pixel_size = PIXEL_SIZE[image.pixel_type]
+    row_size = image.width * pixel_size
+    buffer = image.get_buffer()
+    pixels = b''.join(
+        buffer[y * row_size + left * pixel_size:y * row_size + (left + width) * pixel_size]
+        for y in range(top, top + height)
+    )
+    return ImageData(width, height, image.pixel_type, pixels)
+
+
+def _get_tile_levels(
+        src_image: ImageData,
+        max_level: int,
+        options: ResizeOptions,
+) -> List[ImageData]:
+    """Returns levels of tiles from the level 0 to the level
+    with the full size of the source image."""
+    levels = [src_image]
+    for _ in range(max_level):
+        prev_level = levels[-1]
+        width = (prev_level.width + 1) // 2
+        height = (prev_level.height + 1) // 2
+        level = ImageData(width, height, prev_level.pixel_type)
+        Resizer().resize(prev_level, level, options)
+        levels.append(level)
+    return levels[::-1]
+
+
+@pytest.mark.parametrize(
+    ('tile_size', 'overlap'),
+    [(32, 0), (32, 2), (16, 1), (256, 1)],
+)
+def test_tiles_deep_zoom(thread_pool, tile_size, overlap):
+    pixels = bytes((x * 5 + y * 7 + x * y) % 256 for y in range(37) for x in range(100 * 4))
+    src_image = ImageData(100, 37, PixelType.U8x4, pixels)
+    options = ResizeOptions(thread_pool=thread_pool, threading_threshold=0)
+    tiles = list(Resizer().tiles(src_image, tile_size, overlap, options=options))
+
+    level_options = options.copy()
+    level_options.resize_alg = ResizeAlg.convolution(FilterType.box)
+    # The level 0 has size 1x1 pixel
+    levels = _get_tile_levels(src_image, 7, level_options)
+    assert [(level.width, level.height) for level in levels] == [
+        (1, 1), (2, 1), (4, 2), (7, 3), (13, 5), (25, 10), (50, 19), (100, 37),
+    ]
+    expected_positions = []
+    for level_num in range(7, -1, -1):
+        level = levels[level_num]
+        cols = (level.width + tile_size - 1) // tile_size
+        rows = (level.height + tile_size - 1) // tile_size
+        expected_positions.extend(
+            (level_num, col, row) for row in range(rows) for col in range(cols)
+        )
+    assert [tile[:3] for tile in tiles] == expected_positions
+
+    for level_num, col, row, tile in tiles:
+        level = levels[level_num]
+        left = max(col * tile_size - overlap, 0)
+        top = max(row * tile_size - overlap, 0)
+        right = min((col + 1) * tile_size + overlap, level.width)
+        bottom = min((row + 1) * tile_size + overlap, level.height)
+        assert (tile.width, tile.height) == (right - left, bottom - top)
+        expected_tile = _crop_image(level, left, top, right - left, bottom - top)
+        assert tile.get_buffer() == expected_tile.get_buffer()
+
+
+def test_tiles_xyz():
+    pixels = bytes((x * 5 + y * 7 + x * y) % 256 for y in range(37) for x in range(100 * 3))
+    src_image = ImageData(100, 37, PixelType.U8x3, pixels)
+    tiles = list(Resizer().tiles(src_image, 32, layout=TileLayout.xyz))
+
+    level_options = ResizeOptions(ResizeAlg.convolution(FilterType.box))
+    # The level 0 fits into one tile
+    levels = _get_tile_levels(src_image, 2, level_options)
+    assert [(level.width, level.height) for level in levels] == [(25, 10), (50, 19), (100, 37)]
+    assert [tile[:3] for tile in tiles] == [
+        (2, 0, 0), (2, 1, 0), (2, 2, 0), (2, 3, 0),
+        (2, 0, 1), (2, 1, 1), (2, 2, 1), (2, 3, 1),
+        (1, 0, 0), (1, 1, 0),
+        (0, 0, 0),
+    ]
+
+    for level_num, col, row, tile in tiles:
+        assert (tile.width, tile.height) == (32, 32)
+        level = levels[level_num]
+        width = min(32, level.width - col * 32)
+        height = min(32, level.height - row * 32)
+        expected_tile = ImageData(32, 32, PixelType.U8x3)
+        region = _crop_image(level, col * 32, row * 32, width, height)
+        Resizer().resize(region, expected_tile, ResizeOptions(dst_region=(0, 0, width, height)))
+        # Pixels outside the level are transparent
+        assert tile.get_buffer() == expected_tile.get_buffer()
+
+
+@pytest.mark.parametrize('layout', [TileLayout.deep_zoom, TileLayout.xyz])
+def test_tiles_channel_order(layout):
+    pixels = bytes((x * 5 + y * 7 + x * y) % 256 for y in range(37) for x in range(100 * 3))
+    src_image = ImageData(100, 37, PixelType.U8x3, pixels)
+    options = ResizeOptions(channel_order=[2, 1, 0])
+    tiles = list(Resizer().tiles(src_image, 16, layout=layout, options=options))
+    plain_tiles = list(Resizer().tiles(src_image, 16, layout=layout))
+    assert len({tile[0] for tile in tiles}) >= 3
+
+    # Channels of tiles of every level are reordered once
+    assert [tile[:3] for tile in tiles] == [tile[:3] for tile in plain_tiles]
+    for (*_, tile), (*_, plain_tile) in zip(tiles, plain_tiles):
+        assert tile.pixel_type is PixelType.U8x3
+        plain_pixels = plain_tile.get_buffer()
+        expected_pixels = bytearray(len(plain_pixels))
+        expected_pixels[0::3] = plain_pixels[2::3]
+        expected_pixels[1::3] = plain_pixels[1::3]
+        expected_pixels[2::3] = plain_pixels[0::3]
+        assert tile.get_buffer() == expected_pixels
+
+
+def test_tiles_xyz_background():
+    pixels = bytes([10, 20, 30, 0, 200, 100, 50, 255] * 50 * 37)
+    src_image = ImageData(100, 37, PixelType.U8x4, pixels)
+    options = ResizeOptions(background=[255, 255, 255])
+    tiles = list(Resizer().tiles(src_image, 32, layout=TileLayout.xyz, options=options))
+    assert len({tile[0] for tile in tiles}) == 3
+
+    levels = {2: (100, 37), 1: (50, 19), 0: (25, 10)}
+    for level_num, col, row, tile in tiles:
+        level_width, level_height = levels[level_num]
+        width = min(32, level_width - col * 32)
+        height = min(32, level_height - row * 32)
+        buffer = tile.get_buffer()
+        for y in range(32):
+            row_pixels = buffer[y * 32 * 4:(y + 1) * 32 * 4]
+            # Pixels outside the level are filled with the background color
+            outside = row_pixels if y >= height else row_pixels[width * 4:]
+            assert outside == bytes([255, 255, 255, 255]) * (len(outside) // 4)
+            # Pixels of the level are composited onto the background
+            if y < height:
+                assert row_pixels[3:width * 4:4] == bytes([255]) * width
+
+
+@pytest.mark.parametrize('mode', ['RGB', 'RGBA', 'L'])
+def test_tiles_pil(mode):
+    pixel_type = {'RGB': PixelType.U8x3, 'RGBA': PixelType.U8x4, 'L': PixelType.U8}[mode]
+    pixel_size = PIXEL_SIZE[pixel_type]
+    pixels = bytes(
+        (x * 5 + y * 7 + x * y) % 256 for y in range(200) for x in range(300 * pixel_size)
+    )
+    src_image = Image.frombytes(mode, (300, 200), pixels)
+    image_data = ImageData(300, 200, pixel_type, pixels)
+    resizer = Resizer()
+    tiles = list(resizer.tiles_pil(src_image, 128, 1))
+    expected_tiles = list(resizer.tiles(image_data, 128, 1))
+    assert len(tiles) == len(expected_tiles)
+    for (*position, tile), (*expected_position, expected_tile) in zip(tiles, expected_tiles):
+        assert position == expected_position
+        assert tile.mode == mode
+        assert tile.size == (expected_tile.width, expected_tile.height)
+        assert tile.tobytes() == expected_tile.get_buffer()
+
+
+def test_tiles_errors():
+    src_image = ImageData(16, 16, PixelType.U8x4)
+    resizer = Resizer()
+    with pytest.raises(ValueError, match='Size of tiles must be greater than zero'):
+        resizer.tiles(src_image, 0).__next__()
+    with pytest.raises(ValueError, match="Overlap of tiles can't be used with XYZ layout"):
+        resizer.tiles(src_image, 8, 1, TileLayout.xyz).__next__()
+    with pytest.raises(ValueError, match='"CMYK" is unsupported mode of source PIL image'):
+        resizer.tiles_pil(Image.new('CMYK', (16, 16))).__next__()
+    assert list(resizer.tiles(ImageData(0, 0, PixelType.U8))) == []
+    assert [tile[:3] for tile in resizer.tiles(ImageData(1, 1, PixelType.U8))] == [(0, 0, 0)]
+
+
+@pytest.mark.parametrize(
+    ('src_pixel_type', 'dst_pixel_type', 'options'),
+    [
+        (PixelType.U8x4, PixelType.U8x4, None),
+        (PixelType.U8x4, PixelType.U8x4, ResizeOptions(fit_into_destination=True)),
+        (PixelType.U8x4, PixelType.U16x4, ResizeOptions(ResizeAlg.nearest())),
+        (PixelType.U8x3, PixelType.U8x3, ResizeOptions(dst_region=(2, 3, 8, 5))),
+    ],
+)
+def test_resize_plan(thread_pool, src_pixel_type, dst_pixel_type, options):
+    options = options.copy() if options else ResizeOptions()
+    options.thread_pool = thread_pool
+    options.threading_threshold = 0
+    plan = ResizePlan(40, 30, src_pixel_type, 16, 12, dst_pixel_type, options)
+    pixel_size = PIXEL_SIZE[src_pixel_type]
+    for i in range(3):
+        pixels = bytes(
+            (x * 5 + y * 7 + i * 50) % 256 for y in range(30) for x in range(40 * pixel_size)
+        )
+        src_image = ImageData(40, 30, src_pixel_type, pixels)
+        dst_image = ImageData(16, 12, dst_pixel_type)
+        report = plan.run(src_image, dst_image)
+        expected_image = ImageData(16, 12, dst_pixel_type)
+        expected_report = Resizer().resize(src_image, expected_image, options)
+        assert report == expected_report
+        assert dst_image.get_buffer() == expected_image.get_buffer()
+
+
+def test_resize_plan_errors():
+    # Types of pixels and options are checked while creating the plan
+    with pytest.raises(ValueError, match='Unknown type of pixels: 100'):
+        RustResizePlan((40, 30), 100, (16, 12), PixelType.U8x4.value)
+    with pytest.raises(RuntimeError, match='Pixel type of source image does not match'):
+        ResizePlan(40, 30, PixelType.U8x4, 16, 12, PixelType.U8x3)
+    with pytest.raises(ValueError, match='Region is out of boundaries'):
+        ResizePlan(
+            40, 30, PixelType.U8x4, 16, 12, options=ResizeOptions(dst_region=(10, 0, 8, 8))
+        )
+    for crop_box in [CropBox(0, 0, 0, 10), CropBox(0, 0, float('nan'), 10)]:
+        with pytest.raises(ValueError, match='must have finite values and positive sizes'):
+            ResizePlan(40, 30, PixelType.U8x4, 16, 12, options=ResizeOptions(crop_box=crop_box))
+
+    plan = ResizePlan(40, 30, PixelType.U8x4, 16, 12)
+    with pytest.raises(ValueError, match="source image doesn't match to the plan"):
+        plan.run(ImageData(40, 31, PixelType.U8x4), ImageData(16, 12, PixelType.U8x4))
+    with pytest.raises(ValueError, match="source image doesn't match to the plan"):
+        plan.run(ImageData(40, 30, PixelType.U16x4), ImageData(16, 12, PixelType.U8x4))
+    with pytest.raises(ValueError, match="destination image doesn't match to the plan"):
+        plan.run(ImageData(40, 30, PixelType.U8x4), ImageData(16, 13, PixelType.U8x4))
+
+
+def _get_bands(image: ImageData, band_height: int) -> List[ImageData]:
+    row_size = image.width * PIXEL_SIZE[image.pixel_type]
+    buffer = image.get_buffer()
+    bands = []
+    for top in range(0, image.height, band_height):
+        height = min(band_height, image.height - top)
+        pixels = buffer[top * row_size:(top + height) * row_size]
+        bands.append(ImageData(image.width, height, image.pixel_type, pixels))
+    return bands
+
+
+@pytest.mark.parametrize('band_height', [1, 7, 30])
+@pytest.mark.parametrize('crop_box', [None, CropBox(3.5, 2.25, 30, 20)])
+@pytest.mark.parametrize('dst_size', [(13, 9), (50, 70)])
+@pytest.mark.parametrize(
+    'resize_alg',
+    [
+        ResizeAlg.nearest(),
+        ResizeAlg.convolution(FilterType.bilinear),
+        ResizeAlg.convolution(FilterType.lanczos3),
+        ResizeAlg.convolution(FilterType.catmull_rom),
+    ],
+)
+def test_streaming_resizer(thread_pool, band_height, crop_box, dst_size, resize_alg):
+    pixels = bytes(
+        (x * 5 + y * 7 + x * y) % 256 if x % 4 != 3 else 128 + (x + y) % 128
+        for y in range(30)
+        for x in range(40 * 4)
+    )
+    src_image = ImageData(40, 30, PixelType.U8x4, pixels)
+    options = ResizeOptions(
+        resize_alg,
+        crop_box=crop_box,
+        thread_pool=thread_pool,
+        threading_threshold=0,
+    )
+    expected_image = ImageData(*dst_size, PixelType.U8x4)
+    Resizer().resize(src_image, expected_image, options)
+
+    resizer = StreamingResizer(40, 30, PixelType.U8x4, *dst_size, options=options)
+    dst_bands = list(resizer.resize(_get_bands(src_image, band_height)))
+    assert resizer.src_rows == 30
+    assert resizer.dst_rows == dst_size[1]
+    assert all(band.width == dst_size[0] for band in dst_bands)
+    assert sum(band.height for band in dst_bands) == dst_size[1]
+    buffer = b''.join(band.get_buffer() for band in dst_bands)
+    # Precision of fixed-point coefficients of the vertical pass depends
+    # on the max coefficient of calculated rows, so values of some pixels
+    # of bands may slightly differ from the result of resizing of the whole image.
+    assert max(abs(a - b) for a, b in zip(buffer, expected_image.get_buffer())) <= 2
+
+
+def test_streaming_resizer_incremental():
+    pixels = bytes((x + y) % 256 for y in range(1000) for x in range(100))
+    src_image = ImageData(100, 1000, PixelType.U8, pixels)
+    options = ResizeOptions(ResizeAlg.convolution(FilterType.lanczos3))
+    resizer = StreamingResizer(100, 1000, PixelType.U8, 10, 100, PixelType.U16, options)
+
+    bands = iter(_get_bands(src_image, 100))
+    dst_band = resizer.push(next(bands))
+    assert resizer.src_rows == 100
+    # Rows near the bottom of the band require the next rows of the source image
+    assert 0 < dst_band.height == resizer.dst_rows < 10
+    assert dst_band.pixel_type is PixelType.U16
+    dst_bands = [dst_band]
+    dst_bands.extend(resizer.resize(lambda: next(bands, None)))
+    assert resizer.src_rows == 1000
+    assert resizer.dst_rows == 100
+
+    expected_image = ImageData(10, 100, PixelType.U16)
+    Resizer().resize(src_image, expected_image, options)
+    buffer = b''.join(band.get_buffer() for band in dst_bands)
+    assert buffer == expected_image.get_buffer()
+
+
+def test_streaming_resizer_errors():
+    for options in [
+        ResizeOptions(ResizeAlg.super_sampling(FilterType.lanczos3, 2)),
+        ResizeOptions(dithering=Dithering.ordered),
+        ResizeOptions(bleed_alpha=True),
+        ResizeOptions(contain_in_destination=True),
+        ResizeOptions(dst_region=(0, 0, 5, 5)),
+        ResizeOptions(border_mode=BorderMode.clamp),
+    ]:
+        with pytest.raises(ValueError, match="can't be used by the streaming resizer"):
+            StreamingResizer(20, 20, PixelType.U8x4, 10, 10, options=options)
+
+    with pytest.raises(ValueError, match='must be inside the source image'):
+        StreamingResizer(
+            20, 20, PixelType.U8x4, 10, 10, options=ResizeOptions(crop_box=CropBox(-1, 0, 10, 10))
+        )
+
+    resizer = StreamingResizer(20, 20, PixelType.U8x4, 10, 10)
+    with pytest.raises(ValueError, match='same width and type of pixels'):
+        resizer.push(ImageData(10, 5, PixelType.U8x4))
+    with pytest.raises(ValueError, match='same width and type of pixels'):
+        resizer.push(ImageData(20, 5, PixelType.U8x3))
+    with pytest.raises(ValueError, match='greater than height of the source image'):
+        resizer.push(ImageData(20, 21, PixelType.U8x4))
+    resizer.push(ImageData(20, 5, PixelType.U8x4))
+    with pytest.raises(ValueError, match='Not all rows of the source image were pushed'):
+        resizer.finish()
+
+
+@pytest.mark.parametrize('dst_mode', ('RGB', 'RGBA', 'RGBa', 'L'))
+@pytest.mark.parametrize('src_mode', ('RGB', 'RGBA', 'RGBa', 'L'))
+def test_image_modes_in_linear_light(source_image: Image.Image, src_mode, dst_mode):
+    if source_image.mode != src_mode:
+        source_image = source_image.convert(src_mode)
+    resizer = Resizer()
+    dst_size = (int(round(source_image.width / 8)), int(round(source_image.height / 8)))
+    dst_image = Image.new(dst_mode, dst_size)
+    resizer.resize_pil(
+        source_image,
+        dst_image,
+        ResizeOptions(
+            ResizeAlg.convolution(FilterType.lanczos3),
+            linear_light=True,
+        ),
+    )
+    assert dst_image.mode == dst_mode
+
+    save_result(
+        dst_image,
+        Path('resize') / 'linear_light',
+        f'nasa-{src_mode}_into_{dst_mode}.png',
+    )
@@ -2,11 +2,29 @@
 :Authors: cykooz
 :Date: 12.08.2021
 """
+import copy
+import pickle
+import struct
 import weakref
 
+import pytest
 from PIL import Image
 
+from cykooz_resizer import (
+    BorderMode,
+    CropBox,
+    Dithering,
+    EdgeMode,
+    FilterType,
+    GrayscaleWeights,
+    ImageData,
+    PixelType,
+    ResizeAlg,
+    ResizeOptions,
+    ResizerThreadPool,
+)
 from cykooz_resizer.rust_lib import PilImageWrapper
+from cykooz_resizer.structs import PIXEL_SIZE
 
 
 def test_pillow_image_view_gc():
@@ -25,3 +43,504 @@ def test_pillow_image_view_gc():
     assert image_ref() is not None
     del _image_view
     assert image_ref() is None
+
+
+def _unpack(fmt: str, image: ImageData) -> tuple:
+    buffer = image.get_buffer()
+    return struct.unpack(f'<{len(buffer) // struct.calcsize(fmt)}{fmt}', buffer)
+
+
+def test_image_convert_integers():
+    src_image = ImageData(256, 4, PixelType.U8x4, bytes(range(256)) * 4 * 4)
+    assert src_image.pixel_type is PixelType.U8x4
+
+    u16_image = src_image.convert(PixelType.U16x4)
+    assert u16_image.pixel_type is PixelType.U16x4
+    assert (u16_image.width, u16_image.height) == (256, 4)
+    assert _unpack('H', u16_image) == tuple(v * 257 for v in range(256)) * 16
+
+    # Back into U8 without losses
+    assert u16_image.convert(PixelType.U8x4).get_buffer() == src_image.get_buffer()
+
+    gray_image = ImageData(256, 1, PixelType.U8, bytes(range(256)))
+    i32_image = gray_image.convert(PixelType.I32)
+    assert _unpack('i', i32_image) == tuple(round(v * (2 ** 31 - 1) / 255) for v in range(256))
+    assert i32_image.convert(PixelType.U8).get_buffer() == gray_image.get_buffer()
+
+    u16_pixels = struct.pack('<256H', *(v * 257 for v in range(256)))
+    u16_image = ImageData(256, 1, PixelType.U16, u16_pixels)
+    i32_image = u16_image.convert(PixelType.I32)
+    assert _unpack('i', i32_image)[-1] == 2 ** 31 - 1
+    assert i32_image.convert(PixelType.U16).get_buffer() == u16_image.get_buffer()
+    assert i32_image.convert(PixelType.U8).get_buffer() == gray_image.get_buffer()
+
+
+def test_image_convert_floats():
+    src_image = ImageData(256, 1, PixelType.U8x3, bytes(range(256)) * 3)
+    f32_image = src_image.convert(PixelType.F32x3)
+    values = _unpack('f', f32_image)
+    expected = (bytes(range(256)) * 3)[:256 * 3]
+    for value, v in zip(values, expected):
+        assert value == pytest.approx(v / 255)
+    assert f32_image.convert(PixelType.U8x3).get_buffer() == src_image.get_buffer()
+
+    i32_image = ImageData(2, 1, PixelType.I32, struct.pack('<2i', 0, 2 ** 31 - 1))
+    assert _unpack('f', i32_image.convert(PixelType.F32)) == (0., 1.)
+
+
+def test_image_convert_u8_i32_f32_round_trip():
+    # All conversions of I32 components use the same scale
+    gray_image = ImageData(256, 1, PixelType.U8, bytes(range(256)))
+    f32_image = gray_image.convert(PixelType.I32).convert(PixelType.F32)
+    expected = _unpack('f', gray_image.convert(PixelType.F32))
+    assert _unpack('f', f32_image) == pytest.approx(expected, abs=1e-9)
+    assert f32_image.convert(PixelType.U8).get_buffer() == gray_image.get_buffer()
+
+    # F32 components have not enough precision to restore I32 values exactly
+    i32_image = gray_image.convert(PixelType.F32).convert(PixelType.I32)
+    expected = _unpack('i', gray_image.convert(PixelType.I32))
+    assert _unpack('i', i32_image) == pytest.approx(expected, abs=2 ** 7)
+    assert i32_image.convert(PixelType.U8).get_buffer() == gray_image.get_buffer()
+
+
+def test_image_convert_negative_i32():
+    values = (-2 ** 31 + 1, -2 ** 30, -1, 0, 1, 2 ** 30, 2 ** 31 - 1)
+    i32_image = ImageData(len(values), 1, PixelType.I32, struct.pack(f'<{len(values)}i', *values))
+    f32_image = i32_image.convert(PixelType.F32)
+    for value, v in zip(_unpack('f', f32_image), values):
+        assert value == pytest.approx(v / (2 ** 31 - 1))
+    assert _unpack('i', f32_image.convert(PixelType.I32)) == values
+
+    f32_image = ImageData(3, 1, PixelType.F32, struct.pack('<3f', -2., -1., -0.5))
+    assert _unpack('i', f32_image.convert(PixelType.I32)) == (-2 ** 31 + 1, -2 ** 31 + 1, -2 ** 30)
+
+
+def test_image_convert_clamp():
+    pixels = struct.pack('<4f', -0.5, 0.5, 1.0, 2.0)
+    src_image = ImageData(4, 1, PixelType.F32, pixels)
+    assert src_image.convert(PixelType.U8).get_buffer() == bytes([0, 128, 255, 255])
+    assert _unpack('H', src_image.convert(PixelType.U16)) == (0, 32768, 65535, 65535)
+
+    with pytest.raises(RuntimeError, match='out of range of U8 pixels'):
+        src_image.convert(PixelType.U8, clamp=False)
+    # F32 -> F32 doesn't need clamping
+    assert src_image.convert(PixelType.F32, clamp=False).get_buffer() == pixels
+
+    valid_image = ImageData(2, 1, PixelType.F32, struct.pack('<2f', 0., 1.))
+    assert valid_image.convert(PixelType.U8, clamp=False).get_buffer() == b'\x00\xff'
+
+    nan_image = ImageData(1, 1, PixelType.F32, struct.pack('<f', float('nan')))
+    with pytest.raises(RuntimeError, match='out of range'):
+        nan_image.convert(PixelType.U16, clamp=False)
+
+    negative_image = ImageData(1, 1, PixelType.I32, struct.pack('<i', -1))
+    assert negative_image.convert(PixelType.U8).get_buffer() == b'\x00'
+    with pytest.raises(RuntimeError, match='out of range'):
+        negative_image.convert(PixelType.U8, clamp=False)
+
+
+def test_image_convert_into_existing_image():
+    src_image = ImageData(64, 64, PixelType.U16, bytes(range(256)) * 32)
+    dst_image = ImageData(64, 64, PixelType.U8)
+    result = src_image.convert(PixelType.U8, dst_image=dst_image)
+    assert result is dst_image
+    assert dst_image.get_buffer() == bytes(range(1, 256, 2)) * 32
+
+    with pytest.raises(ValueError, match='is not equal to'):
+        src_image.convert(PixelType.F32, dst_image=dst_image)
+    with pytest.raises(RuntimeError, match='dimensions'):
+        src_image.convert(PixelType.U8, dst_image=ImageData(64, 32, PixelType.U8))
+    with pytest.raises(RuntimeError, match='Unsupported combination'):
+        src_image.convert(PixelType.U8x2)
+
+
+def test_image_convert_with_thread_pool():
+    src_image = ImageData(256, 256, PixelType.U8x4, bytes(range(256)) * 256 * 4)
+    expected = src_image.convert(PixelType.F32x4).get_buffer()
+    with ResizerThreadPool(num_threads=3) as thread_pool:
+        dst_image = src_image.convert(
+            PixelType.F32x4,
+            thread_pool=thread_pool,
+            threading_threshold=0,
+        )
+        assert dst_image.get_buffer() == expected
+    with pytest.raises(RuntimeError, match='Thread pool is shut down'):
+        src_image.convert(PixelType.F32x4, thread_pool=thread_pool, threading_threshold=0)
+
+
+@pytest.mark.parametrize(
+    'dithering',
+    [Dithering.ordered, Dithering.blue_noise, Dithering.floyd_steinberg],
+)
+def test_image_convert_with_dithering(dithering):
+    # Value between two levels of U8 components
+    src_image = ImageData(64, 64, PixelType.U16x2, struct.pack('<H', 128 * 257 + 128) * 2 * 64 * 64)
+    assert set(src_image.convert(PixelType.U8x2).get_buffer()) == {128}
+
+    dst_image = src_image.convert(PixelType.U8x2, dithering=dithering)
+    buffer = dst_image.get_buffer()
+    assert set(buffer) == {128, 129}
+    assert sum(buffer) / len(buffer) == pytest.approx(128.5, abs=0.02)
+
+    # Exact levels of U8 components are not changed
+    u8_image = ImageData(256, 4, PixelType.U8x4, bytes(range(256)) * 4 * 4)
+    for pixel_type in (PixelType.U16x4, PixelType.F32x4):
+        src_image = u8_image.convert(pixel_type)
+        dst_image = src_image.convert(PixelType.U8x4, dithering=dithering)
+        assert dst_image.get_buffer() == u8_image.get_buffer()
+
+    # Values out of range
+    src_image = ImageData(4, 1, PixelType.F32, struct.pack('<4f', -0.5, 2.0, 1.0, float('nan')))
+    dst_image = src_image.convert(PixelType.U8, dithering=dithering)
+    assert dst_image.get_buffer() == bytes([0, 255, 255, 0])
+    with pytest.raises(RuntimeError, match='out of range of U8 pixels'):
+        src_image.convert(PixelType.U8, clamp=False, dithering=dithering)
+
+
+@pytest.mark.parametrize(
+    'dithering',
+    [Dithering.ordered, Dithering.blue_noise, Dithering.floyd_steinberg],
+)
+def test_image_convert_with_dithering_and_thread_pool(dithering):
+    values = [(x * 7 + y * 3) / 1000 for y in range(256) for x in range(256)]
+    src_image = ImageData(256, 256, PixelType.F32, struct.pack(f'<{len(values)}f', *values))
+    expected = src_image.convert(PixelType.U8, dithering=dithering).get_buffer()
+    with ResizerThreadPool(num_threads=3) as thread_pool:
+        dst_image = src_image.convert(
+            PixelType.U8,
+            dithering=dithering,
+            thread_pool=thread_pool,
+            threading_threshold=0,
+        )
+    assert dst_image.get_buffer() == expected
+
+
+def test_image_swizzle():
+    src_image = ImageData(2, 1, PixelType.U8x4, bytes([1, 2, 3, 4, 5, 6, 7, 8]))
+    # BGRA -> RGBA
+    assert src_image.swizzle([2, 1, 0, 3]).get_buffer() == bytes([3, 2, 1, 4, 7, 6, 5, 8])
+    # ARGB -> RGBA
+    assert src_image.swizzle([1, 2, 3, 0]).get_buffer() == bytes([2, 3, 4, 1, 6, 7, 8, 5])
+    # BGRA -> RGB
+    dst_image = src_image.swizzle([2, 1, 0])
+    assert dst_image.pixel_type is PixelType.U8x3
+    assert dst_image.get_buffer() == bytes([3, 2, 1, 7, 6, 5])
+    # Gray -> RGB
+    gray_image = ImageData(2, 1, PixelType.F32, struct.pack('<2f', 0.25, 0.5))
+    dst_image = gray_image.swizzle([0, 0, 0])
+    assert dst_image.pixel_type is PixelType.F32x3
+    assert _unpack('f', dst_image) == (0.25, 0.25, 0.25, 0.5, 0.5, 0.5)
+
+    with pytest.raises(RuntimeError, match='Index of channel 4 is out of range of U8x4 pixels'):
+        src_image.swizzle([0, 1, 4])
+    with pytest.raises(ValueError, match="can't have 5 channels"):
+        src_image.swizzle([0, 1, 2, 3, 0])
+    with pytest.raises(ValueError, match="can't have 0 channels"):
+        src_image.swizzle([])
+
+
+@pytest.mark.parametrize(
+    ('pixel_type', 'fmt', 'opaque'),
+    [
+        (PixelType.U8x3, 'B', 255),
+        (PixelType.U16, 'H', 65535),
+        (PixelType.F32x3, 'f', 1.0),
+    ],
+)
+def test_image_add_and_drop_alpha(pixel_type, fmt, opaque):
+    channels = PIXEL_SIZE[pixel_type] // struct.calcsize(fmt)
+    values = tuple(range(channels * 4))
+    src_image = ImageData(2, 2, pixel_type, struct.pack(f'<{len(values)}{fmt}', *values))
+
+    rgba_image = src_image.add_alpha()
+    assert PIXEL_SIZE[rgba_image.pixel_type] == PIXEL_SIZE[pixel_type] // channels * (channels + 1)
+    rgba_values = _unpack(fmt, rgba_image)
+    assert rgba_values[channels::channels + 1] == (opaque,) * 4
+    assert rgba_image.drop_alpha().get_buffer() == src_image.get_buffer()
+
+    rgba_image = src_image.add_alpha(7, threading_threshold=0)
+    assert _unpack(fmt, rgba_image)[channels::channels + 1] == (7,) * 4
+
+    with pytest.raises(ValueError, match='Image already has alpha channel'):
+        rgba_image.add_alpha()
+    with pytest.raises(ValueError, match='Image has not alpha channel'):
+        src_image.drop_alpha()
+
+
+@pytest.mark.parametrize(
+    ('pixel_type', 'fmt'),
+    [
+        (PixelType.U8x4, 'B'),
+        (PixelType.U16x2, 'H'),
+        (PixelType.F32x3, 'f'),
+    ],
+)
+def test_image_split_and_merge(pixel_type, fmt):
+    channels = PIXEL_SIZE[pixel_type] // struct.calcsize(fmt)
+    # Width of rows is not a multiple of width of SIMD registers
+    values = tuple(i % 256 for i in range(channels * 67 * 64))
+    src_image = ImageData(67, 64, pixel_type, struct.pack(f'<{len(values)}{fmt}', *values))
+
+    channel_images = src_image.split(threading_threshold=0)
+    assert len(channel_images) == channels
+    for channel, channel_image in enumerate(channel_images):
+        assert PIXEL_SIZE[channel_image.pixel_type] == struct.calcsize(fmt)
+        assert _unpack(fmt, channel_image) == values[channel::channels]
+
+    with ResizerThreadPool(num_threads=3) as thread_pool:
+        parallel_images = src_image.split(thread_pool=thread_pool, threading_threshold=0)
+    assert [i.get_buffer() for i in parallel_images] == [i.get_buffer() for i in channel_images]
+
+    merged_image = ImageData.merge(channel_images)
+    assert merged_image.pixel_type is pixel_type
+    assert merged_image.get_buffer() == src_image.get_buffer()
+
+    # Merge in other order
+    merged_image = ImageData.merge(channel_images[::-1])
+    assert merged_image.get_buffer() == src_image.swizzle(range(channels)[::-1]).get_buffer()
+
+
+def test_image_merge_into_other_pixel_type():
+    alpha = ImageData(2, 1, PixelType.U8, bytes([0, 255]))
+    gray = ImageData(2, 1, PixelType.U8, bytes([51, 102]))
+    merged_image = ImageData.merge([gray, gray, gray, alpha], PixelType.F32x4)
+    assert merged_image.pixel_type is PixelType.F32x4
+    assert _unpack('f', merged_image) == pytest.approx((0.2, 0.2, 0.2, 0., 0.4, 0.4, 0.4, 1.))
+
+    with pytest.raises(ValueError, match='At least one channel is required'):
+        ImageData.merge([])
+    with pytest.raises(ValueError, match="can't have 5 channels"):
+        ImageData.merge([gray] * 5)
+    with pytest.raises(ValueError, match="F32x3 pixels can't be merged from 4 channels"):
+        ImageData.merge([gray] * 4, PixelType.F32x3)
+    with pytest.raises(ValueError, match='must have one channel'):
+        ImageData.merge([gray, merged_image])
+    with pytest.raises(ValueError, match='must have the same type of pixels'):
+        ImageData.merge([gray, gray.convert(PixelType.U16)])
+    with pytest.raises(ValueError, match='must have the same dimensions'):
+        ImageData.merge([gray, ImageData(1, 2, PixelType.U8)])
+
+
+def test_image_to_grayscale():
+    src_image = ImageData(2, 1, PixelType.U8x3, bytes([255, 0, 0, 10, 20, 30]))
+    gray_image = src_image.to_grayscale()
+    assert gray_image.pixel_type is PixelType.U8
+    assert gray_image.get_buffer() == bytes([76, 18])
+    gray_image = src_image.to_grayscale(GrayscaleWeights.rec709)
+    assert gray_image.get_buffer() == bytes([54, 19])
+    # Custom weights, values out of range are clamped
+    gray_image = src_image.to_grayscale((1., 1., 1.), threading_threshold=0)
+    assert gray_image.get_buffer() == bytes([255, 60])
+
+    # Alpha channel is preserved
+    pixels = struct.pack('<8H', 65535, 65535, 65535, 1000, 0, 0, 65535, 2000)
+    src_image = ImageData(2, 1, PixelType.U16x4, pixels)
+    gray_image = src_image.to_grayscale((0.5, 0.25, 0.25))
+    assert gray_image.pixel_type is PixelType.U16x2
+    assert _unpack('H', gray_image) == (65535, 1000, 16384, 2000)
+
+    src_image = ImageData(1, 1, PixelType.F32x3, struct.pack('<3f', 1., 0.5, 0.))
+    gray_image = src_image.to_grayscale((0.5, 0.5, 0.5))
+    assert gray_image.pixel_type is PixelType.F32
+    assert _unpack('f', gray_image) == (0.75,)
+
+    with pytest.raises(ValueError, match="U8x2 pixels can't be converted into grayscale"):
+        ImageData(1, 1, PixelType.U8x2).to_grayscale()
+    with pytest.raises(ValueError, match='Invalid weights of channels'):
+        src_image.to_grayscale((float('nan'), 0.5, 0.5))
+
+
+@pytest.mark.parametrize('pixel_type', [PixelType.U8x3, PixelType.U16x4, PixelType.F32x3])
+def test_image_is_grayscale(thread_pool, pixel_type):
+    gray_image = ImageData(64, 64, PixelType.U8, bytes(range(256)) * 16)
+    channels = [gray_image] * 3
+    if pixel_type is PixelType.U16x4:
+        channels.append(ImageData(64, 64, PixelType.U8, bytes([100]) * 64 * 64))
+    image = ImageData.merge(channels, pixel_type)
+    assert image.is_grayscale(thread_pool, threading_threshold=0)
+
+    # Change blue channel of the last pixel
+    pixels = bytearray(range(256)) * 16
+    pixels[-1] = 0
+    channels[2] = ImageData(64, 64, PixelType.U8, bytes(pixels))
+    image = ImageData.merge(channels, pixel_type)
+    assert not image.is_grayscale(thread_pool, threading_threshold=0)
+
+    assert not gray_image.is_grayscale()
+
+
+@pytest.mark.parametrize('pixel_type', [PixelType.U8x2, PixelType.U16x4, PixelType.F32x4])
+def test_image_is_opaque(thread_pool, pixel_type):
+    channels = [ImageData(64, 64, PixelType.U8, bytes(range(256)) * 16)]
+    if pixel_type is not PixelType.U8x2:
+        channels *= 3
+    alpha = ImageData(64, 64, PixelType.U8, bytes([255]) * 64 * 64)
+    image = ImageData.merge(channels + [alpha], pixel_type)
+    assert image.is_opaque(thread_pool, threading_threshold=0)
+
+    alpha = ImageData(64, 64, PixelType.U8, bytes([255]) * (64 * 64 - 1) + bytes([254]))
+    image = ImageData.merge(channels + [alpha], pixel_type)
+    assert not image.is_opaque(thread_pool, threading_threshold=0)
+
+    # Images without alpha channel are always opaque
+    assert channels[0].is_opaque()
+    assert ImageData(0, 0, pixel_type).is_opaque()
+
+
+def test_image_bleed_alpha(thread_pool):
+    pixels = bytes([255, 0, 0, 255] + [9, 9, 9, 0] * 4)
+    image = ImageData(5, 1, PixelType.U8x4, pixels)
+    bled_image = image.bleed_alpha(thread_pool=thread_pool, threading_threshold=0)
+    assert bled_image.get_buffer() == bytes([255, 0, 0, 255] + [255, 0, 0, 0] * 4)
+    # Source image is not changed
+    assert image.get_buffer() == pixels
+
+    bled_image = image.bleed_alpha(2, thread_pool=thread_pool, threading_threshold=0)
+    assert bled_image.get_buffer() == bytes(
+        [255, 0, 0, 255] + [255, 0, 0, 0] * 2 + [9, 9, 9, 0] * 2
+    )
+
+    # Colors of visible neighbours are averaged
+    pixels = struct.pack(
+        '<12H',
+        65535, 0, 0, 1,
+        9, 9, 9, 0,
+        0, 0, 65535, 65535,
+    )
+    image = ImageData(3, 1, PixelType.U16x4, pixels)
+    bled_image = image.bleed_alpha()
+    assert _unpack('H', bled_image)[4:8] == (32768, 0, 32768, 0)
+
+    pixels = struct.pack('<6f', 0.5, 0.1, 0.25, 0., 1., 0.)
+    image = ImageData(1, 3, PixelType.F32x2, pixels)
+    assert _unpack('f', image.bleed_alpha()) == pytest.approx((0.5, 0.1, 0.5, 0., 0.5, 0.))
+
+    # Transparent image is not changed
+    image = ImageData(3, 3, PixelType.U8x4, bytes([7, 8, 9, 0]) * 9)
+    assert image.bleed_alpha().get_buffer() == image.get_buffer()
+
+    with pytest.raises(ValueError, match='Image has not alpha channel'):
+        ImageData(3, 3, PixelType.U8x3).bleed_alpha()
+    with pytest.raises(ValueError, match='must be greater than zero'):
+        image.bleed_alpha(0)
+
+
+def test_resize_options_pickle():
+    thread_pool = ResizerThreadPool(num_threads=2)
+    options = ResizeOptions(
+        ResizeAlg.super_sampling(FilterType.mitchell, 3),
+        use_alpha=False,
+        crop_box=CropBox(1, 2, 30.5, 40),
+        thread_pool=thread_pool,
+        threading_threshold=1000,
+    )
+    for copied in (copy.copy(options), copy.deepcopy(options)):
+        assert copied == options
+        assert hash(copied) == hash(options)
+        assert copied.thread_pool == thread_pool
+
+    restored = pickle.loads(pickle.dumps(options))
+    assert restored.resize_alg == options.resize_alg
+    assert restored.use_alpha is False
+    assert restored.crop_box == CropBox(1, 2, 30.5, 40)
+    assert restored.threading_threshold == 1000
+    assert restored.thread_pool.num_threads == 2
+    # The restored options use another thread pool
+    assert restored != options
+    restored.thread_pool = thread_pool
+    assert restored == options
+
+    options = ResizeOptions(fit_into_destination=(0.2, 0.8))
+    restored = pickle.loads(pickle.dumps(options))
+    assert restored == options
+    assert restored.get_fit_into_destination_centering() == (0.2, 0.8)
+
+    options = ResizeOptions(contain_in_destination=(0.2, 0.8), padding=(1, 2, 3))
+    restored = pickle.loads(pickle.dumps(options))
+    assert restored == options
+    assert restored.get_contain_in_destination_centering() == (0.2, 0.8)
+    assert restored.padding == (1, 2, 3)
+
+    options = ResizeOptions(dst_region=(1, 2, 3, 4))
+    restored = pickle.loads(pickle.dumps(options))
+    assert restored == options
+    assert restored.dst_region == (1, 2, 3, 4)
+
+    options = ResizeOptions(
+        crop_box=CropBox(-1, -2, 30, 40),
+        edge_mode=EdgeMode.constant,
+        edge_color=(1, 2, 3),
+        border_mode=BorderMode.wrap,
+    )
+    restored = pickle.loads(pickle.dumps(options))
+    assert restored == options
+    assert restored.crop_box == CropBox(-1, -2, 30, 40)
+    assert restored.edge_mode is EdgeMode.constant
+    assert restored.edge_color == (1, 2, 3)
+    assert restored.border_mode is BorderMode.wrap
+    with pytest.raises(ValueError, match='Unknown border mode: 10'):
+        options.rust_options.set_border_mode(10)
+
+    options = ResizeOptions(dithering=Dithering.blue_noise)
+    restored = pickle.loads(pickle.dumps(options))
+    assert restored == options
+    assert restored.dithering is Dithering.blue_noise
+    assert restored != ResizeOptions()
+
+    # Options with colors are hashable
+    options = ResizeOptions(
+        background=(255, 255, 255),
+        padding=(1, 2, 3),
+        crop_box=CropBox(-1, -2, 30, 40),
+        edge_mode=EdgeMode.constant,
+        edge_color=(4, 5, 6, 7),
+    )
+    restored = pickle.loads(pickle.dumps(options))
+    assert hash(restored) == hash(options)
+    assert hash(options) != hash(ResizeOptions(background=(255, 255, 0)))
+    assert {options: 1}[restored] == 1
+    assert {copy.copy(options): 1}[options] == 1
+
+    assert ResizeOptions() != ResizeOptions(use_alpha=False)
+    cache = {ResizeOptions(): 1, ResizeOptions(use_alpha=False): 2}
+    assert cache[ResizeOptions()] == 1
+    assert 'use_alpha=False' in repr(ResizeOptions(use_alpha=False))
+
+
+def test_resize_options_corrupted_state():
+    state = ResizeOptions(border_mode=BorderMode.wrap).rust_options.__getstate__()
+    assert isinstance(state, dict)
+    assert state['version'] == 1
+    assert state['border_mode'] == 3
+    corrupted_states = [
+        ({'version': 10}, 'Unsupported version of state of options: 10'),
+        ({'algorithm': 10}, 'Unknown resize algorithm: 10'),
+        ({'filter_type': 10}, 'Unknown resize algorithm: 2 with filter 10'),
+        (
+            {'crop_box': (0.0, 0.0, 10.0, 10.0), 'fit_into_destination': (0.5, 0.5)},
+            'Only one kind of cropping of the source image can be set',
+        ),
+        ({'border_mode': 10}, 'Unknown border mode: 10'),
+    ]
+    for changes, message in corrupted_states:
+        options = ResizeOptions().rust_options
+        with pytest.raises(ValueError, match=message):
+            options.__setstate__({**state, **changes})
+        # Options are not changed by the invalid state
+        assert options == ResizeOptions().rust_options
+
+    options = ResizeOptions().rust_options
+    state = options.__getstate__()
+    del state['border_mode']
+    with pytest.raises(KeyError):
+        options.__setstate__(state)
+
+
+def test_resize_alg_pickle():
+    alg = ResizeAlg.super_sampling(FilterType.lanczos3, 4)
+    restored = pickle.loads(pickle.dumps(alg))
+    assert restored == alg
+    assert restored.multiplicity == 4
+    assert hash(restored) == hash(alg)
+    assert copy.copy(alg) == alg
+    assert {alg: 1}[restored] == 1
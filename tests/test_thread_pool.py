@@ -0,0 +1,289 @@
+"""
+:Authors: cykooz
+:Date: 18.10.2026
+"""
+import copy
+import multiprocessing
+import os
+import pickle
+import sys
+import threading
+from pathlib import Path
+from typing import List, Tuple
+
+import pytest
+
+from cykooz_resizer import (
+    AlphaMulDiv,
+    FilterType,
+    ImageData,
+    PixelType,
+    ResizeAlg,
+    ResizeOptions,
+    Resizer,
+    ResizerThreadPool,
+)
+from utils import Checksum, get_image_checksum
+
+
+SRC_PIXELS = bytes(range(256)) * 256 * 4
+
+
+def _resize_with_pool(thread_pool: ResizerThreadPool) -> Checksum:
+    src_image = ImageData(256, 256, PixelType.U8x4, SRC_PIXELS)
+    dst_image = ImageData(64, 64, PixelType.U8x4)
+    Resizer().resize(
+        src_image,
+        dst_image,
+        ResizeOptions(
+            ResizeAlg.convolution(FilterType.lanczos3),
+            thread_pool=thread_pool,
+            threading_threshold=0,
+        ),
+    )
+    return get_image_checksum(dst_image.get_buffer())
+
+
+def _child_process(thread_pool: ResizerThreadPool, queue):
+    queue.put((thread_pool.current_num_threads, _resize_with_pool(thread_pool)))
+
+
+@pytest.mark.skipif(sys.platform == 'win32', reason='fork() is not supported')
+def test_thread_pool_after_fork():
+    thread_pool = ResizerThreadPool(num_threads=4)
+    expected_checksum = _resize_with_pool(thread_pool)
+
+    ctx = multiprocessing.get_context('fork')
+    queue = ctx.Queue()
+    process = ctx.Process(target=_child_process, args=(thread_pool, queue))
+    process.start()
+    process.join(timeout=30)
+    if process.is_alive():
+        process.kill()
+        pytest.fail('Resizing with thread-pool in the child process hangs')
+    assert process.exitcode == 0
+
+    num_threads, checksum = queue.get(timeout=5)
+    assert num_threads == 4
+    assert checksum == expected_checksum
+
+    # The pool is still usable in the parent process
+    assert _resize_with_pool(thread_pool) == expected_checksum
+
+
+def _resize_without_pool() -> Checksum:
+    src_image = ImageData(256, 256, PixelType.U8x4, SRC_PIXELS)
+    dst_image = ImageData(64, 64, PixelType.U8x4)
+    options = ResizeOptions(ResizeAlg.convolution(FilterType.lanczos3))
+    Resizer().resize(src_image, dst_image, options)
+    return get_image_checksum(dst_image.get_buffer())
+
+
+def _resize_multi_without_pool() -> List[Checksum]:
+    src_image = ImageData(256, 256, PixelType.U8x4, SRC_PIXELS)
+    dst_images = [
+        ImageData(128, 128, PixelType.U8x4),
+        ImageData(100, 256, PixelType.U8x4),
+        ImageData(64, 256, PixelType.U8x4),
+    ]
+    Resizer().resize_multi(src_image, dst_images)
+    return [get_image_checksum(image.get_buffer()) for image in dst_images]
+
+
+def _tiles_without_pool() -> List[Tuple[int, int, int, Checksum]]:
+    src_image = ImageData(256, 256, PixelType.U8x4, SRC_PIXELS)
+    return [
+        (level, col, row, get_image_checksum(tile.get_buffer()))
+        for level, col, row, tile in Resizer().tiles(src_image, 64, 1)
+    ]
+
+
+def _child_process_without_pool(func, queue):
+    queue.put(func())
+
+
+@pytest.mark.skipif(sys.platform == 'win32', reason='fork() is not supported')
+@pytest.mark.parametrize(
+    'func',
+    [_resize_without_pool, _resize_multi_without_pool, _tiles_without_pool],
+)
+def test_without_pool_after_fork(func):
+    expected_result = func()
+    ctx = multiprocessing.get_context('fork')
+    queue = ctx.Queue()
+    process = ctx.Process(target=_child_process_without_pool, args=(func, queue))
+    # The child process is forked from a thread which isn't a worker
+    # of rayon's global pool, so the pool has no workers in the child.
+    thread = threading.Thread(target=process.start)
+    thread.start()
+    thread.join(timeout=30)
+    process.join(timeout=30)
+    if process.is_alive():
+        process.kill()
+        pytest.fail('Processing without thread-pool in the child process hangs')
+    assert process.exitcode == 0
+    assert queue.get(timeout=5) == expected_result
+
+
+def test_thread_pool_config():
+    thread_pool = ResizerThreadPool(
+        num_threads=3,
+        thread_name_prefix='resizer',
+        stack_size=4 * 1024 * 1024,
+    )
+    assert thread_pool.num_threads == 3
+    assert thread_pool.current_num_threads == 3
+    assert thread_pool.thread_name_prefix == 'resizer'
+    assert thread_pool.stack_size == 4 * 1024 * 1024
+    assert thread_pool.use_current_thread is False
+    assert thread_pool.is_shutdown is False
+
+    thread_pool = ResizerThreadPool()
+    assert thread_pool.num_threads is None
+    assert thread_pool.thread_name_prefix is None
+    assert thread_pool.stack_size is None
+
+
+@pytest.mark.skipif(sys.platform != 'linux', reason='requires /proc file system')
+def test_thread_pool_thread_names():
+    def get_thread_names():
+        task_dir = Path('/proc/self/task')
+        return {(p / 'comm').read_text().strip() for p in task_dir.iterdir()}
+
+    with ResizerThreadPool(num_threads=2, thread_name_prefix='rz') as thread_pool:
+        _resize_with_pool(thread_pool)
+        assert {'rz-0', 'rz-1'} <= get_thread_names()
+    assert not {'rz-0', 'rz-1'} & get_thread_names()
+
+
+def test_thread_pool_use_current_thread():
+    results = []
+
+    def target():
+        thread_pool = ResizerThreadPool(num_threads=2, use_current_thread=True)
+        assert thread_pool.use_current_thread is True
+        results.append(_resize_with_pool(thread_pool))
+        thread_pool.shutdown()
+
+    thread = threading.Thread(target=target)
+    thread.start()
+    thread.join(timeout=30)
+    assert results == [_resize_with_pool(ResizerThreadPool(num_threads=2))]
+
+
+def test_thread_pool_use_importing_thread():
+    # The thread that imported the module is a worker of the global pool,
+    # so a new worker thread is spawned instead of it.
+    with ResizerThreadPool(num_threads=2, use_current_thread=True) as thread_pool:
+        assert thread_pool.use_current_thread is True
+        assert thread_pool.current_num_threads == 2
+        expected_checksum = _resize_with_pool(ResizerThreadPool(num_threads=2))
+        assert _resize_with_pool(thread_pool) == expected_checksum
+
+
+def test_thread_pool_shutdown():
+    thread_pool = ResizerThreadPool(num_threads=2)
+    with thread_pool as pool:
+        assert pool is thread_pool
+        _resize_with_pool(pool)
+    assert thread_pool.is_shutdown is True
+    with pytest.raises(RuntimeError, match='Thread pool is shut down'):
+        _resize_with_pool(thread_pool)
+    # Repeated shutdown does nothing
+    thread_pool.shutdown()
+
+
+@pytest.mark.skipif(sys.platform != 'linux', reason='supported only on Linux')
+def test_thread_pool_cpus():
+    available_cpus = sorted(os.sched_getaffinity(0))
+    cpus = available_cpus[-2:]
+    thread_pool = ResizerThreadPool(num_threads=4, cpus=cpus)
+    assert thread_pool.cpus == cpus
+    assert thread_pool.affinity is None
+    assert thread_pool.cpu_affinity == [[cpus[i % len(cpus)]] for i in range(4)]
+    # Pinned pool works as usual
+    assert _resize_with_pool(thread_pool) == _resize_with_pool(None)
+
+    with pytest.raises(RuntimeError, match='is not available'):
+        ResizerThreadPool(num_threads=2, cpus=[max(available_cpus) + 1])
+    with pytest.raises(RuntimeError, match='must not be empty'):
+        ResizerThreadPool(num_threads=2, cpus=[])
+
+
+@pytest.mark.skipif(sys.platform != 'linux', reason='supported only on Linux')
+@pytest.mark.parametrize('affinity', ['compact', 'spread'])
+def test_thread_pool_affinity_strategy(affinity):
+    available_cpus = sorted(os.sched_getaffinity(0))
+    num_threads = 2
+    thread_pool = ResizerThreadPool(num_threads=num_threads, affinity=affinity)
+    assert thread_pool.affinity == affinity
+    cpu_affinity = thread_pool.cpu_affinity
+    assert len(cpu_affinity) == num_threads
+    assert all(len(cpus) == 1 for cpus in cpu_affinity)
+    if len(available_cpus) >= num_threads:
+        if affinity == 'compact':
+            expected = available_cpus[:num_threads]
+        else:
+            expected = [
+                available_cpus[i * len(available_cpus) // num_threads]
+                for i in range(num_threads)
+            ]
+        assert [cpus[0] for cpus in cpu_affinity] == expected
+
+
+def test_thread_pool_invalid_affinity():
+    with pytest.raises(RuntimeError, match='Unknown CPU affinity strategy'):
+        ResizerThreadPool(num_threads=2, affinity='random')
+
+
+def test_threading_threshold():
+    options = ResizeOptions()
+    assert options.threading_threshold > 0
+    options = ResizeOptions(threading_threshold=100)
+    assert options.threading_threshold == 100
+    assert options.copy().threading_threshold == 100
+    with pytest.raises(ValueError):
+        options.threading_threshold = -1
+
+    # The shut-down pool raises an error if it is used to process an image.
+    thread_pool = ResizerThreadPool(num_threads=2)
+    thread_pool.shutdown()
+    src_image = ImageData(256, 256, PixelType.U8x4, SRC_PIXELS)
+    dst_image = ImageData(64, 64, PixelType.U8x4)
+    resizer = Resizer()
+    alg = ResizeAlg.convolution(FilterType.lanczos3)
+
+    # Small images are resized without thread pool
+    resizer.resize(src_image, dst_image, ResizeOptions(alg, thread_pool=thread_pool))
+    with pytest.raises(RuntimeError, match='Thread pool is shut down'):
+        resizer.resize(
+            src_image,
+            dst_image,
+            ResizeOptions(alg, thread_pool=thread_pool, threading_threshold=0),
+        )
+
+    mul_div = AlphaMulDiv()
+    mul_div.multiply_alpha_inplace(dst_image, thread_pool)
+    with pytest.raises(RuntimeError, match='Thread pool is shut down'):
+        mul_div.multiply_alpha_inplace(dst_image, thread_pool, threading_threshold=0)
+
+
+def test_thread_pool_pickle():
+    thread_pool = ResizerThreadPool(num_threads=2, thread_name_prefix='pickled')
+    restored = pickle.loads(pickle.dumps(thread_pool))
+    assert restored is not thread_pool
+    assert restored != thread_pool
+    assert restored.num_threads == 2
+    assert restored.thread_name_prefix == 'pickled'
+    assert restored.stack_size is None
+    assert restored.use_current_thread is False
+    assert _resize_with_pool(restored) == _resize_with_pool(thread_pool)
+
+    # Copies share the same pool
+    assert copy.copy(thread_pool) == thread_pool
+    assert copy.deepcopy(thread_pool) == thread_pool
+    assert hash(copy.copy(thread_pool)) == hash(thread_pool)
+    assert repr(thread_pool) == (
+        "ResizerThreadPool(num_threads=2, thread_name_prefix=\"pickled\")"
+    )
+